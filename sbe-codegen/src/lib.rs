@@ -0,0 +1,223 @@
+//! # sbe-codegen
+//!
+//! `sbe-codegen` generates zero-copy Rust decoders and encoders of SBE schemas without the Java
+//! SBE tool.
+//!
+//! The generated module has a decoder and an encoder for every message and composite, a Rust
+//! enum for every enum and a newtype for every set. Decoders of messages borrow the buffer and
+//! read fields added in a later version than the acting version as `None`. Decoders and encoders
+//! check the length of the buffer and fail with `Truncated` instead of panicking. Encoders of var
+//! data fail with `EncodeError::TooLong` if the data exceeds the maximum of its length.
+
+mod names;
+mod rust;
+
+use sbe_schema::{SbeSchemaError, Schema, ValueError};
+use std::result::Result as StdResult;
+use thiserror::Error;
+
+/// Result type returned from methods that have [`enum@self::GenerateError`].
+pub type Result<T> = StdResult<T, GenerateError>;
+
+/// Errors that can occur when generating code of a schema.
+#[derive(Error, Debug)]
+pub enum GenerateError {
+	/// The layout of the schema could not be computed.
+	#[error(transparent)]
+	Schema(#[from] SbeSchemaError),
+	/// A value in the schema does not match its type.
+	#[error(transparent)]
+	Value(#[from] ValueError),
+	/// The referenced type is not defined.
+	#[error("{path}: unresolved type `{name}`")]
+	Unresolved {
+		/// The referenced name.
+		name: String,
+		/// Path of the element with the reference.
+		path: String,
+	},
+	/// The schema uses an encoding the generator does not support.
+	#[error("{path}: {message}")]
+	Unsupported {
+		/// Description of the problem.
+		message: String,
+		/// Path of the element.
+		path: String,
+	},
+}
+
+/// Generate the source of a Rust module with the decoders and encoders of the schema.
+pub fn generate(schema: &Schema) -> Result<String> {
+	rust::Generator::new(schema)?.generate()
+}
+
+/// Name of the generated module, the package of the schema in snake case.
+pub fn module_name(schema: &Schema) -> String {
+	names::module(schema.package.as_deref().unwrap_or("schema"))
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{
+		fs,
+		path::{Path, PathBuf},
+		process::Command,
+	};
+
+	use sbe_schema::SchemaLoader;
+
+	use super::*;
+
+	fn load(file: &str) -> Schema {
+		let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+			.join("../sbe-schema/tests/resources")
+			.join(file);
+		SchemaLoader::new().load(path).unwrap()
+	}
+
+	/// Directory of the compiled binary, removed on drop.
+	struct Compiled(PathBuf);
+
+	impl Compiled {
+		fn binary(&self) -> PathBuf {
+			self.0.join("main")
+		}
+	}
+
+	impl Drop for Compiled {
+		fn drop(&mut self) {
+			let _ = fs::remove_dir_all(&self.0);
+		}
+	}
+
+	/// Compile the generated module with the given main into a binary.
+	fn compile(name: &str, code: &str, main: &str) -> Compiled {
+		let dir = std::env::temp_dir().join(format!("sbe-codegen-{}-{name}", std::process::id()));
+		fs::create_dir_all(&dir).unwrap();
+		let compiled = Compiled(dir);
+		let source = compiled.0.join("main.rs");
+		fs::write(&source, format!("mod generated {{\n{code}\n}}\n\nfn main() {{\n{main}\n}}\n"))
+			.unwrap();
+		let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+		let output = Command::new(rustc)
+			.args(["--edition", "2021", "-o"])
+			.arg(compiled.binary())
+			.arg(&source)
+			.output()
+			.unwrap();
+		assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+		compiled
+	}
+
+	#[test]
+	fn test_generated_code_compiles() {
+		for file in [
+			"basic-schema-constant-header-field.xml",
+			"code-generation-schema.xml",
+			"composite-elements-schema.xml",
+			"composite-offsets-schema.xml",
+			"constant-enum-fields.xml",
+			"example-bigendian-test-schema.xml",
+			"group-with-data-schema.xml",
+			"json-printer-test-schema.xml",
+			"nested-group-schema.xml",
+			"since-version-filter-schema.xml",
+		] {
+			let code = generate(&load(file)).unwrap();
+			compile(file.trim_end_matches(".xml"), &code, "");
+		}
+	}
+
+	#[test]
+	fn test_round_trip() {
+		let code = generate(&load("example-schema.xml")).unwrap();
+		let main = r#"
+			use generated::*;
+			let mut buf = [0u8; 256];
+			let mut car = CarEncoder::header(&mut buf, 0).unwrap();
+			car.serial_number(1234).model_year(2013).available(BooleanType::T).code(Model::A);
+			car.vehicle_code(b"abcdef").extras(*OptionalExtras::default().set_cruise_control(true));
+			car.engine().capacity(2000).num_cylinders(4).booster().boost_type(BoostType::NITROUS);
+			let mut fuel = car.fuel_figures(2).unwrap();
+			fuel.next_entry().unwrap().unwrap().speed(30).mpg(35.9).usage_description(b"Urban Cycle").unwrap();
+			fuel.next_entry().unwrap().unwrap().speed(55).mpg(49.0).usage_description(b"Combined Cycle").unwrap();
+			let mut performance = car.performance_figures(1).unwrap();
+			let entry = performance.next_entry().unwrap().unwrap().octane_rating(95);
+			entry.acceleration(1).unwrap().next_entry().unwrap().unwrap().mph(30).seconds(4.0);
+			car.manufacturer(b"Honda").unwrap().model(b"Civic VTi").unwrap().activation_code(b"abcdef").unwrap();
+			let length = HEADER_LENGTH + car.encoded_length();
+
+			let mut car = CarDecoder::header(&buf[..length], 0).unwrap().unwrap();
+			assert_eq!((car.serial_number(), car.model_year()), (1234, 2013));
+			assert_eq!((car.available(), car.code(), car.discounted_model()), (BooleanType::T, Model::A, Model::C));
+			assert_eq!(car.vehicle_code(), b"abcdef");
+			assert!(car.extras().cruise_control() && !car.extras().sun_roof());
+			let engine = car.engine();
+			assert_eq!((engine.capacity(), engine.max_rpm(), engine.fuel()), (2000, 9000, &b"Petrol"[..]));
+			assert_eq!(engine.booster().boost_type(), BoostType::NITROUS);
+			let mut fuel = car.fuel_figures().unwrap();
+			let mut usage = Vec::new();
+			while let Some(entry) = fuel.next_entry().unwrap() {
+				usage.push((entry.speed(), entry.usage_description().unwrap().to_vec()));
+			}
+			assert_eq!(usage, [(30, b"Urban Cycle".to_vec()), (55, b"Combined Cycle".to_vec())]);
+			let mut performance = car.performance_figures().unwrap();
+			let entry = performance.next_entry().unwrap().unwrap();
+			assert_eq!(entry.octane_rating(), 95);
+			let mut acceleration = entry.acceleration().unwrap();
+			assert_eq!(acceleration.next_entry().unwrap().unwrap().seconds(), 4.0);
+			assert_eq!(car.manufacturer().unwrap(), b"Honda");
+			assert_eq!(car.model().unwrap(), b"Civic VTi");
+			assert_eq!(car.activation_code().unwrap(), b"abcdef");
+			assert_eq!(HEADER_LENGTH + car.encoded_length(), length);
+
+			// truncated buffers fail instead of panicking
+			assert!(CarDecoder::header(&buf[..4], 0).is_err());
+			assert!(CarDecoder::header(&buf[..HEADER_LENGTH + 10], 0).is_err());
+			let mut car = CarDecoder::header(&buf[..length - 1], 0).unwrap().unwrap();
+			let mut fuel = car.fuel_figures().unwrap();
+			while let Some(entry) = fuel.next_entry().unwrap() {
+				entry.usage_description().unwrap();
+			}
+			let mut performance = car.performance_figures().unwrap();
+			while let Some(entry) = performance.next_entry().unwrap() {
+				let mut acceleration = entry.acceleration().unwrap();
+				while acceleration.next_entry().unwrap().is_some() {}
+			}
+			car.manufacturer().unwrap();
+			car.model().unwrap();
+			let error = car.activation_code().unwrap_err();
+			assert_eq!((error.required, error.available), (length, length - 1));
+			assert!(CarEncoder::header(&mut buf[..HEADER_LENGTH], 0).is_err());
+			let block = HEADER_LENGTH + CarEncoder::BLOCK_LENGTH as usize;
+			let mut car = CarEncoder::header(&mut buf[..block], 0).unwrap();
+			assert!(car.fuel_figures(0).is_err());
+			let mut car = CarEncoder::header(&mut buf[..block + 4], 0).unwrap();
+			let mut fuel = car.fuel_figures(1).unwrap();
+			assert!(fuel.next_entry().is_err());
+			println!("ok");
+		"#;
+		let compiled = compile("round-trip", &code, main);
+		let output = Command::new(compiled.binary()).output().unwrap();
+		assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+	}
+
+	#[test]
+	fn test_var_data_too_long() {
+		let code = generate(&load("basic-variable-length-schema.xml")).unwrap();
+		let main = r#"
+			use generated::*;
+			let mut buf = [0u8; 512];
+			let mut message = TestMessage1Encoder::header(&mut buf, 0).unwrap();
+			let error = message.encrypted_new_password(&[0; 300]).unwrap_err();
+			assert_eq!(error, EncodeError::TooLong { length: 300, max: 254 });
+			message.encrypted_new_password(&[0; 254]).unwrap();
+			let mut message = TestMessage1Encoder::header(&mut buf[..HEADER_LENGTH + 10], 0).unwrap();
+			let error = message.encrypted_new_password(&[0; 20]).unwrap_err();
+			assert!(matches!(error, EncodeError::Truncated(_)), "{error}");
+		"#;
+		let compiled = compile("too-long", &code, main);
+		let output = Command::new(compiled.binary()).output().unwrap();
+		assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+	}
+}
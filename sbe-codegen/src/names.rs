@@ -0,0 +1,91 @@
+//! Rust identifiers of schema names.
+
+const KEYWORDS: &[&str] = &[
+	"as", "async", "await", "box", "break", "const", "continue", "crate", "dyn", "else", "enum",
+	"extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+	"mut", "pub", "ref", "return", "self", "static", "struct", "super", "trait", "true", "try",
+	"type", "unsafe", "use", "where", "while", "yield",
+];
+
+/// Names of the methods of the generated decoders and encoders.
+const RESERVED: &[&str] =
+	&["wrap", "header", "count", "next_entry", "encoded_length", "acting_version"];
+
+/// Method name of a field, e.g. `serialNumber` becomes `serial_number`.
+pub(crate) fn snake_case(name: &str) -> String {
+	let chars = name.chars().collect::<Vec<_>>();
+	let mut snake = String::new();
+	for (i, &c) in chars.iter().enumerate() {
+		if !c.is_ascii_alphanumeric() {
+			snake.push('_');
+			continue;
+		}
+		let previous = i.checked_sub(1).map(|i| chars[i]);
+		let next = chars.get(i + 1);
+		let boundary = c.is_ascii_uppercase() &&
+			previous.is_some_and(|p| {
+				p.is_ascii_lowercase() ||
+					p.is_ascii_digit() ||
+					(p.is_ascii_uppercase() && next.is_some_and(|n| n.is_ascii_lowercase()))
+			});
+		if boundary && !snake.ends_with('_') {
+			snake.push('_');
+		}
+		snake.push(c.to_ascii_lowercase());
+	}
+	escape(snake, KEYWORDS.iter().chain(RESERVED))
+}
+
+/// Type name of a schema type, e.g. `messageHeader` becomes `MessageHeader`.
+pub(crate) fn camel_case(name: &str) -> String {
+	let camel = name
+		.split(|c: char| !c.is_ascii_alphanumeric())
+		.filter(|part| !part.is_empty())
+		.map(|part| {
+			let mut chars = part.chars();
+			let first = chars.next().map(|c| c.to_ascii_uppercase());
+			first.into_iter().chain(chars).collect::<String>()
+		})
+		.collect::<String>();
+	escape(camel, ["Self"].iter())
+}
+
+/// Variant name of a valid value, kept as written in the schema.
+pub(crate) fn variant(name: &str) -> String {
+	let name = name.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect();
+	escape(name, KEYWORDS.iter().chain(&["Self"]))
+}
+
+/// Module name of a package, e.g. `composite.offsets` becomes `composite_offsets`.
+pub(crate) fn module(package: &str) -> String {
+	package.split('.').map(snake_case).collect::<Vec<_>>().join("_")
+}
+
+fn escape<'a>(mut name: String, reserved: impl Iterator<Item = &'a &'a str>) -> String {
+	if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+		name.insert(0, '_');
+	}
+	if reserved.into_iter().any(|r| *r == name) {
+		name.push('_');
+	}
+	name
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_names() {
+		assert_eq!(snake_case("serialNumber"), "serial_number");
+		assert_eq!(snake_case("TagGroup1"), "tag_group1");
+		assert_eq!(snake_case("URLPath"), "url_path");
+		assert_eq!(snake_case("MDEntryPx"), "md_entry_px");
+		assert_eq!(snake_case("type"), "type_");
+		assert_eq!(snake_case("count"), "count_");
+		assert_eq!(camel_case("messageHeader"), "MessageHeader");
+		assert_eq!(camel_case("group_size"), "GroupSize");
+		assert_eq!(variant("TURBO"), "TURBO");
+		assert_eq!(module("composite.offsets.test"), "composite_offsets_test");
+	}
+}
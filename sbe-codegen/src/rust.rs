@@ -0,0 +1,1388 @@
+//! Zero-copy Rust decoders and encoders of a schema.
+
+use std::collections::HashSet;
+
+use sbe_schema::{
+	build_vtable, layout, ByteOrder, Composite, CompositeMember, Data, DataLayout, EnumType, Field,
+	FieldLayout, Group, GroupLayout, MemberLayout, Presence, PrimitiveType, Schema, SchemaLayout,
+	SetType, Type, VTable, VTableObject, Value,
+};
+
+use crate::{
+	names::{camel_case, snake_case, variant},
+	GenerateError, Result,
+};
+
+/// What a field or a composite member encodes.
+enum Kind {
+	/// A primitive value or an array of them, `null` is set for optional scalars.
+	Primitive { primitive: PrimitiveType, length: u32, null: Option<Value> },
+	/// A constant, not encoded on the wire.
+	Constant { ty: String, value: String },
+	/// A value of the named enum, optional ones are initialized to `NullVal`.
+	Enum { name: String, primitive: PrimitiveType, optional: bool },
+	/// A bitset of the named set.
+	Set { name: String, primitive: PrimitiveType },
+	/// The named composite.
+	Composite { name: String },
+}
+
+/// A field or a composite member at an offset of a block.
+struct Slot {
+	name: String,
+	method: String,
+	offset: u32,
+	size: u32,
+	since: u32,
+	kind: Kind,
+}
+
+/// Offset and primitive type of a member of a composite, or its constant value.
+struct Member {
+	offset: u32,
+	primitive: PrimitiveType,
+	constant: Option<Value>,
+	/// The `maxValue` or the default maximum of an integer member.
+	max: Option<i128>,
+}
+
+impl Member {
+	fn ty(&self) -> &'static str {
+		rust_type(self.primitive)
+	}
+
+	/// Expression reading the member from `buf` at `base`.
+	fn read(&self, base: &str) -> String {
+		match &self.constant {
+			Some(value) => literal(value),
+			None => format!("get::<{}>(buf, {})", self.ty(), at(base, self.offset)),
+		}
+	}
+}
+
+/// The members of the message header the decoders and encoders use.
+struct Header {
+	block_length: Option<Member>,
+	template_id: Option<Member>,
+	schema_id: Option<Member>,
+	version: Option<Member>,
+}
+
+/// Groups of a block with the names of their decoders or encoders, generated after the block.
+type Nested<'m, 'l> = Vec<(String, &'m Group, &'l GroupLayout)>;
+
+/// The struct the accessors of a block are generated for.
+struct Block<'n> {
+	/// Name of the decoder or encoder struct.
+	name: &'n str,
+	/// The place of the limit, `self.limit` or `*self.limit`.
+	limit: &'static str,
+}
+
+pub(crate) struct Generator<'s> {
+	schema: &'s Schema,
+	vtable: VTable<'s>,
+	layout: SchemaLayout,
+	header: Header,
+	version_ty: &'static str,
+	out: String,
+	indent: usize,
+}
+
+impl<'s> Generator<'s> {
+	pub(crate) fn new(schema: &'s Schema) -> Result<Self> {
+		let layout = layout(schema)?;
+		let mut generator = Self {
+			schema,
+			vtable: build_vtable(schema),
+			layout,
+			header: Header {
+				block_length: None,
+				template_id: None,
+				schema_id: None,
+				version: None,
+			},
+			version_ty: "u16",
+			out: String::new(),
+			indent: 0,
+		};
+		let name = schema.header_type_name();
+		let member = |g: &Self, member| g.member(name, member).ok();
+		generator.header = Header {
+			block_length: member(&generator, "blockLength"),
+			template_id: member(&generator, "templateId"),
+			schema_id: member(&generator, "schemaId"),
+			version: member(&generator, "version"),
+		};
+		generator.version_ty = generator.header.version.as_ref().map_or("u16", Member::ty);
+		Ok(generator)
+	}
+
+	pub(crate) fn generate(mut self) -> Result<String> {
+		self.prelude();
+		let mut seen = HashSet::new();
+		for e in self.schema.enums() {
+			self.enum_type(e, &mut seen)?;
+		}
+		for s in self.schema.sets() {
+			self.set_type(s, &mut seen)?;
+		}
+		let composites = self.layout.composites.clone();
+		for (composite, layout) in self.schema.composites().zip(&composites) {
+			self.composite(composite, &layout.members, layout.size, &mut seen)?;
+		}
+		let messages = self.layout.messages.clone();
+		for (message, layout) in self.schema.messages().zip(&messages) {
+			let name = camel_case(&message.name);
+			let fingerprint = self
+				.schema
+				.message_fingerprint(message.id)
+				.map(|f| f.to_string())
+				.unwrap_or_default();
+			let fields = self.slots(&message.name, message.fields(), &layout.fields)?;
+
+			self.line(&format!("/// Decoder of the `{}` message.", message.name));
+			self.line("#[derive(Debug)]");
+			self.open(&format!("pub struct {name}Decoder<'a> {{"));
+			self.line("buf: &'a [u8],");
+			self.line("offset: usize,");
+			self.line("acting_block_length: usize,");
+			self.line(&format!("acting_version: {},", self.version_ty));
+			self.line("limit: usize,");
+			self.close("}");
+			self.line("");
+			self.open(&format!("impl<'a> {name}Decoder<'a> {{"));
+			self.message_constants(message.id, layout.block_length, &fingerprint);
+			self.line(
+				"/// Decode the message at the offset, encoded with the given block length and",
+			);
+			self.line("/// schema version.");
+			self.line("/// Fails if the buffer is shorter than the block.");
+			self.open(&format!(
+				"pub fn wrap(buf: &'a [u8], offset: usize, acting_block_length: usize, acting_version: {}) -> Result<Self, Truncated> {{",
+				self.version_ty
+			));
+			self.line("let limit = offset + acting_block_length;");
+			self.line(&format!(
+				"check(buf, offset + acting_block_length.max({}))?;",
+				required_block_length(&fields, "acting_version")
+			));
+			self.line("Ok(Self { buf, offset, acting_block_length, acting_version, limit })");
+			self.close("}");
+			self.line("");
+			self.decoder_header();
+			self.line("/// Schema version the message was encoded with.");
+			self.open(&format!("pub fn acting_version(&self) -> {} {{", self.version_ty));
+			self.line("self.acting_version");
+			self.close("}");
+			self.line("");
+			self.line("/// Length of the message read so far, the full length once every group and var data");
+			self.line("/// was read.");
+			self.open("pub fn encoded_length(&self) -> usize {");
+			self.line("self.limit - self.offset");
+			self.close("}");
+			let block = Block { name: &format!("{name}Decoder"), limit: "self.limit" };
+			let groups = self.decoder_members(
+				&block,
+				&fields,
+				message.groups(),
+				&layout.groups,
+				message.data(),
+				&layout.data,
+			)?;
+			self.close("}");
+			self.line("");
+			for (name, group, layout) in groups {
+				self.group_decoder(&name, group, layout)?;
+			}
+
+			self.line(&format!("/// Encoder of the `{}` message.", message.name));
+			self.line("#[derive(Debug)]");
+			self.open(&format!("pub struct {name}Encoder<'a> {{"));
+			self.line("buf: &'a mut [u8],");
+			self.line("offset: usize,");
+			self.line("limit: usize,");
+			self.close("}");
+			self.line("");
+			self.open(&format!("impl<'a> {name}Encoder<'a> {{"));
+			self.message_constants(message.id, layout.block_length, &fingerprint);
+			self.line(
+				"/// Encode the message at the offset, the block is zeroed and optional fields are",
+			);
+			self.line("/// set to null. Fails if the buffer is shorter than the block.");
+			self.open("pub fn wrap(buf: &'a mut [u8], offset: usize) -> Result<Self, Truncated> {");
+			self.line("let limit = offset + Self::BLOCK_LENGTH as usize;");
+			self.line("check(buf, limit)?;");
+			self.line("buf[offset..limit].fill(0);");
+			self.nulls(&fields);
+			self.line("Ok(Self { buf, offset, limit })");
+			self.close("}");
+			self.line("");
+			self.encoder_header();
+			self.line("/// Length of the message encoded so far.");
+			self.open("pub fn encoded_length(&self) -> usize {");
+			self.line("self.limit - self.offset");
+			self.close("}");
+			let block = Block { name: &format!("{name}Encoder"), limit: "self.limit" };
+			let groups = self.encoder_members(
+				&block,
+				&fields,
+				message.groups(),
+				&layout.groups,
+				message.data(),
+				&layout.data,
+			)?;
+			self.close("}");
+			self.line("");
+			for (name, group, layout) in groups {
+				self.group_encoder(&name, group, layout)?;
+			}
+		}
+		Ok(self.out)
+	}
+
+	fn prelude(&mut self) {
+		let schema = self.schema;
+		let package = schema.package.as_deref().unwrap_or("schema");
+		let (from, to) = match schema.byte_order() {
+			ByteOrder::LittleEndian => ("from_le_bytes", "to_le_bytes"),
+			ByteOrder::BigEndian => ("from_be_bytes", "to_be_bytes"),
+		};
+		let id_ty = self.header.schema_id.as_ref().map_or("u16", Member::ty);
+		self.line(&format!(
+			"//! Decoders and encoders of the `{package}` schema, id {} version {}.",
+			schema.id.unwrap_or_default(),
+			schema.version
+		));
+		self.line("//!");
+		self.line("//! Generated by `sbe schema generate --engine native`, do not edit.");
+		self.line("#![allow(dead_code, missing_docs, non_camel_case_types, clippy::all)]");
+		self.line("");
+		self.line("/// Id of the schema.");
+		self.line(&format!("pub const SCHEMA_ID: {id_ty} = {};", schema.id.unwrap_or_default()));
+		self.line("/// Version of the schema.");
+		self.line(&format!("pub const SCHEMA_VERSION: {} = {};", self.version_ty, schema.version));
+		self.line("/// Fingerprint of the wire format of the schema.");
+		self.line(&format!("pub const SCHEMA_FINGERPRINT: &str = \"{}\";", schema.fingerprint()));
+		self.line("/// Encoded length of the message header.");
+		self.line(&format!("pub const HEADER_LENGTH: usize = {};", self.layout.header_length));
+		self.line("");
+		self.line("/// Primitive value encoded in the byte order of the schema.");
+		self.open("trait Primitive: Copy {");
+		self.line("fn read(buf: &[u8], offset: usize) -> Self;");
+		self.line("fn write(self, buf: &mut [u8], offset: usize);");
+		self.close("}");
+		self.line("");
+		self.open("macro_rules! primitive {");
+		self.open("($($t:ty),*) => {$(");
+		self.open("impl Primitive for $t {");
+		self.line("#[inline]");
+		self.open("fn read(buf: &[u8], offset: usize) -> Self {");
+		self.line("let mut bytes = [0; std::mem::size_of::<$t>()];");
+		self.line("bytes.copy_from_slice(&buf[offset..offset + std::mem::size_of::<$t>()]);");
+		self.line(&format!("<$t>::{from}(bytes)"));
+		self.close("}");
+		self.line("");
+		self.line("#[inline]");
+		self.open("fn write(self, buf: &mut [u8], offset: usize) {");
+		self.line(&format!("let bytes = self.{to}();"));
+		self.line("buf[offset..offset + bytes.len()].copy_from_slice(&bytes);");
+		self.close("}");
+		self.close("}");
+		self.close(")*};");
+		self.close("}");
+		self.line("");
+		self.line("primitive!(u8, i8, u16, i16, u32, i32, u64, i64, f32, f64);");
+		self.line("");
+		self.line("#[inline]");
+		self.open("fn get<T: Primitive>(buf: &[u8], offset: usize) -> T {");
+		self.line("T::read(buf, offset)");
+		self.close("}");
+		self.line("");
+		self.line("#[inline]");
+		self.open("fn put<T: Primitive>(buf: &mut [u8], offset: usize, value: T) {");
+		self.line("value.write(buf, offset)");
+		self.close("}");
+		self.line("");
+		self.line("/// The buffer is too short for the message.");
+		self.line("#[derive(Debug, Clone, Copy, PartialEq, Eq)]");
+		self.open("pub struct Truncated {");
+		self.line("/// Length the buffer needs at least.");
+		self.line("pub required: usize,");
+		self.line("/// Length of the buffer.");
+		self.line("pub available: usize,");
+		self.close("}");
+		self.line("");
+		self.open("impl std::fmt::Display for Truncated {");
+		self.open("fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {");
+		self.line("write!(f, \"buffer of {} bytes is truncated, {} bytes required\", self.available, self.required)");
+		self.close("}");
+		self.close("}");
+		self.line("");
+		self.line("impl std::error::Error for Truncated {}");
+		self.line("");
+		self.line("/// A var data could not be encoded.");
+		self.line("#[derive(Debug, Clone, Copy, PartialEq, Eq)]");
+		self.open("pub enum EncodeError {");
+		self.line("/// The buffer is too short for the var data.");
+		self.line("Truncated(Truncated),");
+		self.line("/// The var data is longer than the maximum of its length.");
+		self.open("TooLong {");
+		self.line("/// Length of the var data.");
+		self.line("length: usize,");
+		self.line("/// Maximum of the length.");
+		self.line("max: u64,");
+		self.close("},");
+		self.close("}");
+		self.line("");
+		self.open("impl From<Truncated> for EncodeError {");
+		self.open("fn from(truncated: Truncated) -> Self {");
+		self.line("EncodeError::Truncated(truncated)");
+		self.close("}");
+		self.close("}");
+		self.line("");
+		self.open("impl std::fmt::Display for EncodeError {");
+		self.open("fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {");
+		self.open("match self {");
+		self.line("EncodeError::Truncated(truncated) => truncated.fmt(f),");
+		self.line("EncodeError::TooLong { length, max } => write!(f, \"var data of {length} bytes is longer than the maximum of {max}\"),");
+		self.close("}");
+		self.close("}");
+		self.close("}");
+		self.line("");
+		self.line("impl std::error::Error for EncodeError {}");
+		self.line("");
+		self.line("/// Check that the buffer reaches the end offset.");
+		self.line("#[inline]");
+		self.open("fn check(buf: &[u8], end: usize) -> Result<(), Truncated> {");
+		self.open("if end <= buf.len() {");
+		self.line("Ok(())");
+		self.close_open("} else {");
+		self.line("Err(Truncated { required: end, available: buf.len() })");
+		self.close("}");
+		self.close("}");
+		self.line("");
+	}
+
+	fn enum_type(&mut self, e: &EnumType, seen: &mut HashSet<String>) -> Result<()> {
+		let name = camel_case(&e.name);
+		if !seen.insert(name.clone()) {
+			return Ok(());
+		}
+		let primitive = self.encoding(
+			e.encoding_type.as_ref().and_then(|t| t.primitive_type(self.schema)),
+			&e.name,
+		)?;
+		let ty = rust_type(primitive);
+		let null = e.null(primitive)?.unwrap_or_else(|| primitive.null_value());
+		let values = e
+			.valid_values()
+			.map(|v| Ok((variant(&v.name), literal(&v.parse(primitive)?))))
+			.collect::<Result<Vec<_>>>()?;
+
+		self.doc(e.description.as_deref(), &format!("`{}` enum.", e.name));
+		self.line("#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]");
+		self.line(&format!("#[repr({ty})]"));
+		self.open(&format!("pub enum {name} {{"));
+		for (variant, value) in &values {
+			self.line(&format!("{variant} = {value},"));
+		}
+		self.line("/// The null value, also for values unknown to this version of the schema.");
+		self.line("#[default]");
+		self.line(&format!("NullVal = {},", literal(&null)));
+		self.close("}");
+		self.line("");
+		self.open(&format!("impl From<{ty}> for {name} {{"));
+		self.open(&format!("fn from(value: {ty}) -> Self {{"));
+		self.open("match value {");
+		for (variant, value) in &values {
+			self.line(&format!("{value} => Self::{variant},"));
+		}
+		self.line("_ => Self::NullVal,");
+		self.close("}");
+		self.close("}");
+		self.close("}");
+		self.line("");
+		self.open(&format!("impl From<{name}> for {ty} {{"));
+		self.open(&format!("fn from(value: {name}) -> Self {{"));
+		self.line(&format!("value as {ty}"));
+		self.close("}");
+		self.close("}");
+		self.line("");
+		Ok(())
+	}
+
+	fn set_type(&mut self, s: &SetType, seen: &mut HashSet<String>) -> Result<()> {
+		let name = camel_case(&s.name);
+		if !seen.insert(name.clone()) {
+			return Ok(());
+		}
+		let primitive = self.encoding(
+			s.encoding_type.as_ref().and_then(|t| t.primitive_type(self.schema)),
+			&s.name,
+		)?;
+		let ty = rust_type(primitive);
+
+		self.doc(s.description.as_deref(), &format!("`{}` set of choices.", s.name));
+		self.line("#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]");
+		self.line(&format!("pub struct {name}(pub {ty});"));
+		self.line("");
+		self.open(&format!("impl {name} {{"));
+		for choice in s.choices() {
+			let bit = choice.bit(primitive)?;
+			let method = snake_case(&choice.name);
+			self.line(&format!("/// Whether `{}` is set.", choice.name));
+			self.open(&format!("pub fn {method}(&self) -> bool {{"));
+			self.line(&format!("self.0 & (1 << {bit}) != 0"));
+			self.close("}");
+			self.line("");
+			self.line(&format!("/// Set or clear `{}`.", choice.name));
+			self.open(&format!(
+				"pub fn set_{}(&mut self, value: bool) -> &mut Self {{",
+				method.trim_end_matches('_')
+			));
+			self.open("if value {");
+			self.line(&format!("self.0 |= 1 << {bit};"));
+			self.close_open("} else {");
+			self.line(&format!("self.0 &= !(1 << {bit});"));
+			self.close("}");
+			self.line("self");
+			self.close("}");
+			self.line("");
+		}
+		self.close("}");
+		self.line("");
+		Ok(())
+	}
+
+	fn composite(
+		&mut self,
+		composite: &Composite,
+		layouts: &[MemberLayout],
+		size: u32,
+		seen: &mut HashSet<String>,
+	) -> Result<()> {
+		let name = camel_case(&composite.name);
+		if !seen.insert(name.clone()) {
+			return Ok(());
+		}
+		let mut slots = Vec::new();
+		for (member, layout) in composite.members().into_iter().zip(layouts) {
+			let path = format!("{}.{}", composite.name, layout.name);
+			let (kind, since) = match member {
+				CompositeMember::Type(t) =>
+					(self.type_kind(t, t.presence.unwrap_or_default(), &path)?, t.since_version),
+				CompositeMember::Ref(r) => (
+					self.reference_kind(&r.ref_type, r.presence, r.value_ref.as_deref(), &path)?,
+					r.since_version,
+				),
+				CompositeMember::Enum(e) => {
+					self.enum_type(e, seen)?;
+					(self.enum_kind(e, false)?, e.since_version)
+				},
+				CompositeMember::Set(s) => {
+					self.set_type(s, seen)?;
+					(self.set_kind(s)?, s.since_version)
+				},
+				CompositeMember::Composite(c) => {
+					self.composite(c, &layout.members, layout.size, seen)?;
+					(Kind::Composite { name: camel_case(&c.name) }, c.since_version)
+				},
+			};
+			slots.push(Slot {
+				name: layout.name.clone(),
+				method: snake_case(&layout.name),
+				offset: layout.offset,
+				size: layout.size,
+				since: since.unwrap_or_default(),
+				kind,
+			});
+		}
+
+		self.doc(
+			composite.description.as_deref(),
+			&format!("Decoder of the `{}` composite.", composite.name),
+		);
+		self.line("#[derive(Debug, Clone, Copy)]");
+		self.open(&format!("pub struct {name}Decoder<'a> {{"));
+		self.line("buf: &'a [u8],");
+		self.line("offset: usize,");
+		self.line(&format!("acting_version: {},", self.version_ty));
+		self.close("}");
+		self.line("");
+		self.open(&format!("impl<'a> {name}Decoder<'a> {{"));
+		self.line("/// Encoded length of the composite.");
+		self.line(&format!("pub const ENCODED_LENGTH: usize = {size};"));
+		self.line("");
+		self.line("/// Decode the composite at the offset, encoded with the given schema version. Fails if");
+		self.line("/// the buffer is shorter than the composite.");
+		self.open(&format!(
+			"pub fn wrap(buf: &'a [u8], offset: usize, acting_version: {}) -> Result<Self, Truncated> {{",
+			self.version_ty
+		));
+		self.line("check(buf, offset + Self::ENCODED_LENGTH)?;");
+		self.line("Ok(Self { buf, offset, acting_version })");
+		self.close("}");
+		for slot in &slots {
+			self.getter(slot);
+		}
+		self.close("}");
+		self.line("");
+
+		self.line(&format!("/// Encoder of the `{}` composite.", composite.name));
+		self.line("#[derive(Debug)]");
+		self.open(&format!("pub struct {name}Encoder<'a> {{"));
+		self.line("buf: &'a mut [u8],");
+		self.line("offset: usize,");
+		self.close("}");
+		self.line("");
+		self.open(&format!("impl<'a> {name}Encoder<'a> {{"));
+		self.line("/// Encoded length of the composite.");
+		self.line(&format!("pub const ENCODED_LENGTH: usize = {size};"));
+		self.line("");
+		self.line("/// Encode the composite at the offset. Fails if the buffer is shorter than the composite.");
+		self.open("pub fn wrap(buf: &'a mut [u8], offset: usize) -> Result<Self, Truncated> {");
+		self.line("check(buf, offset + Self::ENCODED_LENGTH)?;");
+		self.line("Ok(Self { buf, offset })");
+		self.close("}");
+		for slot in &slots {
+			self.setter(slot);
+		}
+		self.close("}");
+		self.line("");
+		Ok(())
+	}
+
+	fn message_constants(&mut self, id: u32, block_length: u32, fingerprint: &str) {
+		let template_ty = self.header.template_id.as_ref().map_or("u16", Member::ty);
+		let block_ty = self.header.block_length.as_ref().map_or("u16", Member::ty);
+		self.line("/// Template id of the message.");
+		self.line(&format!("pub const TEMPLATE_ID: {template_ty} = {id};"));
+		self.line("/// Length of the block of fixed size fields.");
+		self.line(&format!("pub const BLOCK_LENGTH: {block_ty} = {block_length};"));
+		self.line("/// Fingerprint of the wire format of the message.");
+		self.line(&format!("pub const FINGERPRINT: &'static str = \"{fingerprint}\";"));
+		self.line("");
+	}
+
+	fn decoder_header(&mut self) {
+		if self.schema.message_header().is_none() {
+			return;
+		}
+		self.line(
+			"/// Decode the message following the message header at the offset, `None` if the",
+		);
+		self.line(
+			"/// header is of another message. Fails if the buffer is shorter than the header or",
+		);
+		self.line("/// the block.");
+		self.open(
+			"pub fn header(buf: &'a [u8], offset: usize) -> Result<Option<Self>, Truncated> {",
+		);
+		self.line("check(buf, offset + HEADER_LENGTH)?;");
+		if let Some(template_id) = &self.header.template_id {
+			let read = template_id.read("offset");
+			self.open(&format!("if {read} != Self::TEMPLATE_ID {{"));
+			self.line("return Ok(None);");
+			self.close("}");
+		}
+		let block_length = self
+			.header
+			.block_length
+			.as_ref()
+			.map_or("Self::BLOCK_LENGTH".to_string(), |m| m.read("offset"));
+		let version = self
+			.header
+			.version
+			.as_ref()
+			.map_or("SCHEMA_VERSION".to_string(), |m| m.read("offset"));
+		self.line(&format!("let block_length = {block_length} as usize;"));
+		self.line(&format!("let version = {version} as {};", self.version_ty));
+		self.line("Self::wrap(buf, offset + HEADER_LENGTH, block_length, version).map(Some)");
+		self.close("}");
+		self.line("");
+	}
+
+	fn encoder_header(&mut self) {
+		if self.schema.message_header().is_none() {
+			return;
+		}
+		self.line("/// Encode the message header at the offset followed by the message. Fails if the buffer");
+		self.line("/// is shorter than the header and the block.");
+		self.open("pub fn header(buf: &'a mut [u8], offset: usize) -> Result<Self, Truncated> {");
+		self.line("check(buf, offset + HEADER_LENGTH + Self::BLOCK_LENGTH as usize)?;");
+		self.line("buf[offset..offset + HEADER_LENGTH].fill(0);");
+		let members = [
+			(&self.header.block_length, "Self::BLOCK_LENGTH"),
+			(&self.header.template_id, "Self::TEMPLATE_ID"),
+			(&self.header.schema_id, "SCHEMA_ID"),
+			(&self.header.version, "SCHEMA_VERSION"),
+		];
+		let lines = members
+			.into_iter()
+			.filter_map(|(member, value)| {
+				let member = member.as_ref().filter(|m| m.constant.is_none())?;
+				Some(format!(
+					"put::<{ty}>(buf, {}, {value} as {ty});",
+					at("offset", member.offset),
+					ty = member.ty()
+				))
+			})
+			.collect::<Vec<_>>();
+		for line in lines {
+			self.line(&line);
+		}
+		self.line("Self::wrap(buf, offset + HEADER_LENGTH)");
+		self.close("}");
+		self.line("");
+	}
+
+	/// Accessors of the fields, groups and var data of a message or a group entry decoder.
+	fn decoder_members<'m, 'l>(
+		&mut self,
+		block: &Block,
+		fields: &[Slot],
+		groups: impl Iterator<Item = &'m Group>,
+		group_layouts: &'l [GroupLayout],
+		data: impl Iterator<Item = &'m Data>,
+		data_layouts: &[DataLayout],
+	) -> Result<Nested<'m, 'l>> {
+		for slot in fields {
+			self.getter(slot);
+		}
+		let mut nested = Vec::new();
+		for (group, layout) in groups.zip(group_layouts) {
+			let name =
+				format!("{}{}", block.name.trim_end_matches("Decoder"), camel_case(&group.name));
+			let since = group.since_version.unwrap_or_default();
+			self.line("");
+			self.line(&format!(
+				"/// Entries of the `{}` group, read them all before the next group or var data.",
+				group.name
+			));
+			if since > 0 {
+				self.line(&format!("/// Added in version {since}, empty for older versions."));
+			}
+			self.line("/// Fails if the buffer is shorter than the dimension.");
+			self.open(&format!(
+				"pub fn {}(&mut self) -> Result<{name}Decoder<'a, '_>, Truncated> {{",
+				snake_case(&group.name)
+			));
+			let limit = format!("&mut {}", block.limit);
+			let present = match since {
+				0 => "true".to_string(),
+				since => format!("self.acting_version >= {since}"),
+			};
+			self.line(&format!(
+				"{name}Decoder::wrap(self.buf, {limit}, self.acting_version, {present})"
+			));
+			self.close("}");
+			nested.push((name, group, layout));
+		}
+		for (d, layout) in data.zip(data_layouts) {
+			let (length, start) = self.data_members(d)?;
+			let since = d.since_version.unwrap_or_default();
+			self.line("");
+			self.line(&format!(
+				"/// Bytes of the `{}` var data, read after all groups and preceding var data.",
+				d.name
+			));
+			if since > 0 {
+				self.line(&format!("/// Added in version {since}, empty for older versions."));
+			}
+			self.line("/// Fails if the buffer is shorter than the var data.");
+			self.open(&format!(
+				"pub fn {}(&mut self) -> Result<&'a [u8], Truncated> {{",
+				snake_case(&layout.name)
+			));
+			if since > 0 {
+				self.open(&format!("if self.acting_version < {since} {{"));
+				self.line("return Ok(&[]);");
+				self.close("}");
+			}
+			self.line(&format!("let offset = {};", block.limit));
+			self.line(&format!("let start = {};", at("offset", start)));
+			self.line("check(self.buf, start)?;");
+			self.line(&format!(
+				"let length = get::<{}>(self.buf, {}) as usize;",
+				length.ty(),
+				at("offset", length.offset)
+			));
+			self.line("check(self.buf, start + length)?;");
+			self.line(&format!("{} = start + length;", block.limit));
+			self.line("Ok(&self.buf[start..start + length])");
+			self.close("}");
+		}
+		Ok(nested)
+	}
+
+	fn group_decoder(&mut self, name: &str, group: &Group, layout: &GroupLayout) -> Result<()> {
+		let (block_length, count) = self.dimension(group)?;
+		let fields = self.slots(&group.name, group.fields(), &layout.fields)?;
+		self.line(&format!("/// Decoder of the entries of the `{}` group.", group.name));
+		self.line("#[derive(Debug)]");
+		self.open(&format!("pub struct {name}Decoder<'a, 'l> {{"));
+		self.line("buf: &'a [u8],");
+		self.line("limit: &'l mut usize,");
+		self.line("offset: usize,");
+		self.line("block_length: usize,");
+		self.line(&format!("acting_version: {},", self.version_ty));
+		self.line("count: usize,");
+		self.line("index: usize,");
+		self.close("}");
+		self.line("");
+		self.open(&format!("impl<'a, 'l> {name}Decoder<'a, 'l> {{"));
+		self.line("/// Length of the block of fixed size fields of an entry.");
+		self.line(&format!(
+			"pub const BLOCK_LENGTH: {} = {};",
+			block_length.ty(),
+			layout.block_length
+		));
+		self.line("");
+		self.open(&format!(
+			"fn wrap(buf: &'a [u8], limit: &'l mut usize, acting_version: {}, present: bool) -> Result<Self, Truncated> {{",
+			self.version_ty
+		));
+		self.line("let offset = *limit;");
+		self.line("let (block_length, count) = if present {");
+		self.indent += 1;
+		self.line(&format!("check(buf, offset + {})?;", layout.dimension_length));
+		self.line(&format!("*limit += {};", layout.dimension_length));
+		self.line(&format!(
+			"({} as usize, {} as usize)",
+			block_length.read("offset"),
+			count.read("offset")
+		));
+		self.close("} else {");
+		self.indent += 1;
+		self.line("(0, 0)");
+		self.close("};");
+		self.line("Ok(Self { buf, limit, offset, block_length, acting_version, count, index: 0 })");
+		self.close("}");
+		self.line("");
+		self.line("/// Number of entries of the group.");
+		self.open("pub fn count(&self) -> usize {");
+		self.line("self.count");
+		self.close("}");
+		self.line("");
+		self.line("/// Move to the next entry, `None` after the last one. Fails if the buffer is shorter than");
+		self.line("/// the block of the entry.");
+		self.open("pub fn next_entry(&mut self) -> Result<Option<&mut Self>, Truncated> {");
+		self.open("if self.index >= self.count {");
+		self.line("return Ok(None);");
+		self.close("}");
+		self.line(&format!(
+			"check(self.buf, *self.limit + self.block_length.max({}))?;",
+			required_block_length(&fields, "self.acting_version")
+		));
+		self.line("self.offset = *self.limit;");
+		self.line("*self.limit += self.block_length;");
+		self.line("self.index += 1;");
+		self.line("Ok(Some(self))");
+		self.close("}");
+		let block = Block { name: &format!("{name}Decoder"), limit: "*self.limit" };
+		let groups = self.decoder_members(
+			&block,
+			&fields,
+			group.groups(),
+			&layout.groups,
+			group.data(),
+			&layout.data,
+		)?;
+		self.close("}");
+		self.line("");
+		for (name, group, layout) in groups {
+			self.group_decoder(&name, group, layout)?;
+		}
+		Ok(())
+	}
+
+	/// Accessors of the fields, groups and var data of a message or a group entry encoder.
+	fn encoder_members<'m, 'l>(
+		&mut self,
+		block: &Block,
+		fields: &[Slot],
+		groups: impl Iterator<Item = &'m Group>,
+		group_layouts: &'l [GroupLayout],
+		data: impl Iterator<Item = &'m Data>,
+		data_layouts: &[DataLayout],
+	) -> Result<Nested<'m, 'l>> {
+		for slot in fields {
+			self.setter(slot);
+		}
+		let mut nested = Vec::new();
+		for (group, layout) in groups.zip(group_layouts) {
+			let name =
+				format!("{}{}", block.name.trim_end_matches("Encoder"), camel_case(&group.name));
+			let (_, count) = self.dimension(group)?;
+			self.line("");
+			self.line(&format!(
+				"/// Start the `{}` group with the number of entries, encode them all before the next",
+				group.name
+			));
+			self.line("/// group or var data. Fails if the buffer is shorter than the dimension.");
+			self.open(&format!(
+				"pub fn {}(&mut self, count: {}) -> Result<{name}Encoder<'_>, Truncated> {{",
+				snake_case(&group.name),
+				count.ty()
+			));
+			self.line(&format!("{name}Encoder::wrap(self.buf, &mut {}, count)", block.limit));
+			self.close("}");
+			nested.push((name, group, layout));
+		}
+		for (d, layout) in data.zip(data_layouts) {
+			let (length, start) = self.data_members(d)?;
+			self.line("");
+			self.line(&format!("/// Append the bytes of the `{}` var data, after all groups and preceding var data.", d.name));
+			self.line("/// Fails if they are longer than the length allows or the buffer is too short for them.");
+			self.open(&format!(
+				"pub fn {}(&mut self, value: &[u8]) -> Result<&mut Self, EncodeError> {{",
+				snake_case(&layout.name)
+			));
+			if let Some(max) = length.max {
+				let max = max.max(0);
+				self.open(&format!("if value.len() as u64 > {max} {{"));
+				self.line(&format!(
+					"return Err(EncodeError::TooLong {{ length: value.len(), max: {max} }});"
+				));
+				self.close("}");
+			}
+			self.line(&format!("let offset = {};", block.limit));
+			self.line(&format!("check(self.buf, {} + value.len())?;", at("offset", start)));
+			self.line(&format!(
+				"put::<{ty}>(self.buf, {}, value.len() as {ty});",
+				at("offset", length.offset),
+				ty = length.ty()
+			));
+			self.line(&format!("let start = {};", at("offset", start)));
+			self.line("self.buf[start..start + value.len()].copy_from_slice(value);");
+			self.line(&format!("{} = start + value.len();", block.limit));
+			self.line("Ok(self)");
+			self.close("}");
+		}
+		Ok(nested)
+	}
+
+	fn group_encoder(&mut self, name: &str, group: &Group, layout: &GroupLayout) -> Result<()> {
+		let (block_length, count) = self.dimension(group)?;
+		let fields = self.slots(&group.name, group.fields(), &layout.fields)?;
+		self.line(&format!("/// Encoder of the entries of the `{}` group.", group.name));
+		self.line("#[derive(Debug)]");
+		self.open(&format!("pub struct {name}Encoder<'a> {{"));
+		self.line("buf: &'a mut [u8],");
+		self.line("limit: &'a mut usize,");
+		self.line("offset: usize,");
+		self.line("count: usize,");
+		self.line("index: usize,");
+		self.close("}");
+		self.line("");
+		self.open(&format!("impl<'a> {name}Encoder<'a> {{"));
+		self.line("/// Length of the block of fixed size fields of an entry.");
+		self.line(&format!(
+			"pub const BLOCK_LENGTH: {} = {};",
+			block_length.ty(),
+			layout.block_length
+		));
+		self.line("");
+		self.open(&format!(
+			"fn wrap(buf: &'a mut [u8], limit: &'a mut usize, count: {}) -> Result<Self, Truncated> {{",
+			count.ty()
+		));
+		self.line("let offset = *limit;");
+		self.line(&format!("check(buf, offset + {})?;", layout.dimension_length));
+		if block_length.constant.is_none() {
+			self.line(&format!(
+				"put::<{}>(buf, {}, Self::BLOCK_LENGTH);",
+				block_length.ty(),
+				at("offset", block_length.offset)
+			));
+		}
+		self.line(&format!("put::<{}>(buf, {}, count);", count.ty(), at("offset", count.offset)));
+		self.line(&format!("*limit += {};", layout.dimension_length));
+		self.line("Ok(Self { buf, limit, offset, count: count as usize, index: 0 })");
+		self.close("}");
+		self.line("");
+		self.line("/// Move to the next entry, `None` after the last one. The block of the entry is zeroed");
+		self.line("/// and optional fields are set to null. Fails if the buffer is shorter than the block.");
+		self.open("pub fn next_entry(&mut self) -> Result<Option<&mut Self>, Truncated> {");
+		self.open("if self.index >= self.count {");
+		self.line("return Ok(None);");
+		self.close("}");
+		self.line("let offset = *self.limit;");
+		self.line("check(self.buf, offset + Self::BLOCK_LENGTH as usize)?;");
+		self.line("*self.limit += Self::BLOCK_LENGTH as usize;");
+		self.line("let buf = &mut *self.buf;");
+		self.line("buf[offset..offset + Self::BLOCK_LENGTH as usize].fill(0);");
+		self.nulls(&fields);
+		self.line("self.offset = offset;");
+		self.line("self.index += 1;");
+		self.line("Ok(Some(self))");
+		self.close("}");
+		let block = Block { name: &format!("{name}Encoder"), limit: "*self.limit" };
+		let groups = self.encoder_members(
+			&block,
+			&fields,
+			group.groups(),
+			&layout.groups,
+			group.data(),
+			&layout.data,
+		)?;
+		self.close("}");
+		self.line("");
+		for (name, group, layout) in groups {
+			self.group_encoder(&name, group, layout)?;
+		}
+		Ok(())
+	}
+
+	/// Set the optional fields of a zeroed block at `offset` of `buf` to null.
+	fn nulls(&mut self, fields: &[Slot]) {
+		for slot in fields {
+			let offset = at("offset", slot.offset);
+			match &slot.kind {
+				Kind::Primitive { primitive, null: Some(null), .. } => self.line(&format!(
+					"put::<{}>(buf, {offset}, {});",
+					rust_type(*primitive),
+					literal(null)
+				)),
+				Kind::Enum { name, primitive, optional: true } => self.line(&format!(
+					"put::<{}>(buf, {offset}, {name}::NullVal.into());",
+					rust_type(*primitive)
+				)),
+				_ => {},
+			}
+		}
+	}
+
+	fn getter(&mut self, slot: &Slot) {
+		let at = at("self.offset", slot.offset);
+		let (ty, body, optional) = match &slot.kind {
+			Kind::Primitive { length: 0, .. } => return,
+			Kind::Primitive { primitive: PrimitiveType::Char, length, .. } if *length > 1 =>
+				("&'a [u8]".to_string(), vec![format!("&self.buf[{at}..{at} + {length}]")], false),
+			Kind::Primitive { primitive, length, .. } if *length > 1 => {
+				let ty = rust_type(*primitive);
+				(
+					format!("[{ty}; {length}]"),
+					vec![format!(
+						"std::array::from_fn(|i| get::<{ty}>(self.buf, {at} + i * {}))",
+						primitive.size()
+					)],
+					false,
+				)
+			},
+			Kind::Primitive { primitive, null: Some(null), .. } => {
+				let ty = rust_type(*primitive);
+				let check = match null.as_f64() {
+					Some(v) if v.is_nan() => "!value.is_nan()".to_string(),
+					_ => format!("value != {}", literal(null)),
+				};
+				(
+					format!("Option<{ty}>"),
+					vec![
+						format!("let value = get::<{ty}>(self.buf, {at});"),
+						format!("({check}).then_some(value)"),
+					],
+					true,
+				)
+			},
+			Kind::Primitive { primitive, .. } => {
+				let ty = rust_type(*primitive);
+				(ty.to_string(), vec![format!("get::<{ty}>(self.buf, {at})")], false)
+			},
+			Kind::Constant { ty, value } => {
+				self.line("");
+				self.line(&format!("/// `{}`, a constant not encoded on the wire.", slot.name));
+				self.open(&format!("pub fn {}(&self) -> {ty} {{", slot.method));
+				self.line(value);
+				self.close("}");
+				return;
+			},
+			Kind::Enum { name, primitive, .. } => (
+				name.clone(),
+				vec![format!("{name}::from(get::<{}>(self.buf, {at}))", rust_type(*primitive))],
+				false,
+			),
+			Kind::Set { name, primitive } => (
+				name.clone(),
+				vec![format!("{name}(get::<{}>(self.buf, {at}))", rust_type(*primitive))],
+				false,
+			),
+			Kind::Composite { name } => (
+				format!("{name}Decoder<'a>"),
+				vec![format!(
+					"{name}Decoder {{ buf: self.buf, offset: {at}, acting_version: self.acting_version }}"
+				)],
+				false,
+			),
+		};
+		self.line("");
+		if optional {
+			self.line(&format!("/// `{}`, `None` if null.", slot.name));
+		} else {
+			self.line(&format!("/// `{}`.", slot.name));
+		}
+		let versioned = slot.since > 0;
+		if versioned {
+			self.line(&format!("/// Added in version {}, `None` for older versions.", slot.since));
+		}
+		let ty = if versioned && !optional { format!("Option<{ty}>") } else { ty };
+		self.open(&format!("pub fn {}(&self) -> {ty} {{", slot.method));
+		if versioned {
+			self.open(&format!("if self.acting_version < {} {{", slot.since));
+			self.line("return None;");
+			self.close("}");
+		}
+		let (last, lines) = body.split_last().expect("getter has a body");
+		for line in lines {
+			self.line(line);
+		}
+		if versioned && !optional {
+			self.line(&format!("Some({last})"));
+		} else {
+			self.line(last);
+		}
+		self.close("}");
+	}
+
+	fn setter(&mut self, slot: &Slot) {
+		let at = at("self.offset", slot.offset);
+		let method = &slot.method;
+		let (signature, body) = match &slot.kind {
+			Kind::Primitive { length: 0, .. } | Kind::Constant { .. } => return,
+			Kind::Primitive { primitive: PrimitiveType::Char, length, .. } if *length > 1 => (
+				format!("pub fn {method}(&mut self, value: &[u8]) -> &mut Self"),
+				vec![
+					format!("let field = &mut self.buf[{at}..{at} + {length}];"),
+					"let length = value.len().min(field.len());".to_string(),
+					"field[..length].copy_from_slice(&value[..length]);".to_string(),
+					"field[length..].fill(0);".to_string(),
+				],
+			),
+			Kind::Primitive { primitive, length, .. } if *length > 1 => {
+				let ty = rust_type(*primitive);
+				(
+					format!("pub fn {method}(&mut self, value: [{ty}; {length}]) -> &mut Self"),
+					vec![
+						"for (i, value) in value.into_iter().enumerate() {".to_string(),
+						format!("\tput::<{ty}>(self.buf, {at} + i * {}, value);", primitive.size()),
+						"}".to_string(),
+					],
+				)
+			},
+			Kind::Primitive { primitive, null: Some(null), .. } => {
+				let ty = rust_type(*primitive);
+				(
+					format!("pub fn {method}(&mut self, value: Option<{ty}>) -> &mut Self"),
+					vec![format!(
+						"put::<{ty}>(self.buf, {at}, value.unwrap_or({}));",
+						literal(null)
+					)],
+				)
+			},
+			Kind::Primitive { primitive, .. } => {
+				let ty = rust_type(*primitive);
+				(
+					format!("pub fn {method}(&mut self, value: {ty}) -> &mut Self"),
+					vec![format!("put::<{ty}>(self.buf, {at}, value);")],
+				)
+			},
+			Kind::Enum { name, primitive, .. } => (
+				format!("pub fn {method}(&mut self, value: {name}) -> &mut Self"),
+				vec![format!("put::<{}>(self.buf, {at}, value.into());", rust_type(*primitive))],
+			),
+			Kind::Set { name, primitive } => (
+				format!("pub fn {method}(&mut self, value: {name}) -> &mut Self"),
+				vec![format!("put::<{}>(self.buf, {at}, value.0);", rust_type(*primitive))],
+			),
+			Kind::Composite { name } => {
+				self.line("");
+				self.line(&format!("/// Encoder of `{}`.", slot.name));
+				self.open(&format!("pub fn {method}(&mut self) -> {name}Encoder<'_> {{"));
+				self.line(&format!("{name}Encoder {{ buf: self.buf, offset: {at} }}"));
+				self.close("}");
+				return;
+			},
+		};
+		self.line("");
+		self.line(&format!("/// Set `{}`.", slot.name));
+		self.open(&format!("{signature} {{"));
+		for line in body {
+			self.line(&line);
+		}
+		self.line("self");
+		self.close("}");
+	}
+
+	/// Slots of the fields of a block with their layouts.
+	fn slots<'m>(
+		&self,
+		parent: &str,
+		fields: impl Iterator<Item = &'m Field>,
+		layouts: &[FieldLayout],
+	) -> Result<Vec<Slot>> {
+		fields
+			.zip(layouts)
+			.map(|(field, layout)| {
+				let path = format!("{parent}.{}", field.name);
+				Ok(Slot {
+					name: field.name.clone(),
+					method: snake_case(&field.name),
+					offset: layout.offset,
+					size: layout.size,
+					since: field.since_version.unwrap_or_default(),
+					kind: self.reference_kind(
+						&field.r#type,
+						field.presence,
+						field.value_ref.as_deref(),
+						&path,
+					)?,
+				})
+			})
+			.collect()
+	}
+
+	/// Kind of a field or a ref encoded by the named type.
+	fn reference_kind(
+		&self,
+		type_name: &str,
+		presence: Option<Presence>,
+		value_ref: Option<&str>,
+		path: &str,
+	) -> Result<Kind> {
+		if presence == Some(Presence::Constant) {
+			if let Some(constant) = value_ref.and_then(enum_constant) {
+				return Ok(constant);
+			}
+		}
+		let unresolved =
+			|| GenerateError::Unresolved { name: type_name.to_string(), path: path.to_string() };
+		match self.vtable.resolve(type_name).ok_or_else(unresolved)? {
+			VTableObject::Primitive(primitive) => {
+				let presence = presence.unwrap_or_default();
+				if presence == Presence::Constant {
+					return Err(GenerateError::Unsupported {
+						message: "constant of a primitive type without a value".to_string(),
+						path: path.to_string(),
+					});
+				}
+				let null = (presence == Presence::Optional).then(|| primitive.null_value());
+				Ok(Kind::Primitive { primitive: *primitive, length: 1, null })
+			},
+			VTableObject::Type(t) =>
+				self.type_kind(t, presence.or(t.presence).unwrap_or_default(), path),
+			VTableObject::Enum(e) => self.enum_kind(e, presence == Some(Presence::Optional)),
+			VTableObject::Set(s) => self.set_kind(s),
+			VTableObject::Composite(c) => Ok(Kind::Composite { name: camel_case(&c.name) }),
+		}
+	}
+
+	fn type_kind(&self, t: &Type, presence: Presence, path: &str) -> Result<Kind> {
+		if presence == Presence::Constant {
+			if let Some(constant) = t.value_ref.as_deref().and_then(enum_constant) {
+				return Ok(constant);
+			}
+			let Some(value) = t.constant()? else {
+				return Err(GenerateError::Unsupported {
+					message: format!("constant `{}` has no value", t.name),
+					path: path.to_string(),
+				});
+			};
+			let ty = match &value {
+				Value::Chars(_) => "&'static [u8]".to_string(),
+				value => rust_type(value.primitive_type()).to_string(),
+			};
+			return Ok(Kind::Constant { ty, value: literal(&value) });
+		}
+		let length = t.length.unwrap_or(1);
+		let null =
+			if presence == Presence::Optional && length == 1 { Some(t.null()?) } else { None };
+		let primitive = t.null()?.primitive_type();
+		Ok(Kind::Primitive { primitive, length, null })
+	}
+
+	fn enum_kind(&self, e: &EnumType, optional: bool) -> Result<Kind> {
+		let primitive = self.encoding(
+			e.encoding_type.as_ref().and_then(|t| t.primitive_type(self.schema)),
+			&e.name,
+		)?;
+		Ok(Kind::Enum { name: camel_case(&e.name), primitive, optional })
+	}
+
+	fn set_kind(&self, s: &SetType) -> Result<Kind> {
+		let primitive = self.encoding(
+			s.encoding_type.as_ref().and_then(|t| t.primitive_type(self.schema)),
+			&s.name,
+		)?;
+		Ok(Kind::Set { name: camel_case(&s.name), primitive })
+	}
+
+	fn encoding(&self, primitive: Option<PrimitiveType>, name: &str) -> Result<PrimitiveType> {
+		primitive.ok_or_else(|| GenerateError::Unsupported {
+			message: "encodingType is not a primitive type".to_string(),
+			path: name.to_string(),
+		})
+	}
+
+	/// The `blockLength` and `numInGroup` members of the dimension of a group.
+	fn dimension(&self, group: &Group) -> Result<(Member, Member)> {
+		Ok((
+			self.member(&group.dimension_type, "blockLength")?,
+			self.member(&group.dimension_type, "numInGroup")?,
+		))
+	}
+
+	/// The `length` member of the composite of a var data and the offset of the bytes.
+	fn data_members(&self, data: &Data) -> Result<(Member, u32)> {
+		let length = self.member(&data.r#type, "length")?;
+		let start = self
+			.member(&data.r#type, "varData")
+			.map_or(length.offset + length.primitive.size(), |m| m.offset);
+		Ok((length, start))
+	}
+
+	/// A primitive member of a standalone composite.
+	fn member(&self, composite: &str, name: &str) -> Result<Member> {
+		let missing = || GenerateError::Unsupported {
+			message: format!("composite has no `{name}` member"),
+			path: composite.to_string(),
+		};
+		let layout = self.layout.composite(composite).ok_or_else(missing)?;
+		let definition =
+			self.schema.composites().find(|c| c.name == composite).ok_or_else(missing)?;
+		let (member, layout) = definition
+			.members()
+			.into_iter()
+			.zip(&layout.members)
+			.find(|(_, layout)| layout.name == name)
+			.ok_or_else(missing)?;
+		let t = match member {
+			CompositeMember::Type(t) => t,
+			CompositeMember::Ref(r) => match self.vtable.resolve(&r.ref_type) {
+				Some(VTableObject::Type(t)) => t,
+				_ => return Err(missing()),
+			},
+			_ => return Err(missing()),
+		};
+		let constant = if t.presence == Some(Presence::Constant) { t.constant()? } else { None };
+		Ok(Member {
+			offset: layout.offset,
+			primitive: t.null()?.primitive_type(),
+			constant,
+			max: t.max()?.as_i128(),
+		})
+	}
+
+	fn doc(&mut self, description: Option<&str>, default: &str) {
+		match description.map(str::trim).filter(|d| !d.is_empty()) {
+			Some(description) =>
+				for line in description.lines() {
+					self.line(&format!("/// {}", line.trim()));
+				},
+			None => self.line(&format!("/// {default}")),
+		}
+	}
+
+	fn line(&mut self, line: &str) {
+		if !line.is_empty() {
+			for _ in 0..self.indent {
+				self.out.push('\t');
+			}
+			self.out.push_str(line);
+		}
+		self.out.push('\n');
+	}
+
+	fn open(&mut self, line: &str) {
+		self.line(line);
+		self.indent += 1;
+	}
+
+	fn close(&mut self, line: &str) {
+		self.indent -= 1;
+		self.line(line);
+	}
+
+	fn close_open(&mut self, line: &str) {
+		self.close(line);
+		self.indent += 1;
+	}
+}
+
+/// Constant of a `valueRef` to a valid value of an enum, e.g. `Model.C`.
+fn enum_constant(value_ref: &str) -> Option<Kind> {
+	let (enum_name, value) = value_ref.split_once('.')?;
+	let ty = camel_case(enum_name);
+	Some(Kind::Constant { value: format!("{ty}::{}", variant(value)), ty })
+}
+
+/// Expression of the block length the fields need in the acting version, fields added later are
+/// not read.
+fn required_block_length(fields: &[Slot], acting_version: &str) -> String {
+	let end = |version| {
+		fields
+			.iter()
+			.filter(|slot| slot.since <= version)
+			.map(|slot| slot.offset + slot.size)
+			.max()
+			.unwrap_or_default()
+	};
+	let mut versions = fields.iter().map(|slot| slot.since).filter(|&v| v > 0).collect::<Vec<_>>();
+	versions.sort_unstable();
+	versions.dedup();
+	let (mut expression, mut previous) = (end(0).to_string(), end(0));
+	for version in versions {
+		let length = end(version);
+		if length != previous {
+			expression =
+				format!("if {acting_version} >= {version} {{ {length} }} else {{ {expression} }}");
+			previous = length;
+		}
+	}
+	expression
+}
+
+/// Offset expression of a member at the offset from the base.
+fn at(base: &str, offset: u32) -> String {
+	match offset {
+		0 => base.to_string(),
+		offset => format!("{base} + {offset}"),
+	}
+}
+
+fn rust_type(primitive: PrimitiveType) -> &'static str {
+	match primitive {
+		PrimitiveType::Char | PrimitiveType::Uint8 => "u8",
+		PrimitiveType::Int8 => "i8",
+		PrimitiveType::Uint16 => "u16",
+		PrimitiveType::Int16 => "i16",
+		PrimitiveType::Uint32 => "u32",
+		PrimitiveType::Int32 => "i32",
+		PrimitiveType::Uint64 => "u64",
+		PrimitiveType::Int64 => "i64",
+		PrimitiveType::Float => "f32",
+		PrimitiveType::Double => "f64",
+	}
+}
+
+/// Rust literal of the value.
+fn literal(value: &Value) -> String {
+	let float = |v: f64, ty: &str| {
+		if v.is_nan() {
+			format!("{ty}::NAN")
+		} else if v.is_infinite() {
+			format!("{ty}::{}INFINITY", if v < 0.0 { "NEG_" } else { "" })
+		} else {
+			format!("{v:?}")
+		}
+	};
+	match value {
+		Value::Char(c) if c.is_ascii_graphic() || *c == b' ' =>
+			format!("b'{}'", char::from(*c).escape_default()),
+		Value::Char(c) => c.to_string(),
+		Value::Chars(s) => format!("b\"{}\"", s.escape_default()),
+		Value::Float(v) => float(f64::from(*v), "f32"),
+		Value::Double(v) => float(*v, "f64"),
+		value => value.to_string(),
+	}
+}
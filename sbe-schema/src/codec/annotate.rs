@@ -0,0 +1,213 @@
+//! Annotated hex dumps of messages, labelling every byte with the element it encodes.
+
+use std::fmt;
+
+use serde_json::Value as Json;
+
+use super::{Decoded, Decoder};
+use crate::{Result, Schema};
+
+/// Bytes per line of the hex dump.
+const LINE: usize = 16;
+
+/// What a range of bytes of a message encodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpanKind {
+	/// A member of the message header.
+	Header,
+	/// A field of the message or of an entry of a group, or a member of its composite.
+	Field,
+	/// A member of the dimension of a group, e.g. `numInGroup`.
+	Dimension,
+	/// The length of a var data.
+	Length,
+	/// The bytes of a var data.
+	Data,
+	/// Bytes of the message no element of the schema covers, e.g. for alignment or fields of a
+	/// newer version.
+	Padding,
+	/// Bytes after the end of the message.
+	Trailing,
+}
+
+impl fmt::Display for SpanKind {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let name = match self {
+			SpanKind::Header => "header",
+			SpanKind::Field => "field",
+			SpanKind::Dimension => "dimension",
+			SpanKind::Length => "length",
+			SpanKind::Data => "data",
+			SpanKind::Padding => "padding",
+			SpanKind::Trailing => "trailing",
+		};
+		f.pad(name)
+	}
+}
+
+/// A range of bytes of a message and the element it encodes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+	/// Offset of the first byte in the buffer.
+	pub offset: usize,
+	/// Number of bytes.
+	pub length: usize,
+	/// What the bytes encode.
+	pub kind: SpanKind,
+	/// Path of the element, e.g. `Car.fuelFigures[0].speed`, empty for padding and trailing
+	/// bytes.
+	pub label: String,
+	/// The decoded value of the element.
+	pub value: Option<Json>,
+}
+
+/// A message decoded from the start of a buffer with spans covering every byte of the buffer in
+/// order. Displays as a hex dump with the label of every span.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotated<'b> {
+	/// The decoded message.
+	pub decoded: Decoded,
+	/// The buffer.
+	pub bytes: &'b [u8],
+	/// The spans of the buffer in order.
+	pub spans: Vec<Span>,
+}
+
+/// Decode the message at the start of the buffer and label its bytes, see
+/// [`Decoder::annotate`].
+pub fn annotate<'b>(schema: &Schema, buf: &'b [u8]) -> Result<Annotated<'b>> {
+	Decoder::new(schema)?.annotate(buf)
+}
+
+/// Sort the spans read from a message, cover the gaps between them with padding and the bytes
+/// after the message with a trailing span.
+pub(super) fn fill(mut spans: Vec<Span>, length: usize, total: usize) -> Vec<Span> {
+	spans.sort_by_key(|span| span.offset);
+	let gap = |offset, end: usize, kind| Span {
+		offset,
+		length: end - offset,
+		kind,
+		label: String::new(),
+		value: None,
+	};
+	let mut filled = Vec::with_capacity(spans.len());
+	let mut cursor = 0;
+	for span in spans {
+		if span.offset > cursor {
+			filled.push(gap(cursor, span.offset, SpanKind::Padding));
+		}
+		cursor = cursor.max(span.offset + span.length);
+		filled.push(span);
+	}
+	if cursor < length {
+		filled.push(gap(cursor, length, SpanKind::Padding));
+	}
+	if length < total {
+		filled.push(gap(length, total, SpanKind::Trailing));
+	}
+	filled
+}
+
+impl fmt::Display for Annotated<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let decoded = &self.decoded;
+		writeln!(
+			f,
+			"{} (template id {}, version {}, block length {}): {} of {} bytes",
+			decoded.name,
+			decoded.template_id,
+			decoded.version,
+			decoded.block_length,
+			decoded.length,
+			self.bytes.len()
+		)?;
+		for span in &self.spans {
+			let bytes = &self.bytes[span.offset..span.offset + span.length];
+			for (i, line) in bytes.chunks(LINE).enumerate() {
+				let hex = line.iter().map(|b| format!("{b:02x}")).collect::<Vec<_>>().join(" ");
+				let ascii: String = line
+					.iter()
+					.map(|&b| if b.is_ascii_graphic() || b == b' ' { char::from(b) } else { '.' })
+					.collect();
+				let mut text = format!("{:08x}  {hex:<47}  {ascii:<16}", span.offset + i * LINE);
+				if i == 0 {
+					text += &format!("  {:<9}  {}", span.kind, span.label);
+					if let Some(value) = &span.value {
+						text += &format!(" = {value}");
+					}
+				}
+				writeln!(f, "{}", text.trim_end())?;
+			}
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::path::Path;
+
+	use serde_json::json;
+
+	use super::*;
+	use crate::{encode, SchemaLoader};
+
+	fn load(file: &str) -> Schema {
+		let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/resources").join(file);
+		SchemaLoader::new().load(path).unwrap()
+	}
+
+	#[test]
+	fn test_annotate() {
+		let schema = load("json-printer-test-schema.xml");
+		let car = json!({
+			"serialNumber": 1,
+			"modelYear": 2013,
+			"available": "T",
+			"code": "A",
+			"someNumbers": [1, 2, 3, 4, 5],
+			"vehicleCode": "abc",
+			"engine": { "capacity": 2000, "numCylinders": 4, "manufacturerCode": "123" },
+			"fuelFigures": [{ "speed": 30, "mpg": 35.9 }],
+			"model": "Civic"
+		});
+		let mut buf = encode(&schema, "Car", &car).unwrap();
+		// a producer of a newer version with two more bytes in the block
+		buf[0] = 64;
+		buf.splice(70..70, [0xaa, 0xbb]);
+		buf.extend([0xff; 3]);
+
+		let annotated = annotate(&schema, &buf).unwrap();
+		let spans = &annotated.spans;
+		assert!(spans.windows(2).all(|w| w[0].offset + w[0].length == w[1].offset));
+		assert_eq!(spans.last().map(|s| s.offset + s.length), Some(buf.len()));
+
+		let span = |label: &str| spans.iter().find(|s| s.label == label).unwrap();
+		assert_eq!(span("messageHeader.blockLength").value, Some(json!(64)));
+		assert_eq!(span("Car.engine.capacity").kind, SpanKind::Field);
+		let count = span("Car.fuelFigures.numInGroup");
+		assert_eq!(
+			(count.kind, count.offset, count.value.clone()),
+			(SpanKind::Dimension, 74, Some(json!(1)))
+		);
+		assert_eq!(span("Car.fuelFigures[0].mpg").offset, 77);
+		assert_eq!(span("Car.model.length").kind, SpanKind::Length);
+		assert_eq!(span("Car.model").kind, SpanKind::Data);
+		let kinds = |kind| {
+			spans
+				.iter()
+				.filter(|s| s.kind == kind)
+				.map(|s| (s.offset, s.length))
+				.collect::<Vec<_>>()
+		};
+		assert_eq!(kinds(SpanKind::Padding), [(70, 2)]);
+		assert_eq!(kinds(SpanKind::Trailing), [(buf.len() - 3, 3)]);
+
+		let dump = annotated.to_string();
+		assert!(dump.starts_with("Car (template id 1, version 2, block length 64)"));
+		assert!(dump.contains(
+			"00000010  dd 07                                            ..                field      Car.modelYear = 2013\n"
+		));
+		assert!(dump.contains("00000046  aa bb                                            ..                padding\n"));
+	}
+}
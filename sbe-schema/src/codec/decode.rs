@@ -0,0 +1,457 @@
+//! Decoding of messages into JSON, as printed by the `JsonPrinter` of the SBE tool.
+
+use std::fmt::Write;
+
+use serde_json::{Map, Number, Value as Json};
+
+use super::{
+	annotate::{self, Annotated, Span, SpanKind},
+	Codec, CodecError, Counter, Encoding, Members,
+};
+use crate::{types::PrimitiveType, Result, Schema, Value};
+
+/// A message decoded from the start of a buffer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decoded {
+	/// Name of the message.
+	pub name: String,
+	/// Template id of the message.
+	pub template_id: u32,
+	/// Block length the message was encoded with, from the message header.
+	pub block_length: usize,
+	/// Schema version the message was encoded with, from the message header.
+	pub version: u32,
+	/// Length of the message including the message header.
+	pub length: usize,
+	/// The fields, groups and var data of the message in schema order.
+	pub value: Json,
+}
+
+/// Decodes messages of a schema into JSON. Fields, groups and var data added after the
+/// version of the encoder are null, char arrays are strings and sets are objects of flags.
+pub struct Decoder<'a> {
+	codec: Codec<'a>,
+}
+
+impl<'a> Decoder<'a> {
+	/// Create a decoder of the messages of the schema.
+	pub fn new(schema: &'a Schema) -> Result<Self> {
+		Ok(Self { codec: Codec::new(schema)? })
+	}
+
+	/// Decode the message at the start of the buffer, the message header selects the message
+	/// and the block length and version it was encoded with.
+	pub fn decode(&self, buf: &[u8]) -> Result<Decoded> {
+		Ok(self.read(buf, None)?.0)
+	}
+
+	/// Decode the message at the start of the buffer and label every byte of the buffer with the
+	/// element of the schema it encodes, see [`Annotated`].
+	pub fn annotate<'b>(&self, buf: &'b [u8]) -> Result<Annotated<'b>> {
+		let (decoded, spans) = self.read(buf, Some(Vec::new()))?;
+		let spans = annotate::fill(spans.unwrap_or_default(), decoded.length, buf.len());
+		Ok(Annotated { decoded, bytes: buf, spans })
+	}
+
+	fn read(&self, buf: &[u8], spans: Option<Vec<Span>>) -> Result<(Decoded, Option<Vec<Span>>)> {
+		let codec = &self.codec;
+		let header = codec.schema.header_type_name();
+		let mut reader = Reader { codec, buf, version: codec.schema.version, limit: 0, spans };
+		reader.composite(SpanKind::Header, header, 0, header)?;
+
+		let template_id = reader.counter(&codec.counter(header, "templateId")?, 0, header)?;
+		let message = u32::try_from(template_id)
+			.ok()
+			.and_then(|id| codec.schema.message(id))
+			.ok_or(CodecError::UnknownTemplate { template_id })?;
+		let layout = codec.layout.message(message.id).expect("every message has a layout");
+		let block_length = match codec.counter(header, "blockLength") {
+			Ok(counter) => usize::try_from(reader.counter(&counter, 0, header)?).unwrap_or(0),
+			Err(_) => layout.block_length as usize,
+		};
+		if let Ok(counter) = codec.counter(header, "version") {
+			reader.version = u32::try_from(reader.counter(&counter, 0, header)?).unwrap_or(0);
+		}
+
+		let header_length = codec.layout.header_length as usize;
+		let members = Members::message(message, layout);
+		let value = reader.block(&message.name, header_length, block_length, &members)?;
+		let decoded = Decoded {
+			name: message.name.clone(),
+			template_id: message.id,
+			block_length,
+			version: reader.version,
+			length: reader.limit,
+			value,
+		};
+		Ok((decoded, reader.spans))
+	}
+}
+
+/// Decode the message at the start of the buffer, see [`Decoder`].
+pub fn decode(schema: &Schema, buf: &[u8]) -> Result<Decoded> {
+	Decoder::new(schema)?.decode(buf)
+}
+
+struct Reader<'s, 'b> {
+	codec: &'s Codec<'s>,
+	buf: &'b [u8],
+	/// The acting version of the message.
+	version: u32,
+	/// End of the message read so far.
+	limit: usize,
+	/// The elements read so far when annotating.
+	spans: Option<Vec<Span>>,
+}
+
+impl<'s, 'b> Reader<'s, 'b> {
+	/// Decode the block at the offset followed by the groups and var data.
+	fn block(
+		&mut self,
+		path: &str,
+		offset: usize,
+		block_length: usize,
+		members: &Members<'s>,
+	) -> Result<Json> {
+		let mut object = Map::new();
+		self.limit = offset + block_length;
+		for (field, layout) in &members.fields {
+			let path = format!("{path}.{}", field.name);
+			let present = field.since_version.unwrap_or_default() <= self.version &&
+				(layout.offset + layout.size) as usize <= block_length;
+			let value = if present {
+				let encoding = self.codec.field(field, &path)?;
+				self.value(encoding, offset + layout.offset as usize, &path)?
+			} else {
+				Json::Null
+			};
+			object.insert(field.name.clone(), value);
+		}
+
+		for (group, layout) in &members.groups {
+			let path = format!("{path}.{}", group.name);
+			if group.since_version.unwrap_or_default() > self.version {
+				object.insert(group.name.clone(), Json::Null);
+				continue;
+			}
+			let (block_length, count) = self.codec.dimension(group)?;
+			let dimension = self.limit;
+			let block_length =
+				usize::try_from(self.counter(&block_length, dimension, &path)?).unwrap_or(0);
+			let count = usize::try_from(self.counter(&count, dimension, &path)?).unwrap_or(0);
+			self.composite(SpanKind::Dimension, &group.dimension_type, dimension, &path)?;
+			self.limit = dimension + layout.dimension_length as usize;
+			// every entry takes at least a byte, so a count from a malformed buffer cannot make
+			// the decoder loop over entries the buffer does not hold
+			let end = self.limit.saturating_add(count.saturating_mul(block_length.max(1)));
+			if end > self.buf.len() {
+				let available = self.buf.len();
+				return Err(CodecError::Truncated { path, end, available }.into());
+			}
+			let nested = Members::group(group, layout);
+			let mut entries = Vec::new();
+			for i in 0..count {
+				let offset = self.limit;
+				entries.push(self.block(&format!("{path}[{i}]"), offset, block_length, &nested)?);
+			}
+			object.insert(group.name.clone(), Json::Array(entries));
+		}
+
+		for (data, _) in &members.data {
+			let path = format!("{path}.{}", data.name);
+			if data.since_version.unwrap_or_default() > self.version {
+				object.insert(data.name.clone(), Json::Null);
+				continue;
+			}
+			let (length, start, text) = self.codec.data(data)?;
+			let offset = self.limit;
+			let length = usize::try_from(self.counter(&length, offset, &path)?).unwrap_or(0);
+			let bytes = self.bytes(offset + start, length, &path)?;
+			self.limit = offset + start + length;
+			let value = match text {
+				true => String::from_utf8_lossy(bytes).into_owned(),
+				false => hex(bytes),
+			};
+			let value = Json::String(value);
+			self.composite(SpanKind::Length, &data.r#type, offset, &path)?;
+			self.mark(offset + start, length, SpanKind::Data, &path, &value);
+			object.insert(data.name.clone(), value);
+		}
+		Ok(Json::Object(object))
+	}
+
+	fn value(&mut self, encoding: Encoding, offset: usize, path: &str) -> Result<Json> {
+		let value = match encoding {
+			Encoding::Constant(t) => t.constant()?.as_ref().map_or(Json::Null, json),
+			Encoding::EnumConstant(value_ref) => {
+				let name = value_ref.split_once('.').map_or(value_ref, |(_, name)| name);
+				Json::String(name.to_string())
+			},
+			Encoding::Primitive { primitive: PrimitiveType::Char, length, .. } if length != 1 => {
+				let bytes = self.bytes(offset, length as usize, path)?;
+				let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+				Json::String(String::from_utf8_lossy(&bytes[..end]).into_owned())
+			},
+			Encoding::Primitive { primitive, length, .. } if length != 1 => (0..length as usize)
+				.map(|i| {
+					Ok(json(&self.read(primitive, offset + i * primitive.size() as usize, path)?))
+				})
+				.collect::<Result<_>>()?,
+			Encoding::Primitive { primitive, .. } => {
+				let value = self.read(primitive, offset, path)?;
+				match encoding.null()? {
+					Some(null) if null.same(&value) => Json::Null,
+					_ => json(&value),
+				}
+			},
+			Encoding::Enum(e, primitive) => {
+				let value = self.read(primitive, offset, path)?;
+				let null = e.null(primitive)?.unwrap_or_else(|| primitive.null_value());
+				match e.valid_values().find(|v| v.parse(primitive).is_ok_and(|v| v == value)) {
+					Some(valid) => Json::String(valid.name.clone()),
+					None if null.same(&value) => Json::Null,
+					None => json(&value),
+				}
+			},
+			Encoding::Set(s, primitive) => {
+				let bits = self.read(primitive, offset, path)?.as_i128().unwrap_or_default();
+				let choices = s
+					.choices()
+					.map(|c| Ok((c.name.clone(), Json::Bool(bits >> c.bit(primitive)? & 1 == 1))))
+					.collect::<Result<_>>()?;
+				Json::Object(choices)
+			},
+			Encoding::Composite(composite, layouts) => {
+				let mut object = Map::new();
+				for (member, layout) in composite.members().into_iter().zip(layouts) {
+					let path = format!("{path}.{}", member.name());
+					let value = if member.since_version() <= self.version {
+						let encoding = self.codec.member(member, layout, &path)?;
+						self.value(encoding, offset + layout.offset as usize, &path)?
+					} else {
+						Json::Null
+					};
+					object.insert(member.name().to_string(), value);
+				}
+				Json::Object(object)
+			},
+		};
+		let size = match encoding {
+			Encoding::Primitive { primitive, length, .. } => primitive.size() * length,
+			Encoding::Enum(_, primitive) | Encoding::Set(_, primitive) => primitive.size(),
+			_ => 0,
+		};
+		self.mark(offset, size as usize, SpanKind::Field, path, &value);
+		Ok(value)
+	}
+
+	/// When annotating, read the members of the standalone composite at the offset as spans of
+	/// the kind, e.g. the members of the dimension of a group.
+	fn composite(&mut self, kind: SpanKind, name: &str, offset: usize, path: &str) -> Result<()> {
+		let Some(spans) = &self.spans else { return Ok(()) };
+		let first = spans.len();
+		let codec = self.codec;
+		let (Some(composite), Some(layout)) =
+			(codec.schema.composites().find(|c| c.name == name), codec.layout.composite(name))
+		else {
+			return Ok(());
+		};
+		self.value(Encoding::Composite(composite, &layout.members), offset, path)?;
+		for span in self.spans.iter_mut().flat_map(|spans| &mut spans[first..]) {
+			span.kind = kind;
+		}
+		Ok(())
+	}
+
+	fn mark(&mut self, offset: usize, length: usize, kind: SpanKind, path: &str, value: &Json) {
+		if let (Some(spans), true) = (&mut self.spans, length > 0) {
+			let label = path.to_string();
+			spans.push(Span { offset, length, kind, label, value: Some(value.clone()) });
+		}
+	}
+
+	/// Value of a counter of the composite at the offset, e.g. `numInGroup`.
+	fn counter(&self, counter: &Counter, offset: usize, path: &str) -> Result<i128> {
+		let value = match &counter.constant {
+			Some(value) => value.clone(),
+			None => self.read(counter.primitive, offset + counter.offset, path)?,
+		};
+		Ok(value.as_i128().unwrap_or_default())
+	}
+
+	fn read(&self, primitive: PrimitiveType, offset: usize, path: &str) -> Result<Value> {
+		let bytes = self.bytes(offset, primitive.size() as usize, path)?;
+		Ok(Value::read(primitive, bytes, self.codec.order).expect("bytes of the primitive size"))
+	}
+
+	fn bytes(&self, offset: usize, length: usize, path: &str) -> Result<&'b [u8]> {
+		let end = offset + length;
+		self.buf.get(offset..end).ok_or_else(|| {
+			CodecError::Truncated { path: path.to_string(), end, available: self.buf.len() }.into()
+		})
+	}
+}
+
+/// Bytes without a character encoding as a hex string.
+fn hex(bytes: &[u8]) -> String {
+	bytes.iter().fold(String::new(), |mut hex, b| {
+		let _ = write!(hex, "{b:02x}");
+		hex
+	})
+}
+
+/// JSON of a value, floats are printed as short as they are written, NaN as null.
+fn json(value: &Value) -> Json {
+	match *value {
+		Value::Char(c) => Json::String(char::from(c).to_string()),
+		Value::Float(v) => v
+			.to_string()
+			.parse()
+			.ok()
+			.and_then(Number::from_f64)
+			.map_or(Json::Null, Json::Number),
+		Value::Double(v) => Number::from_f64(v).map_or(Json::Null, Json::Number),
+		Value::Chars(ref s) => Json::String(s.clone()),
+		ref value => match value.as_i128() {
+			Some(v) if v < 0 => Json::from(v as i64),
+			Some(v) => Json::from(v as u64),
+			None => Json::Null,
+		},
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::path::Path;
+
+	use serde_json::json;
+
+	use super::*;
+	use crate::{SbeSchemaError, SchemaLoader};
+
+	fn load(file: &str) -> Schema {
+		let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/resources").join(file);
+		SchemaLoader::new().load(path).unwrap()
+	}
+
+	/// A `Car` of the json printer schema, encoded by a producer of the given version.
+	fn car(version: u16) -> Vec<u8> {
+		let block_length: u16 = if version >= 2 { 62 } else { 45 };
+		let mut buf = Vec::new();
+		for v in [block_length, 1, 1, version] {
+			buf.extend(v.to_le_bytes());
+		}
+		buf.extend(1234u64.to_le_bytes());
+		buf.extend(2013u16.to_le_bytes());
+		buf.extend([1, b'A']);
+		(0..5).for_each(|i: i32| buf.extend(i.to_le_bytes()));
+		buf.extend(b"abcdef");
+		buf.push(0b100);
+		buf.extend(2000u16.to_le_bytes());
+		buf.push(4);
+		buf.extend(b"123");
+		if version >= 2 {
+			buf.extend(7i64.to_le_bytes());
+			buf.extend(3i64.to_le_bytes());
+			buf.push(u8::MAX);
+		}
+		buf.extend(6u16.to_le_bytes());
+		buf.push(2);
+		for (speed, mpg) in [(30u16, 35.9f32), (55, 49.0)] {
+			buf.extend(speed.to_le_bytes());
+			buf.extend(mpg.to_le_bytes());
+		}
+		buf.extend(1u16.to_le_bytes());
+		buf.extend([1, 95]);
+		buf.extend(6u16.to_le_bytes());
+		buf.push(1);
+		buf.extend(30u16.to_le_bytes());
+		buf.extend(4.0f32.to_le_bytes());
+		for data in ["Honda", "Civic VTi", "abcdef"] {
+			buf.push(data.len() as u8);
+			buf.extend(data.as_bytes());
+		}
+		buf
+	}
+
+	fn expected(uuid: Json) -> Json {
+		json!({
+			"serialNumber": 1234,
+			"modelYear": 2013,
+			"available": "T",
+			"code": "A",
+			"someNumbers": [0, 1, 2, 3, 4],
+			"vehicleCode": "abcdef",
+			"extras": { "sunRoof": false, "sportsPack": false, "cruiseControl": true },
+			"engine": {
+				"capacity": 2000,
+				"numCylinders": 4,
+				"maxRpm": 9000,
+				"manufacturerCode": "123",
+				"fuel": "Petrol"
+			},
+			"uuid": uuid,
+			"cupHolderCount": null,
+			"fuelFigures": [{ "speed": 30, "mpg": 35.9 }, { "speed": 55, "mpg": 49.0 }],
+			"performanceFigures": [
+				{ "octaneRating": 95, "acceleration": [{ "mph": 30, "seconds": 4.0 }] }
+			],
+			"manufacturer": "Honda",
+			"model": "Civic VTi",
+			"activationCode": "abcdef"
+		})
+	}
+
+	#[test]
+	fn test_decode_json() {
+		let schema = load("json-printer-test-schema.xml");
+		let buf = car(2);
+		let decoded = decode(&schema, &buf).unwrap();
+		assert_eq!((decoded.name.as_str(), decoded.version, decoded.block_length), ("Car", 2, 62));
+		assert_eq!(decoded.length, buf.len());
+		assert_eq!(decoded.value.to_string(), expected(json!([7, 3])).to_string());
+	}
+
+	#[test]
+	fn test_decode_older_version() {
+		let schema = load("json-printer-test-schema.xml");
+		let mut buf = car(0);
+		buf.extend([0xff; 3]);
+		let decoded = decode(&schema, &buf).unwrap();
+		assert_eq!((decoded.version, decoded.block_length), (0, 45));
+		assert_eq!(decoded.length, buf.len() - 3);
+		assert_eq!(decoded.value.to_string(), expected(Json::Null).to_string());
+	}
+
+	#[test]
+	fn test_decode_errors() {
+		let schema = load("json-printer-test-schema.xml");
+		let buf = car(2);
+		assert!(matches!(
+			decode(&schema, &buf[..buf.len() - 1]),
+			Err(SbeSchemaError::Codec(CodecError::Truncated { ref path, .. })) if path == "Car.activationCode"
+		));
+		let mut buf = buf;
+		// the fuel figures claim 255 entries, also when their block length is 0
+		buf[8 + 62 + 2] = u8::MAX;
+		assert!(matches!(
+			decode(&schema, &buf),
+			Err(SbeSchemaError::Codec(CodecError::Truncated { ref path, .. })) if path == "Car.fuelFigures"
+		));
+		buf[8 + 62] = 0;
+		assert!(matches!(
+			decode(&schema, &buf),
+			Err(SbeSchemaError::Codec(CodecError::Truncated { ref path, .. })) if path == "Car.fuelFigures"
+		));
+		buf[2] = 9;
+		assert!(matches!(
+			decode(&schema, &buf),
+			Err(SbeSchemaError::Codec(CodecError::UnknownTemplate { template_id: 9 }))
+		));
+
+		let credentials =
+			[0, 0, 2, 0, 1, 0, 2, 0, 4, b't', b'e', b's', b't', 2, 0, 0, 0, 0xca, 0xfe];
+		let decoded = decode(&schema, &credentials).unwrap();
+		assert_eq!(decoded.value, json!({ "login": "test", "encryptedPassword": "cafe" }));
+	}
+}
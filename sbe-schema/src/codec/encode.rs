@@ -0,0 +1,429 @@
+//! Encoding of messages from JSON, in the form the decoder produces.
+
+use serde_json::{Map, Value as Json};
+
+use super::{Codec, CodecError, Counter, Encoding, Members};
+use crate::{
+	types::{PrimitiveType, SetType},
+	Result, SbeSchemaError, Schema, Value,
+};
+
+/// Encodes messages of a schema from JSON in the form of [`Decoded::value`]. Missing or null
+/// values of optional fields are encoded as their null value, missing groups and var data as
+/// empty.
+///
+/// [`Decoded::value`]: super::Decoded::value
+pub struct Encoder<'a> {
+	codec: Codec<'a>,
+}
+
+impl<'a> Encoder<'a> {
+	/// Create an encoder of the messages of the schema.
+	pub fn new(schema: &'a Schema) -> Result<Self> {
+		Ok(Self { codec: Codec::new(schema)? })
+	}
+
+	/// Encode the message with the name or template id, including the message header, in the
+	/// current version of the schema.
+	pub fn encode(&self, message: &str, value: &Json) -> Result<Vec<u8>> {
+		let codec = &self.codec;
+		let schema = codec.schema;
+		let message = schema
+			.messages()
+			.find(|m| m.name == message)
+			.or_else(|| message.parse().ok().and_then(|id| schema.message(id)))
+			.ok_or_else(|| CodecError::UnknownMessage { message: message.to_string() })?;
+		let layout = codec.layout.message(message.id).expect("every message has a layout");
+
+		let header = schema.header_type_name();
+		let header_length = codec.layout.header_length as usize;
+		let mut writer = Writer { codec, buf: vec![0; header_length] };
+		for (name, value) in [
+			("blockLength", i128::from(layout.block_length)),
+			("templateId", message.id.into()),
+			("schemaId", schema.id.unwrap_or_default().into()),
+			("version", schema.version.into()),
+		] {
+			if let Ok(counter) = codec.counter(header, name) {
+				writer.counter(&counter, 0, value, header)?;
+			}
+		}
+		let members = Members::message(message, layout);
+		let block_length = layout.block_length as usize;
+		writer.block(&message.name, header_length, block_length, &members, value)?;
+		Ok(writer.buf)
+	}
+}
+
+/// Encode the message with the name or template id, see [`Encoder`].
+pub fn encode(schema: &Schema, message: &str, value: &Json) -> Result<Vec<u8>> {
+	Encoder::new(schema)?.encode(message, value)
+}
+
+struct Writer<'s> {
+	codec: &'s Codec<'s>,
+	buf: Vec<u8>,
+}
+
+impl<'s> Writer<'s> {
+	/// Encode the block at the end of the buffer followed by the groups and var data.
+	fn block(
+		&mut self,
+		path: &str,
+		offset: usize,
+		block_length: usize,
+		members: &Members<'s>,
+		value: &Json,
+	) -> Result<()> {
+		let empty = Map::new();
+		let object = object(value, &empty, path)?;
+		let known = |name: &str| {
+			members.fields.iter().any(|(f, _)| f.name == name) ||
+				members.groups.iter().any(|(g, _)| g.name == name) ||
+				members.data.iter().any(|(d, _)| d.name == name)
+		};
+		if let Some(name) = object.keys().find(|name| !known(name)) {
+			return Err(invalid(path, format!("unknown member `{name}`")));
+		}
+
+		// the padding of the block stays zero
+		self.buf.resize(offset + block_length, 0);
+		for (field, layout) in &members.fields {
+			let path = format!("{path}.{}", field.name);
+			let encoding = self.codec.field(field, &path)?;
+			let value = object.get(&field.name).unwrap_or(&Json::Null);
+			self.value(encoding, offset + layout.offset as usize, value, &path)?;
+		}
+		for (group, layout) in &members.groups {
+			let path = format!("{path}.{}", group.name);
+			let entries = match object.get(&group.name) {
+				None | Some(Json::Null) => &[][..],
+				Some(Json::Array(entries)) => entries,
+				Some(_) => return Err(invalid(&path, "expected an array of entries")),
+			};
+			let (block_length, count) = self.codec.dimension(group)?;
+			let dimension = self.buf.len();
+			self.buf.resize(dimension + layout.dimension_length as usize, 0);
+			self.counter(&block_length, dimension, layout.block_length.into(), &path)?;
+			self.counter(&count, dimension, entries.len() as i128, &path)?;
+			let nested = Members::group(group, layout);
+			for (i, entry) in entries.iter().enumerate() {
+				let path = format!("{path}[{i}]");
+				let offset = self.buf.len();
+				self.block(&path, offset, layout.block_length as usize, &nested, entry)?;
+			}
+		}
+		for (data, _) in &members.data {
+			let path = format!("{path}.{}", data.name);
+			let (length, start, text) = self.codec.data(data)?;
+			let bytes = match object.get(&data.name) {
+				None | Some(Json::Null) => Vec::new(),
+				Some(Json::String(s)) if text => s.as_bytes().to_vec(),
+				Some(Json::String(s)) =>
+					unhex(s).ok_or_else(|| invalid(&path, "expected a hex string"))?,
+				Some(_) => return Err(invalid(&path, "expected a string")),
+			};
+			let offset = self.buf.len();
+			self.buf.resize(offset + start, 0);
+			self.counter(&length, offset, bytes.len() as i128, &path)?;
+			self.buf.extend(bytes);
+		}
+		Ok(())
+	}
+
+	fn value(&mut self, encoding: Encoding, offset: usize, value: &Json, path: &str) -> Result<()> {
+		let order = self.codec.order;
+		match encoding {
+			Encoding::Constant(_) | Encoding::EnumConstant(_) => {},
+			Encoding::Primitive { primitive, length, optional, t } if length != 1 => {
+				let element = Encoding::Primitive { primitive, length: 1, optional, t };
+				let size = primitive.size() as usize;
+				match value {
+					Json::String(s) if primitive == PrimitiveType::Char => {
+						if s.len() > length as usize {
+							let message = format!("longer than {length} characters");
+							return Err(invalid(path, message));
+						}
+						self.put(offset, s.as_bytes());
+					},
+					Json::Array(values) if values.len() == length as usize =>
+						for (i, value) in values.iter().enumerate() {
+							let path = format!("{path}[{i}]");
+							self.value(element, offset + i * size, value, &path)?;
+						},
+					Json::Null =>
+						for i in 0..length as usize {
+							self.value(element, offset + i * size, value, path)?;
+						},
+					_ => {
+						let message = format!("expected an array of {length} values");
+						return Err(invalid(path, message));
+					},
+				}
+			},
+			Encoding::Primitive { primitive, .. } => {
+				let value = match value {
+					Json::Null => encoding.null()?.ok_or_else(|| invalid(path, "missing value"))?,
+					value => primitive_value(primitive, value, path)?,
+				};
+				self.put(offset, &value.to_bytes(order));
+			},
+			Encoding::Enum(e, primitive) => {
+				let value = match value {
+					Json::String(name) => match e.valid_values().find(|v| v.name == *name) {
+						Some(valid) => valid.parse(primitive)?,
+						None => {
+							let message = format!("`{name}` is not a value of `{}`", e.name);
+							return Err(invalid(path, message));
+						},
+					},
+					Json::Null => e.null(primitive)?.unwrap_or_else(|| primitive.null_value()),
+					value => primitive_value(primitive, value, path)?,
+				};
+				self.put(offset, &value.to_bytes(order));
+			},
+			Encoding::Set(s, primitive) => {
+				let value = match value {
+					Json::Object(flags) =>
+						integer(primitive, bits(s, primitive, flags, path)?, path)?,
+					Json::Null => integer(primitive, 0, path)?,
+					value => primitive_value(primitive, value, path)?,
+				};
+				self.put(offset, &value.to_bytes(order));
+			},
+			Encoding::Composite(composite, layouts) => {
+				let empty = Map::new();
+				let object = object(value, &empty, path)?;
+				let members = composite.members();
+				if let Some(name) =
+					object.keys().find(|name| !members.iter().any(|m| m.name() == *name))
+				{
+					return Err(invalid(path, format!("unknown member `{name}`")));
+				}
+				for (member, layout) in members.into_iter().zip(layouts) {
+					let path = format!("{path}.{}", member.name());
+					let encoding = self.codec.member(member, layout, &path)?;
+					let value = object.get(member.name()).unwrap_or(&Json::Null);
+					self.value(encoding, offset + layout.offset as usize, value, &path)?;
+				}
+			},
+		}
+		Ok(())
+	}
+
+	/// Set a counter of the composite at the offset, e.g. `numInGroup`, unless it is constant.
+	fn counter(&mut self, counter: &Counter, offset: usize, value: i128, path: &str) -> Result<()> {
+		if counter.constant.is_none() {
+			let value = integer(counter.primitive, value, path)?;
+			self.put(offset + counter.offset, &value.to_bytes(self.codec.order));
+		}
+		Ok(())
+	}
+
+	fn put(&mut self, offset: usize, bytes: &[u8]) {
+		self.buf[offset..offset + bytes.len()].copy_from_slice(bytes);
+	}
+}
+
+/// The members of an object, of an empty one for null.
+fn object<'j>(
+	value: &'j Json,
+	empty: &'j Map<String, Json>,
+	path: &str,
+) -> Result<&'j Map<String, Json>> {
+	match value {
+		Json::Object(object) => Ok(object),
+		Json::Null => Ok(empty),
+		_ => Err(invalid(path, "expected an object")),
+	}
+}
+
+/// The bits of the flags of the choices of a set that are true.
+fn bits(
+	s: &SetType,
+	primitive: PrimitiveType,
+	flags: &Map<String, Json>,
+	path: &str,
+) -> Result<i128> {
+	let mut bits = 0;
+	for (name, flag) in flags {
+		let unknown = || invalid(path, format!("`{name}` is not a choice of `{}`", s.name));
+		let choice = s.choices().find(|c| c.name == *name).ok_or_else(unknown)?;
+		match flag {
+			Json::Bool(true) => bits |= 1 << choice.bit(primitive)?,
+			Json::Bool(false) | Json::Null => {},
+			_ => return Err(invalid(path, "expected a bool")),
+		}
+	}
+	Ok(bits)
+}
+
+/// A value of the primitive type from a number, or from a string for characters and for numbers
+/// JSON cannot hold, e.g. `"NaN"`.
+fn primitive_value(primitive: PrimitiveType, value: &Json, path: &str) -> Result<Value> {
+	let parse =
+		|text: &str| Value::parse(primitive, text).map_err(|e| invalid(path, e.to_string()));
+	match (primitive, value) {
+		(PrimitiveType::Char, Json::String(s)) if s.len() == 1 => Ok(Value::Char(s.as_bytes()[0])),
+		(PrimitiveType::Char, Json::Number(n)) =>
+			integer(primitive, n.as_i64().unwrap_or(-1).into(), path),
+		(_, Json::Number(n)) => parse(&n.to_string()),
+		(_, Json::String(s)) => parse(s),
+		_ => Err(invalid(path, format!("expected a {primitive} value"))),
+	}
+}
+
+/// A value of the integer primitive type, or a character of its code.
+fn integer(primitive: PrimitiveType, value: i128, path: &str) -> Result<Value> {
+	match primitive {
+		PrimitiveType::Char => u8::try_from(value).map(Value::Char).map_err(|_| {
+			invalid(path, format!("value {value} is outside the range of {primitive}"))
+		}),
+		_ => Value::parse(primitive, &value.to_string()).map_err(|e| invalid(path, e.to_string())),
+	}
+}
+
+/// Bytes of a hex string, as the decoder prints var data without a character encoding.
+fn unhex(hex: &str) -> Option<Vec<u8>> {
+	if hex.len() % 2 != 0 {
+		return None;
+	}
+	(0..hex.len())
+		.step_by(2)
+		.map(|i| hex.get(i..i + 2).and_then(|b| u8::from_str_radix(b, 16).ok()))
+		.collect()
+}
+
+fn invalid(path: &str, message: impl Into<String>) -> SbeSchemaError {
+	CodecError::Invalid { message: message.into(), path: path.to_string() }.into()
+}
+
+#[cfg(test)]
+mod tests {
+	use std::path::Path;
+
+	use serde_json::json;
+
+	use super::*;
+	use crate::{decode, SchemaLoader};
+
+	fn load(file: &str) -> Schema {
+		let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/resources").join(file);
+		SchemaLoader::new().load(path).unwrap()
+	}
+
+	fn car() -> Json {
+		json!({
+			"serialNumber": 1234,
+			"modelYear": 2013,
+			"available": "T",
+			"code": "A",
+			"someNumbers": [0, 1, 2, 3, 4],
+			"vehicleCode": "abcdef",
+			"extras": { "cruiseControl": true },
+			"engine": { "capacity": 2000, "numCylinders": 4, "manufacturerCode": "123" },
+			"fuelFigures": [{ "speed": 30, "mpg": 35.9 }, { "speed": 55, "mpg": 49.0 }],
+			"performanceFigures": [
+				{ "octaneRating": 95, "acceleration": [{ "mph": 30, "seconds": 4.0 }] }
+			],
+			"manufacturer": "Honda",
+			"model": "Civic VTi"
+		})
+	}
+
+	#[test]
+	fn test_encode_round_trip() {
+		let schema = load("json-printer-test-schema.xml");
+		let buf = encode(&schema, "Car", &car()).unwrap();
+		assert_eq!(buf[..8], [62, 0, 1, 0, 1, 0, 2, 0]);
+		// the optional fields are null
+		assert_eq!(
+			buf[8 + 45..8 + 62],
+			[0, 0, 0, 0, 0, 0, 0, 0x80, 0, 0, 0, 0, 0, 0, 0, 0x80, 0xff]
+		);
+
+		let decoded = decode(&schema, &buf).unwrap();
+		assert_eq!(decoded.length, buf.len());
+		let value = &decoded.value;
+		assert_eq!(value["uuid"], json!([i64::MIN, i64::MIN]));
+		assert_eq!(value["cupHolderCount"], Json::Null);
+		assert_eq!(
+			value["extras"],
+			json!({ "sunRoof": false, "sportsPack": false, "cruiseControl": true })
+		);
+		assert_eq!(value["activationCode"], "");
+		assert_eq!(encode(&schema, "1", value).unwrap(), buf);
+	}
+
+	#[test]
+	fn test_encode_data() {
+		let schema = load("json-printer-test-schema.xml");
+		let credentials = json!({ "login": "test", "encryptedPassword": "cafe" });
+		let buf = encode(&schema, "Credentials", &credentials).unwrap();
+		assert_eq!(
+			buf,
+			[0, 0, 2, 0, 1, 0, 2, 0, 4, b't', b'e', b's', b't', 2, 0, 0, 0, 0xca, 0xfe]
+		);
+
+		let schema = load("example-bigendian-test-schema.xml");
+		let car = json!({
+			"serialNumber": 1,
+			"modelYear": 2013,
+			"available": "F",
+			"code": "B",
+			"someNumbers": [1, 2, 3, 4, 5],
+			"vehicleCode": "ab",
+			"extras": 0,
+			"engine": {
+				"capacity": 2000,
+				"numCylinders": 4,
+				"manufacturerCode": "123",
+				"efficiency": 35,
+				"booster": { "BoostType": "TURBO", "horsePower": 200 }
+			},
+			"fuelFigures": [{ "speed": 30, "mpg": 35.9, "usageDescription": "Urban Cycle" }]
+		});
+		let buf = encode(&schema, "Car", &car).unwrap();
+		assert_eq!(buf[..8], [0, 49, 0, 1, 0, 1, 0, 0]);
+		let decoded = decode(&schema, &buf).unwrap();
+		assert_eq!(decoded.value["fuelFigures"][0]["usageDescription"], "Urban Cycle");
+		assert_eq!(decoded.value["engine"]["booster"]["horsePower"], 200);
+		assert_eq!(decoded.value["vehicleCode"], "ab");
+	}
+
+	#[test]
+	fn test_encode_errors() {
+		let schema = load("json-printer-test-schema.xml");
+		let error = |message: &str, value: Json| match encode(&schema, message, &value) {
+			Err(SbeSchemaError::Codec(CodecError::Invalid { message, path })) =>
+				format!("{path}: {message}"),
+			result => panic!("unexpected {result:?}"),
+		};
+		assert!(matches!(
+			encode(&schema, "Truck", &json!({})),
+			Err(SbeSchemaError::Codec(CodecError::UnknownMessage { .. }))
+		));
+		let mut car = car();
+		car["colour"] = json!("red");
+		assert_eq!(error("Car", car), "Car: unknown member `colour`");
+		let mut car = self::car();
+		car["engine"]["capacity"] = Json::Null;
+		assert_eq!(error("Car", car), "Car.engine.capacity: missing value");
+		let mut car = self::car();
+		car["vehicleCode"] = json!("abcdefg");
+		assert_eq!(error("Car", car), "Car.vehicleCode: longer than 6 characters");
+		let mut car = self::car();
+		car["fuelFigures"][1]["speed"] = json!(-1);
+		assert_eq!(
+			error("Car", car),
+			"Car.fuelFigures[1].speed: value -1 is outside the range of uint16"
+		);
+		let mut car = self::car();
+		car["code"] = json!("D");
+		assert_eq!(error("Car", car), "Car.code: `D` is not a value of `Model`");
+		assert_eq!(
+			error("Credentials", json!({ "encryptedPassword": "xyz" })),
+			"Credentials.encryptedPassword: expected a hex string"
+		);
+	}
+}
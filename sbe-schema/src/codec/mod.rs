@@ -0,0 +1,283 @@
+//! Encoding and decoding of messages driven by the schema, without generated code.
+
+mod annotate;
+mod decode;
+mod encode;
+
+use thiserror::Error;
+
+use crate::{
+	layout::{
+		layout, DataLayout, FieldLayout, GroupLayout, MemberLayout, MessageLayout, SchemaLayout,
+	},
+	types::{
+		ByteOrder, Composite, CompositeMember, Data, EncodingType, EnumType, Field, Group, Message,
+		Presence, PrimitiveType, SetType, Type,
+	},
+	vtable::{build_vtable, VTable, VTableObject},
+	Result, Schema, Value,
+};
+
+pub use annotate::{annotate, Annotated, Span, SpanKind};
+pub use decode::{decode, Decoded, Decoder};
+pub use encode::{encode, Encoder};
+
+/// Errors of messages that do not match the schema.
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum CodecError {
+	/// The schema has no message with the template id.
+	#[error("unknown template id {template_id}")]
+	UnknownTemplate {
+		/// The template id of the message header.
+		template_id: i128,
+	},
+	/// The schema has no message with the name or id.
+	#[error("unknown message `{message}`")]
+	UnknownMessage {
+		/// The name or id of the message.
+		message: String,
+	},
+	/// The message is shorter than its encoding requires.
+	#[error("{path}: needs {end} bytes, only {available} available")]
+	Truncated {
+		/// Path of the element being read.
+		path: String,
+		/// End of the element in the buffer.
+		end: usize,
+		/// Length of the buffer.
+		available: usize,
+	},
+	/// A value to encode does not match the schema.
+	#[error("{path}: {message}")]
+	Invalid {
+		/// Description of the problem.
+		message: String,
+		/// Path of the element.
+		path: String,
+	},
+	/// The schema uses an encoding that cannot be encoded or decoded.
+	#[error("{path}: {message}")]
+	Unsupported {
+		/// Description of the problem.
+		message: String,
+		/// Path of the element.
+		path: String,
+	},
+}
+
+/// How a field or a member of a composite is encoded.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Encoding<'s> {
+	/// A primitive value or an array of them, of a `<type>` unless a primitive type is used
+	/// directly.
+	Primitive { primitive: PrimitiveType, length: u32, optional: bool, t: Option<&'s Type> },
+	/// A constant of a `<type>`, not encoded on the wire.
+	Constant(&'s Type),
+	/// A constant referencing a valid value of an enum, e.g. `Model.C`.
+	EnumConstant(&'s str),
+	/// A value of an enum.
+	Enum(&'s EnumType, PrimitiveType),
+	/// A bitset of a set.
+	Set(&'s SetType, PrimitiveType),
+	/// A composite with the layouts of its members.
+	Composite(&'s Composite, &'s [MemberLayout]),
+}
+
+impl Encoding<'_> {
+	/// The null value of an optional primitive, `None` if the value cannot be null.
+	pub(crate) fn null(&self) -> Result<Option<Value>> {
+		match *self {
+			Encoding::Primitive { optional: true, length: 1, t: Some(t), .. } =>
+				Ok(Some(t.null()?)),
+			Encoding::Primitive { optional: true, length: 1, primitive, t: None } =>
+				Ok(Some(primitive.null_value())),
+			_ => Ok(None),
+		}
+	}
+}
+
+/// The fields, groups and var data of a message or a group entry with their layouts.
+pub(crate) struct Members<'s> {
+	pub(crate) fields: Vec<(&'s Field, &'s FieldLayout)>,
+	pub(crate) groups: Vec<(&'s Group, &'s GroupLayout)>,
+	pub(crate) data: Vec<(&'s Data, &'s DataLayout)>,
+}
+
+impl<'s> Members<'s> {
+	pub(crate) fn message(message: &'s Message, layout: &'s MessageLayout) -> Self {
+		Self {
+			fields: message.fields().zip(&layout.fields).collect(),
+			groups: message.groups().zip(&layout.groups).collect(),
+			data: message.data().zip(&layout.data).collect(),
+		}
+	}
+
+	pub(crate) fn group(group: &'s Group, layout: &'s GroupLayout) -> Self {
+		Self {
+			fields: group.fields().zip(&layout.fields).collect(),
+			groups: group.groups().zip(&layout.groups).collect(),
+			data: group.data().zip(&layout.data).collect(),
+		}
+	}
+}
+
+/// A primitive member of a composite used as a counter, e.g. `numInGroup` of a dimension.
+#[derive(Debug)]
+pub(crate) struct Counter {
+	pub(crate) offset: usize,
+	pub(crate) primitive: PrimitiveType,
+	pub(crate) constant: Option<Value>,
+}
+
+/// Resolves the encodings of the elements of a schema.
+pub(crate) struct Codec<'a> {
+	pub(crate) schema: &'a Schema,
+	pub(crate) layout: SchemaLayout,
+	pub(crate) order: ByteOrder,
+	vtable: VTable<'a>,
+}
+
+impl<'a> Codec<'a> {
+	pub(crate) fn new(schema: &'a Schema) -> Result<Self> {
+		Ok(Self {
+			schema,
+			layout: layout(schema)?,
+			order: schema.byte_order(),
+			vtable: build_vtable(schema),
+		})
+	}
+
+	/// Encoding of a field of a message or a group.
+	pub(crate) fn field<'s>(&'s self, field: &'s Field, path: &str) -> Result<Encoding<'s>> {
+		self.reference(&field.r#type, field.presence, field.value_ref.as_deref(), path)
+	}
+
+	/// Encoding of a member of a composite.
+	pub(crate) fn member<'s>(
+		&'s self,
+		member: CompositeMember<'s>,
+		layout: &'s MemberLayout,
+		path: &str,
+	) -> Result<Encoding<'s>> {
+		match member {
+			CompositeMember::Type(t) => type_encoding(t, t.presence.unwrap_or_default(), path),
+			CompositeMember::Ref(r) =>
+				self.reference(&r.ref_type, r.presence, r.value_ref.as_deref(), path),
+			CompositeMember::Enum(e) =>
+				Ok(Encoding::Enum(e, self.primitive(e.encoding_type.as_ref(), path)?)),
+			CompositeMember::Set(s) =>
+				Ok(Encoding::Set(s, self.primitive(s.encoding_type.as_ref(), path)?)),
+			CompositeMember::Composite(c) => Ok(Encoding::Composite(c, &layout.members)),
+		}
+	}
+
+	fn reference<'s>(
+		&'s self,
+		type_name: &str,
+		presence: Option<Presence>,
+		value_ref: Option<&'s str>,
+		path: &str,
+	) -> Result<Encoding<'s>> {
+		if let (Some(Presence::Constant), Some(value_ref)) = (presence, value_ref) {
+			return Ok(Encoding::EnumConstant(value_ref));
+		}
+		let unresolved = || unsupported(path, format!("unresolved type `{type_name}`"));
+		match self.vtable.resolve(type_name).ok_or_else(unresolved)? {
+			VTableObject::Primitive(primitive) => Ok(Encoding::Primitive {
+				primitive: *primitive,
+				length: 1,
+				optional: presence == Some(Presence::Optional),
+				t: None,
+			}),
+			VTableObject::Type(t) =>
+				type_encoding(t, presence.or(t.presence).unwrap_or_default(), path),
+			VTableObject::Enum(e) =>
+				Ok(Encoding::Enum(e, self.primitive(e.encoding_type.as_ref(), path)?)),
+			VTableObject::Set(s) =>
+				Ok(Encoding::Set(s, self.primitive(s.encoding_type.as_ref(), path)?)),
+			VTableObject::Composite(c) => {
+				let layout = self.layout.composite(&c.name).ok_or_else(unresolved)?;
+				Ok(Encoding::Composite(c, &layout.members))
+			},
+		}
+	}
+
+	fn primitive(&self, encoding: Option<&EncodingType>, path: &str) -> Result<PrimitiveType> {
+		encoding
+			.and_then(|e| e.primitive_type(self.schema))
+			.ok_or_else(|| unsupported(path, "encodingType is not a primitive type".to_string()))
+	}
+
+	/// A primitive member of a standalone composite, e.g. `templateId` of the message header.
+	pub(crate) fn counter(&self, composite: &str, name: &str) -> Result<Counter> {
+		let missing = || unsupported(composite, format!("composite has no `{name}` member"));
+		let layout = self.layout.composite(composite).ok_or_else(missing)?;
+		let definition =
+			self.schema.composites().find(|c| c.name == composite).ok_or_else(missing)?;
+		let (member, layout) = definition
+			.members()
+			.into_iter()
+			.zip(&layout.members)
+			.find(|(_, layout)| layout.name == name)
+			.ok_or_else(missing)?;
+		let t = match member {
+			CompositeMember::Type(t) => t,
+			CompositeMember::Ref(r) => match self.vtable.resolve(&r.ref_type) {
+				Some(VTableObject::Type(t)) => t,
+				_ => return Err(missing()),
+			},
+			_ => return Err(missing()),
+		};
+		let primitive = t.primitive_type.ok_or_else(missing)?;
+		let constant = match t.presence {
+			Some(Presence::Constant) => t.constant()?,
+			_ => None,
+		};
+		Ok(Counter { offset: layout.offset as usize, primitive, constant })
+	}
+
+	/// The `blockLength` and `numInGroup` members of the dimension of a group.
+	pub(crate) fn dimension(&self, group: &Group) -> Result<(Counter, Counter)> {
+		let block_length = self.counter(&group.dimension_type, "blockLength")?;
+		Ok((block_length, self.counter(&group.dimension_type, "numInGroup")?))
+	}
+
+	/// The `length` member of the composite of a var data, the offset of the bytes after it and
+	/// whether the bytes are text.
+	pub(crate) fn data(&self, data: &Data) -> Result<(Counter, usize, bool)> {
+		let length = self.counter(&data.r#type, "length")?;
+		let start = match self.counter(&data.r#type, "varData") {
+			Ok(var_data) => var_data.offset,
+			Err(_) => length.offset + length.primitive.size() as usize,
+		};
+		let text = self
+			.schema
+			.composites()
+			.find(|c| c.name == data.r#type)
+			.and_then(|c| c.types.iter().flatten().find(|t| t.name == "varData"))
+			.is_some_and(|t| t.character_encoding.is_some());
+		Ok((length, start, text))
+	}
+}
+
+fn type_encoding<'s>(t: &'s Type, presence: Presence, path: &str) -> Result<Encoding<'s>> {
+	if presence == Presence::Constant {
+		return Ok(match t.value_ref.as_deref() {
+			Some(value_ref) => Encoding::EnumConstant(value_ref),
+			None => Encoding::Constant(t),
+		});
+	}
+	let primitive = t
+		.primitive_type
+		.ok_or_else(|| unsupported(path, format!("type `{}` has no primitiveType", t.name)))?;
+	Ok(Encoding::Primitive {
+		primitive,
+		length: t.length.unwrap_or(1),
+		optional: presence == Presence::Optional,
+		t: Some(t),
+	})
+}
+
+fn unsupported(path: &str, message: String) -> crate::SbeSchemaError {
+	CodecError::Unsupported { message, path: path.to_string() }.into()
+}
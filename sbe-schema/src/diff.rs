@@ -0,0 +1,196 @@
+//! Semantic difference between two schemas.
+//!
+//! Elements are matched by id where SBE has one (messages, fields, groups, var data) and by name
+//! otherwise. Descriptions and the order of `<types>` are ignored, so only changes that matter
+//! are reported.
+
+use std::fmt::Debug;
+
+use serde::Serialize;
+
+use crate::{
+	ChangeKind, CompatibilityLevel, CompatibilityReport, PartialCompatibility, Result,
+	SbeSchemaValidator, Schema, SchemaValidator,
+};
+
+/// Single difference between two schemas.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Difference {
+	/// Path of the changed element, e.g. `NewOrder/field[id=38]`.
+	pub path: String,
+	/// The kind of the change.
+	pub kind: ChangeKind,
+	/// Human readable description of the change.
+	pub description: String,
+}
+
+/// All differences found between two schemas.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SchemaDiff {
+	differences: Vec<Difference>,
+}
+
+impl SchemaDiff {
+	/// All differences found.
+	pub fn differences(&self) -> &[Difference] {
+		&self.differences
+	}
+
+	/// Check if the schemas are semantically the same.
+	pub fn is_empty(&self) -> bool {
+		self.differences.is_empty()
+	}
+
+	fn push(&mut self, path: &str, kind: ChangeKind, description: String) {
+		self.differences.push(Difference { path: path.to_string(), kind, description });
+	}
+}
+
+impl From<CompatibilityReport> for SchemaDiff {
+	fn from(report: CompatibilityReport) -> Self {
+		let differences = report
+			.findings()
+			.iter()
+			.map(|f| Difference {
+				path: f.path.clone(),
+				kind: f.kind,
+				description: f.reason.clone(),
+			})
+			.collect();
+		Self { differences }
+	}
+}
+
+/// Compute the semantic difference between the old and the new schema.
+pub fn diff(old: &Schema, new: &Schema) -> Result<SchemaDiff> {
+	let validator = SbeSchemaValidator::new(old, new);
+	let mut report = validator.compare_version()?;
+
+	match (old.message_header(), new.message_header()) {
+		(Some(old_header), Some(new_header)) =>
+			new_header.compare(old_header, &new_header.name, &mut report),
+		(Some(old_header), None) => report.push(
+			&old_header.name,
+			ChangeKind::Removed,
+			CompatibilityLevel::None,
+			"message header removed",
+		),
+		(None, Some(new_header)) => report.push(
+			&new_header.name,
+			ChangeKind::Added,
+			CompatibilityLevel::None,
+			"message header added",
+		),
+		(None, None) => {},
+	}
+
+	report.extend(validator.compare_types()?);
+	report.extend(validator.compare_messages()?);
+
+	let mut diff = SchemaDiff::default();
+	compare_attribute(&mut diff, "package", old.package.as_ref(), new.package.as_ref());
+	compare_attribute(&mut diff, "id", old.id.as_ref(), new.id.as_ref());
+	compare_attribute(
+		&mut diff,
+		"semanticVersion",
+		old.semantic_version.as_ref(),
+		new.semantic_version.as_ref(),
+	);
+	compare_attribute(&mut diff, "byteOrder", old.byte_order.as_ref(), new.byte_order.as_ref());
+	compare_attribute(&mut diff, "headerType", old.header_type.as_ref(), new.header_type.as_ref());
+	diff.differences.extend(SchemaDiff::from(report).differences);
+
+	Ok(diff)
+}
+
+fn compare_attribute<T: PartialEq + Debug>(
+	diff: &mut SchemaDiff,
+	name: &str,
+	old: Option<&T>,
+	new: Option<&T>,
+) {
+	if old != new {
+		let describe = |v: Option<&T>| v.map_or_else(|| "unset".to_string(), |v| format!("{v:?}"));
+		diff.push(
+			name,
+			ChangeKind::Modified,
+			format!("{name} changed from {} to {}", describe(old), describe(new)),
+		);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use quick_xml::de::from_str;
+
+	const XML: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+		<sbe:messageSchema xmlns:sbe="http://fixprotocol.io/2016/sbe" id="1" version="1">
+			<types>
+				<composite name="messageHeader" description="header">
+					<type name="blockLength" primitiveType="uint16"/>
+					<type name="templateId" primitiveType="uint16"/>
+					<type name="schemaId" primitiveType="uint16"/>
+					<type name="version" primitiveType="uint16"/>
+				</composite>
+				<enum name="Side" encodingType="char">
+					<validValue name="Buy">1</validValue>
+					<validValue name="Sell">2</validValue>
+				</enum>
+			</types>
+			<types>
+				<set name="Flags" encodingType="uint8">
+					<choice name="A">0</choice>
+				</set>
+			</types>
+			<sbe:message name="NewOrder" id="1" description="new order">
+				<field name="side" id="54" type="Side"/>
+				<field name="qty" id="38" type="int32"/>
+			</sbe:message>
+		</sbe:messageSchema>"#;
+
+	#[test]
+	fn test_diff_ignores_descriptions_and_types_order() {
+		let old: Schema = from_str(XML).unwrap();
+		let (side, flags) = (&XML[XML.find("<enum").unwrap()..], &XML[XML.find("<set").unwrap()..]);
+		let side = &side[..side.find("</enum>").unwrap() + "</enum>".len()];
+		let flags = &flags[..flags.find("</set>").unwrap() + "</set>".len()];
+		let moved = XML
+			.replace(side, "\u{0}")
+			.replace(flags, side)
+			.replace('\u{0}', flags)
+			.replace(r#"description="new order""#, r#"description="a new order""#)
+			.replace(
+				r#"<composite name="messageHeader" description="header">"#,
+				"<composite name=\"messageHeader\">",
+			);
+		assert!(moved.find("<set") < moved.find("<enum"));
+		let new: Schema = from_str(&moved).unwrap();
+		assert!(diff(&old, &new).unwrap().is_empty());
+	}
+
+	#[test]
+	fn test_diff_reports_changes() {
+		let old: Schema = from_str(XML).unwrap();
+		let changed = XML
+			.replace(
+				r#"<choice name="A">0</choice>"#,
+				r#"<choice name="A">0</choice><choice name="B">1</choice>"#,
+			)
+			.replace(r#"<validValue name="Sell">2</validValue>"#, "")
+			.replace(r#"type="int32""#, r#"type="int64""#);
+		let new: Schema = from_str(&changed).unwrap();
+
+		let diff = diff(&old, &new).unwrap();
+		let differences =
+			diff.differences().iter().map(|d| (d.path.as_str(), d.kind)).collect::<Vec<_>>();
+		assert_eq!(
+			differences,
+			vec![
+				("types/enum[name=Side]/validValue[name=Sell]", ChangeKind::Removed),
+				("types/set[name=Flags]/choice[name=B]", ChangeKind::Added),
+				("NewOrder/field[id=38]", ChangeKind::TypeChanged),
+			]
+		);
+	}
+}
@@ -3,3 +3,31 @@
 //! Allowed:
 //! - Delete fields
 //! - Add optional fields
+
+use crate::{CompatibilityLevel, CompatibilityReport, EvolutionStrategy};
+
+use super::{EvolutionError, SchemaValidator};
+
+/// Schema evolution strategy that accept deleting fields and adding optional fields.
+pub struct BackwardCompatibility<V: SchemaValidator> {
+	validator: V,
+}
+
+impl<V: SchemaValidator> BackwardCompatibility<V> {
+	/// Create a new `BackwardCompatibility` strategy with the given validator.
+	pub fn new(validator: V) -> Self {
+		Self { validator }
+	}
+}
+
+impl<V: SchemaValidator> EvolutionStrategy for BackwardCompatibility<V> {
+	type SchemaType = V::SchemaType;
+
+	fn compatibility_level(&self) -> CompatibilityLevel {
+		CompatibilityLevel::Backward
+	}
+
+	fn report(&self) -> Result<CompatibilityReport, EvolutionError> {
+		self.validator.compare_all()
+	}
+}
@@ -0,0 +1,87 @@
+use crate::{
+	evolution::Element,
+	types::{
+		Choice, Composite, Data, EnumType, Field, Group, Message, Ref, SetType, Type, ValidValue,
+	},
+};
+
+impl Element for Message {
+	fn key(&self) -> String {
+		// messages are identified by template id, the name is not part of the wire format
+		self.id.to_string()
+	}
+
+	fn segment(&self) -> String {
+		self.name.clone()
+	}
+}
+
+impl Element for Field {
+	fn key(&self) -> String {
+		format!("field[id={}]", self.id)
+	}
+}
+
+impl Element for Group {
+	fn key(&self) -> String {
+		format!("group[id={}]", self.id)
+	}
+}
+
+impl Element for Data {
+	fn key(&self) -> String {
+		format!("data[id={}]", self.id)
+	}
+}
+
+impl Element for Composite {
+	fn key(&self) -> String {
+		format!("composite[name={}]", self.name)
+	}
+}
+
+impl Element for Type {
+	fn key(&self) -> String {
+		format!("type[name={}]", self.name)
+	}
+}
+
+impl Element for Ref {
+	fn key(&self) -> String {
+		format!("ref[name={}]", self.name)
+	}
+}
+
+impl Element for EnumType {
+	fn key(&self) -> String {
+		format!("enum[name={}]", self.name)
+	}
+}
+
+impl Element for ValidValue {
+	fn key(&self) -> String {
+		format!("validValue[name={}]", self.name)
+	}
+}
+
+impl Element for SetType {
+	fn key(&self) -> String {
+		format!("set[name={}]", self.name)
+	}
+}
+
+impl Element for Choice {
+	fn key(&self) -> String {
+		format!("choice[name={}]", self.name)
+	}
+}
+
+impl<'a, T: Element> Element for &'a T {
+	fn key(&self) -> String {
+		(*self).key()
+	}
+
+	fn segment(&self) -> String {
+		(*self).segment()
+	}
+}
@@ -3,3 +3,31 @@
 //! Allowed:
 //! - Add fields to messages
 //! - Delete optional fields
+
+use crate::{CompatibilityLevel, CompatibilityReport, EvolutionStrategy};
+
+use super::{EvolutionError, SchemaValidator};
+
+/// Schema evolution strategy that accept adding fields and deleting optional fields.
+pub struct ForwardCompatibility<V: SchemaValidator> {
+	validator: V,
+}
+
+impl<V: SchemaValidator> ForwardCompatibility<V> {
+	/// Create a new `ForwardCompatibility` strategy with the given validator.
+	pub fn new(validator: V) -> Self {
+		Self { validator }
+	}
+}
+
+impl<V: SchemaValidator> EvolutionStrategy for ForwardCompatibility<V> {
+	type SchemaType = V::SchemaType;
+
+	fn compatibility_level(&self) -> CompatibilityLevel {
+		CompatibilityLevel::Forward
+	}
+
+	fn report(&self) -> Result<CompatibilityReport, EvolutionError> {
+		self.validator.compare_all()
+	}
+}
@@ -4,7 +4,7 @@
 //! - Add optional fields to messages
 //! - Delete optional fields
 
-use crate::{CompatibilityLevel, EvolutionStrategy};
+use crate::{CompatibilityLevel, CompatibilityReport, EvolutionStrategy};
 
 use super::{EvolutionError, SchemaValidator};
 
@@ -27,12 +27,7 @@ impl<V: SchemaValidator> EvolutionStrategy for FullCompatibility<V> {
 		CompatibilityLevel::Full
 	}
 
-	fn check(&self) -> Result<CompatibilityLevel, EvolutionError> {
-		let r = self.validator.compare_version()?;
-		if r == self.compatibility_level() {
-			Ok(r)
-		} else {
-			Err(EvolutionError::SchemaNotCompatible(self.compatibility_level()))
-		}
+	fn report(&self) -> Result<CompatibilityReport, EvolutionError> {
+		self.validator.compare_all()
 	}
 }
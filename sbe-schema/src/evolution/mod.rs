@@ -1,21 +1,30 @@
 //! This module contains the implementation of the schema evolution strategy.
 
 mod backward;
+mod element_impl_for_types;
 mod forward;
 mod full;
 mod optional_impl_for_types;
 mod partial_compatibility_for_types;
+mod report;
+mod transitive;
 mod validator;
 
+use serde::Serialize;
 use thiserror::Error;
 
+pub use backward::BackwardCompatibility;
+pub use forward::ForwardCompatibility;
 pub use full::FullCompatibility;
+pub(crate) use report::join;
+pub use report::{ChangeKind, CompatibilityReport, Finding};
+pub use transitive::{TransitiveCompatibility, TransitiveReport, VersionReport};
 pub use validator::SbeSchemaValidator;
 
-pub use partial_compatibility_for_types::check_vec;
+pub use partial_compatibility_for_types::compare_vec;
 
 /// The compatibility level of a schema evolution strategy.
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
 pub enum CompatibilityLevel {
 	/// Delete fields and Add optional fields
 	Backward,
@@ -29,6 +38,47 @@ pub enum CompatibilityLevel {
 	NoChange,
 }
 
+impl CompatibilityLevel {
+	/// Combine two compatibility levels into the level that both of them satisfy, e.g. a backward
+	/// and a forward compatible change together are neither backward nor forward compatible.
+	pub fn combine(self, other: Self) -> Self {
+		match (self, other) {
+			(CompatibilityLevel::None, _) | (_, CompatibilityLevel::None) =>
+				CompatibilityLevel::None,
+			(CompatibilityLevel::NoChange, l) | (l, CompatibilityLevel::NoChange) => l,
+			(CompatibilityLevel::Full, l) | (l, CompatibilityLevel::Full) => l,
+			(CompatibilityLevel::Backward, CompatibilityLevel::Backward) =>
+				CompatibilityLevel::Backward,
+			(CompatibilityLevel::Forward, CompatibilityLevel::Forward) =>
+				CompatibilityLevel::Forward,
+			(CompatibilityLevel::Backward, CompatibilityLevel::Forward) |
+			(CompatibilityLevel::Forward, CompatibilityLevel::Backward) => CompatibilityLevel::None,
+		}
+	}
+
+	/// Check if this level satisfies the required compatibility level of a strategy.
+	pub fn satisfies(self, required: Self) -> bool {
+		match required {
+			CompatibilityLevel::None => true,
+			CompatibilityLevel::NoChange => self == CompatibilityLevel::NoChange,
+			CompatibilityLevel::Full =>
+				matches!(self, CompatibilityLevel::Full | CompatibilityLevel::NoChange),
+			CompatibilityLevel::Backward => matches!(
+				self,
+				CompatibilityLevel::Backward |
+					CompatibilityLevel::Full |
+					CompatibilityLevel::NoChange
+			),
+			CompatibilityLevel::Forward => matches!(
+				self,
+				CompatibilityLevel::Forward |
+					CompatibilityLevel::Full |
+					CompatibilityLevel::NoChange
+			),
+		}
+	}
+}
+
 /// A trait for checking partial compatibility.
 pub trait PartialCompatibility<Rhs = Self>
 where
@@ -36,7 +86,23 @@ where
 {
 	/// Check the partial compatibility. First argument beeing current version and second argument
 	/// beeing the latest version.
-	fn partial_compatibility(&self, latest: &Rhs) -> CompatibilityLevel;
+	fn partial_compatibility(&self, latest: &Rhs) -> CompatibilityLevel {
+		let mut report = CompatibilityReport::new();
+		self.compare(latest, "", &mut report);
+		report.level()
+	}
+	/// Compare with the latest version and record every change found under the given path.
+	fn compare(&self, latest: &Rhs, path: &str, report: &mut CompatibilityReport);
+}
+
+/// A trait for matching schema elements between versions and naming them in the report.
+pub trait Element {
+	/// The key used to match the element between the latest and the current schema.
+	fn key(&self) -> String;
+	/// The path segment of the element used in the report, defaults to the key.
+	fn segment(&self) -> String {
+		self.key()
+	}
 }
 
 /// A trait for checking if type is optional
@@ -49,6 +115,8 @@ pub trait Optional {
 pub enum EvolutionError {
 	#[error("Schema is not compatible with the latest schema! Compatibility level: {0:?}")]
 	SchemaNotCompatible(CompatibilityLevel),
+	#[error("Schema is not {level:?} compatible with {label} (version {version})")]
+	NotCompatibleWith { label: String, version: u32, level: CompatibilityLevel },
 	#[error("Missing schema version")]
 	MissingVersion,
 	#[error("Missing message header")]
@@ -61,8 +129,21 @@ pub trait EvolutionStrategy {
 	type SchemaType;
 	/// The compatibility level of the strategy.
 	fn compatibility_level(&self) -> CompatibilityLevel;
+	/// Compare the current schema with the latest schema and report all changes found.
+	fn report(&self) -> Result<CompatibilityReport, EvolutionError>;
+	/// Verify that the changes found in the report are accepted by the strategy.
+	fn verify(&self, report: &CompatibilityReport) -> Result<CompatibilityLevel, EvolutionError> {
+		let level = report.level();
+		if level.satisfies(self.compatibility_level()) {
+			Ok(level)
+		} else {
+			Err(EvolutionError::SchemaNotCompatible(self.compatibility_level()))
+		}
+	}
 	/// Check if the current schema is compatible with the latest schema.
-	fn check(&self) -> Result<CompatibilityLevel, EvolutionError>;
+	fn check(&self) -> Result<CompatibilityLevel, EvolutionError> {
+		self.verify(&self.report()?)
+	}
 }
 
 /// A trait for validating schema versions.
@@ -74,13 +155,33 @@ pub trait SchemaValidator {
 	/// Get the current schema.
 	fn current(&self) -> &Self::SchemaType;
 	/// Compare the version of the current schema with the latest schema.
-	fn compare_version(&self) -> Result<CompatibilityLevel, EvolutionError>;
+	fn compare_version(&self) -> Result<CompatibilityReport, EvolutionError>;
 	/// Compare the message header of the current schema with the latest schema.
-	fn compare_message_header(&self) -> Result<CompatibilityLevel, EvolutionError>;
+	fn compare_message_header(&self) -> Result<CompatibilityReport, EvolutionError>;
 	/// Compare the types of the current schema with the latest schema.
-	fn compare_types(&self) -> Result<CompatibilityLevel, EvolutionError>;
+	fn compare_types(&self) -> Result<CompatibilityReport, EvolutionError>;
 	/// Compare the messages of the current schema with the latest schema.
-	fn compare_messages(&self) -> Result<CompatibilityLevel, EvolutionError>;
+	fn compare_messages(&self) -> Result<CompatibilityReport, EvolutionError>;
+	/// Compare version, message header, types and messages and combine the findings into a single
+	/// report. Any change requires the version to be bumped.
+	fn compare_all(&self) -> Result<CompatibilityReport, EvolutionError> {
+		let mut report = self.compare_version()?;
+		let mut changes = self.compare_message_header()?;
+		changes.extend(self.compare_types()?);
+		changes.extend(self.compare_messages()?);
+
+		if report.is_empty() && !changes.is_empty() {
+			report.push(
+				"version",
+				ChangeKind::Version,
+				CompatibilityLevel::None,
+				"schema changed without bumping the version",
+			);
+		}
+		report.extend(changes);
+
+		Ok(report)
+	}
 }
 
 /// A validator for schema evolution.
@@ -95,6 +196,24 @@ impl<E: EvolutionStrategy> Validator<E> {
 		Self { strategy }
 	}
 
+	/// The compatibility level required by the strategy.
+	pub fn compatibility_level(&self) -> CompatibilityLevel {
+		self.strategy.compatibility_level()
+	}
+
+	/// Compare the current schema with the latest schema and report all changes found.
+	pub fn report(&self) -> Result<CompatibilityReport, EvolutionError> {
+		self.strategy.report()
+	}
+
+	/// Verify that the changes found in the report are accepted by the strategy.
+	pub fn verify(
+		&self,
+		report: &CompatibilityReport,
+	) -> Result<CompatibilityLevel, EvolutionError> {
+		self.strategy.verify(report)
+	}
+
 	/// Check if the current schema is compatible with the latest schema.
 	pub fn check(&self) -> Result<CompatibilityLevel, EvolutionError> {
 		self.strategy.check()
@@ -103,13 +222,13 @@ impl<E: EvolutionStrategy> Validator<E> {
 
 /// A strategy that accepts all changes.
 pub struct NoneCompatibility<V: SchemaValidator> {
-	_validator: V,
+	validator: V,
 }
 
 impl<V: SchemaValidator> NoneCompatibility<V> {
 	/// Create a new `NoneCompatibility` strategy with the given validator.
 	pub fn new(validator: V) -> Self {
-		Self { _validator: validator }
+		Self { validator }
 	}
 }
 
@@ -120,6 +239,14 @@ impl<V: SchemaValidator> EvolutionStrategy for NoneCompatibility<V> {
 		CompatibilityLevel::None
 	}
 
+	fn report(&self) -> Result<CompatibilityReport, EvolutionError> {
+		self.validator.compare_all()
+	}
+
+	fn verify(&self, _report: &CompatibilityReport) -> Result<CompatibilityLevel, EvolutionError> {
+		self.check()
+	}
+
 	fn check(&self) -> Result<CompatibilityLevel, EvolutionError> {
 		match self.compatibility_level() {
 			CompatibilityLevel::None => Ok(CompatibilityLevel::None),
@@ -132,7 +259,7 @@ impl<V: SchemaValidator> EvolutionStrategy for NoneCompatibility<V> {
 mod tests {
 	use tests::validator::SbeSchemaValidator;
 
-	use crate::Schema;
+	use crate::{fixtures, Schema};
 
 	use super::*;
 
@@ -140,9 +267,8 @@ mod tests {
 	fn test_validator_none() {
 		let latest_schema = Schema::default();
 		let current_schema = Schema::default();
-		let strategy = NoneCompatibility {
-			_validator: SbeSchemaValidator::new(&latest_schema, &current_schema),
-		};
+		let strategy =
+			NoneCompatibility::new(SbeSchemaValidator::new(&latest_schema, &current_schema));
 
 		let validator = Validator::new(strategy);
 		let result = validator.check();
@@ -151,4 +277,81 @@ mod tests {
 		let returnd_result = result.unwrap();
 		assert_eq!(returnd_result, expected);
 	}
+
+	fn schema(version: u32, decimal_members: &str) -> Schema {
+		fixtures::schema(
+			version,
+			&format!(r#"<composite name="Decimal">{decimal_members}</composite>"#),
+			"",
+		)
+	}
+
+	const DECIMAL: &str = r#"
+		<type name="mantissa" primitiveType="int64"/>
+		<type name="exponent" primitiveType="int8"/>"#;
+
+	#[test]
+	fn test_compatibility_level_combine() {
+		use CompatibilityLevel as L;
+		assert_eq!(L::NoChange.combine(L::Full), L::Full);
+		assert_eq!(L::Full.combine(L::Backward), L::Backward);
+		assert_eq!(L::Forward.combine(L::Full), L::Forward);
+		assert_eq!(L::Backward.combine(L::Forward), L::None);
+		assert_eq!(L::None.combine(L::NoChange), L::None);
+	}
+
+	#[test]
+	fn test_validator_backward() {
+		let latest_schema = schema(1, DECIMAL);
+		let current_schema = schema(2, r#"<type name="mantissa" primitiveType="int64"/>"#);
+		let strategy =
+			BackwardCompatibility::new(SbeSchemaValidator::new(&latest_schema, &current_schema));
+
+		let validator = Validator::new(strategy);
+		let result = validator.check();
+		assert!(result.is_ok());
+		assert_eq!(result.unwrap(), CompatibilityLevel::Backward);
+	}
+
+	#[test]
+	fn test_validator_forward() {
+		let latest_schema = schema(1, DECIMAL);
+		let current_schema = schema(2, r#"<type name="mantissa" primitiveType="int64"/>"#);
+		let strategy =
+			ForwardCompatibility::new(SbeSchemaValidator::new(&latest_schema, &current_schema));
+
+		let validator = Validator::new(strategy);
+		let result = validator.check();
+		assert!(matches!(
+			result,
+			Err(EvolutionError::SchemaNotCompatible(CompatibilityLevel::Forward))
+		));
+	}
+
+	#[test]
+	fn test_validator_full() {
+		let latest_schema = schema(1, DECIMAL);
+		let bumped = schema(2, DECIMAL);
+		let validator = Validator::new(FullCompatibility::new(SbeSchemaValidator::new(
+			&latest_schema,
+			&bumped,
+		)));
+		assert_eq!(validator.check().unwrap(), CompatibilityLevel::Full);
+
+		// deleted required composite member is not fully compatible even with bumped version
+		let deleted = schema(2, r#"<type name="mantissa" primitiveType="int64"/>"#);
+		let validator = Validator::new(FullCompatibility::new(SbeSchemaValidator::new(
+			&latest_schema,
+			&deleted,
+		)));
+		assert!(validator.check().is_err());
+
+		// any change requires version to be bumped
+		let not_bumped = schema(1, r#"<type name="mantissa" primitiveType="int64"/>"#);
+		let validator = Validator::new(BackwardCompatibility::new(SbeSchemaValidator::new(
+			&latest_schema,
+			&not_bumped,
+		)));
+		assert!(validator.check().is_err());
+	}
 }
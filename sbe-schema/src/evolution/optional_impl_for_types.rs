@@ -1,6 +1,9 @@
 use crate::{
 	evolution::Optional,
-	types::{Composite, EnumType, Presence, Ref, SetType, Type},
+	types::{
+		Choice, Composite, Data, EnumType, Field, Group, Message, Presence, Ref, SetType, Type,
+		ValidValue,
+	},
 };
 
 impl Optional for Type {
@@ -21,19 +24,61 @@ impl Optional for Ref {
 	}
 }
 
-impl<'a> Optional for &'a Composite {
+impl<'a, T: Optional> Optional for &'a T {
+	fn is_optional(&self) -> bool {
+		(*self).is_optional()
+	}
+}
+
+impl Optional for Composite {
+	fn is_optional(&self) -> bool {
+		false
+	}
+}
+
+impl Optional for EnumType {
+	fn is_optional(&self) -> bool {
+		false
+	}
+}
+
+impl Optional for SetType {
+	fn is_optional(&self) -> bool {
+		false
+	}
+}
+
+impl Optional for Message {
+	fn is_optional(&self) -> bool {
+		false
+	}
+}
+
+impl Optional for Field {
+	fn is_optional(&self) -> bool {
+		self.presence == Some(Presence::Optional)
+	}
+}
+
+impl Optional for Group {
+	fn is_optional(&self) -> bool {
+		false
+	}
+}
+
+impl Optional for Data {
 	fn is_optional(&self) -> bool {
 		false
 	}
 }
 
-impl<'a> Optional for &'a EnumType {
+impl Optional for ValidValue {
 	fn is_optional(&self) -> bool {
 		false
 	}
 }
 
-impl<'a> Optional for &'a SetType {
+impl Optional for Choice {
 	fn is_optional(&self) -> bool {
 		false
 	}
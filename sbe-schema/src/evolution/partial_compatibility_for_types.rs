@@ -1,171 +1,513 @@
+use std::fmt::Debug;
+
 use crate::{
-	types::{Composite, Ref, Type},
+	evolution::{
+		report::{join, ChangeKind},
+		CompatibilityReport, Element,
+	},
+	types::{
+		Choice, Composite, Data, EnumType, Field, Group, Message, Presence, Ref, SetType, Type,
+		ValidValue,
+	},
 	CompatibilityLevel, Optional, PartialCompatibility,
 };
 
 impl PartialCompatibility for Composite {
-	fn partial_compatibility(&self, latest: &Self) -> CompatibilityLevel {
+	fn compare(&self, latest: &Self, path: &str, report: &mut CompatibilityReport) {
+		if self == latest {
+			return;
+		}
+
 		if self.name != latest.name {
-			// early return if the name is different
-			return CompatibilityLevel::None;
+			report.push(
+				path,
+				ChangeKind::Renamed,
+				CompatibilityLevel::None,
+				format!("composite renamed from {} to {}", latest.name, self.name),
+			);
 		}
 
-		let mut level = CompatibilityLevel::None;
+		if self.offset != latest.offset {
+			report.push(
+				path,
+				ChangeKind::Modified,
+				CompatibilityLevel::None,
+				format!(
+					"offset changed from {} to {}",
+					describe(latest.offset.as_ref()),
+					describe(self.offset.as_ref())
+				),
+			);
+		}
+
+		compare_members(self.types.as_ref(), latest.types.as_ref(), path, report);
+		compare_members(self.refs.as_ref(), latest.refs.as_ref(), path, report);
+		compare_members(self.enums.as_ref(), latest.enums.as_ref(), path, report);
+		compare_members(self.sets.as_ref(), latest.sets.as_ref(), path, report);
+		compare_members(self.composites.as_ref(), latest.composites.as_ref(), path, report);
+	}
+}
 
-		if self.types == latest.types {
-			level = CompatibilityLevel::NoChange;
+impl PartialCompatibility for Type {
+	fn compare(&self, latest: &Self, path: &str, report: &mut CompatibilityReport) {
+		if self.primitive_type != latest.primitive_type || self.length != latest.length {
+			report.push(
+				path,
+				ChangeKind::TypeChanged,
+				CompatibilityLevel::None,
+				format!(
+					"encoding changed from {}[{}] to {}[{}]",
+					describe(latest.primitive_type.as_ref()),
+					describe(latest.length.as_ref()),
+					describe(self.primitive_type.as_ref()),
+					describe(self.length.as_ref()),
+				),
+			);
 		}
 
-		if level != CompatibilityLevel::NoChange {
-			return level;
+		compare_presence(self.presence.as_ref(), latest.presence.as_ref(), path, report);
+		compare_since_version(self.since_version, latest.since_version, path, report);
+
+		if self.null_value != latest.null_value ||
+			self.value != latest.value ||
+			self.character_encoding != latest.character_encoding
+		{
+			report.push(
+				path,
+				ChangeKind::Modified,
+				CompatibilityLevel::None,
+				"null value, constant value or character encoding changed",
+			);
 		}
 
-		level = check_vec::<Type>(self.types.as_ref(), latest.types.as_ref());
-		let ref_level = match level {
-			CompatibilityLevel::None => return level,
-			_ =>
-				if self.refs == latest.refs {
-					CompatibilityLevel::NoChange
-				} else {
-					check_vec::<Ref>(self.refs.as_ref(), latest.refs.as_ref())
-				},
-		};
+		if self.min_value != latest.min_value || self.max_value != latest.max_value {
+			// valid range is not part of the wire format
+			report.push(
+				path,
+				ChangeKind::Modified,
+				CompatibilityLevel::Full,
+				"min or max value changed",
+			);
+		}
+	}
+}
+
+impl PartialCompatibility for Ref {
+	fn compare(&self, latest: &Self, path: &str, report: &mut CompatibilityReport) {
+		if self.ref_type != latest.ref_type {
+			report.push(
+				path,
+				ChangeKind::TypeChanged,
+				CompatibilityLevel::None,
+				format!("type changed from {} to {}", latest.ref_type, self.ref_type),
+			);
+		}
+
+		compare_presence(self.presence.as_ref(), latest.presence.as_ref(), path, report);
+
+		if self.value_ref != latest.value_ref {
+			report.push(
+				path,
+				ChangeKind::Modified,
+				CompatibilityLevel::None,
+				format!(
+					"valueRef changed from {} to {}",
+					describe(latest.value_ref.as_ref()),
+					describe(self.value_ref.as_ref())
+				),
+			);
+		}
+	}
+}
+
+impl PartialCompatibility for EnumType {
+	fn compare(&self, latest: &Self, path: &str, report: &mut CompatibilityReport) {
+		compare_encoding_type(
+			self.encoding_type.as_ref(),
+			latest.encoding_type.as_ref(),
+			path,
+			report,
+		);
+		compare_vec(self.valid_values.as_ref(), latest.valid_values.as_ref(), path, report);
+	}
+}
+
+impl PartialCompatibility for ValidValue {
+	fn compare(&self, latest: &Self, path: &str, report: &mut CompatibilityReport) {
+		if self.value != latest.value {
+			report.push(
+				path,
+				ChangeKind::Modified,
+				CompatibilityLevel::None,
+				format!("value changed from {} to {}", latest.value, self.value),
+			);
+		}
+	}
+}
+
+impl PartialCompatibility for SetType {
+	fn compare(&self, latest: &Self, path: &str, report: &mut CompatibilityReport) {
+		compare_encoding_type(
+			self.encoding_type.as_ref(),
+			latest.encoding_type.as_ref(),
+			path,
+			report,
+		);
+		compare_vec(self.choices.as_ref(), latest.choices.as_ref(), path, report);
+	}
+}
+
+impl PartialCompatibility for Choice {
+	fn compare(&self, latest: &Self, path: &str, report: &mut CompatibilityReport) {
+		if self.value != latest.value {
+			report.push(
+				path,
+				ChangeKind::Modified,
+				CompatibilityLevel::None,
+				format!("bit position changed from {} to {}", latest.value, self.value),
+			);
+		}
+	}
+}
+
+impl PartialCompatibility for Message {
+	fn compare(&self, latest: &Self, path: &str, report: &mut CompatibilityReport) {
+		if self == latest {
+			return;
+		}
+
+		compare_name(&self.name, &latest.name, path, report);
+
+		compare_since_version(self.since_version, latest.since_version, path, report);
+		compare_block_length(self.block_length, latest.block_length, path, report);
+
+		compare_members(self.fields.as_ref(), latest.fields.as_ref(), path, report);
+		compare_members(self.groups.as_ref(), latest.groups.as_ref(), path, report);
+		compare_members(self.data.as_ref(), latest.data.as_ref(), path, report);
+	}
+}
+
+impl PartialCompatibility for Group {
+	fn compare(&self, latest: &Self, path: &str, report: &mut CompatibilityReport) {
+		if self == latest {
+			return;
+		}
+
+		if self.dimension_type != latest.dimension_type {
+			report.push(
+				path,
+				ChangeKind::TypeChanged,
+				CompatibilityLevel::None,
+				format!(
+					"dimension type changed from {} to {}",
+					latest.dimension_type, self.dimension_type
+				),
+			);
+		}
+
+		compare_since_version(self.since_version, latest.since_version, path, report);
+		compare_block_length(self.block_length, latest.block_length, path, report);
+		compare_name(&self.name, &latest.name, path, report);
+
+		compare_members(self.fields.as_ref(), latest.fields.as_ref(), path, report);
+		compare_members(self.groups.as_ref(), latest.groups.as_ref(), path, report);
+		compare_members(self.data.as_ref(), latest.data.as_ref(), path, report);
+	}
+}
+
+impl PartialCompatibility for Field {
+	fn compare(&self, latest: &Self, path: &str, report: &mut CompatibilityReport) {
+		if self.r#type != latest.r#type {
+			report.push(
+				path,
+				ChangeKind::TypeChanged,
+				CompatibilityLevel::None,
+				format!("type changed from {} to {}", latest.r#type, self.r#type),
+			);
+		}
+
+		if self.offset != latest.offset {
+			report.push(
+				path,
+				ChangeKind::Modified,
+				CompatibilityLevel::None,
+				format!(
+					"offset changed from {} to {}",
+					describe(latest.offset.as_ref()),
+					describe(self.offset.as_ref())
+				),
+			);
+		}
+
+		compare_presence(self.presence.as_ref(), latest.presence.as_ref(), path, report);
+		compare_since_version(self.since_version, latest.since_version, path, report);
+		compare_name(&self.name, &latest.name, path, report);
+	}
+}
 
-		match (level, ref_level) {
-			(CompatibilityLevel::None, _) => CompatibilityLevel::None,
-			(_, CompatibilityLevel::None) => CompatibilityLevel::None,
-			(CompatibilityLevel::NoChange, l) => l,
-			(l, CompatibilityLevel::NoChange) => l,
-			(CompatibilityLevel::Full, _) => ref_level,
-			(_, CompatibilityLevel::Full) => level,
-			(_, CompatibilityLevel::Backward) => CompatibilityLevel::Backward,
-			(CompatibilityLevel::Backward, _) => CompatibilityLevel::Backward,
-			(CompatibilityLevel::Forward, CompatibilityLevel::Forward) =>
-				CompatibilityLevel::Forward,
+impl PartialCompatibility for Data {
+	fn compare(&self, latest: &Self, path: &str, report: &mut CompatibilityReport) {
+		if self.r#type != latest.r#type {
+			report.push(
+				path,
+				ChangeKind::TypeChanged,
+				CompatibilityLevel::None,
+				format!("type changed from {} to {}", latest.r#type, self.r#type),
+			);
 		}
+
+		compare_since_version(self.since_version, latest.since_version, path, report);
+		compare_name(&self.name, &latest.name, path, report);
+	}
+}
+
+impl<T: PartialCompatibility> PartialCompatibility for &T {
+	fn compare(&self, latest: &Self, path: &str, report: &mut CompatibilityReport) {
+		(*self).compare(*latest, path, report)
+	}
+}
+
+fn compare_name(current: &str, latest: &str, path: &str, report: &mut CompatibilityReport) {
+	if current != latest {
+		// renaming does not change the encoding, since the id remains the same
+		report.push(
+			path,
+			ChangeKind::Renamed,
+			CompatibilityLevel::Full,
+			format!("renamed from {latest} to {current}"),
+		);
+	}
+}
+
+fn compare_since_version(
+	current: Option<u32>,
+	latest: Option<u32>,
+	path: &str,
+	report: &mut CompatibilityReport,
+) {
+	if current != latest {
+		report.push(
+			path,
+			ChangeKind::Modified,
+			CompatibilityLevel::None,
+			format!(
+				"sinceVersion changed from {} to {}",
+				describe(latest.as_ref()),
+				describe(current.as_ref())
+			),
+		);
+	}
+}
+
+/// Decoders skip the block by the block length sent in the header, padding the block therefore
+/// does not break the wire format as long as the fields keep their offsets.
+fn compare_block_length(
+	current: Option<u32>,
+	latest: Option<u32>,
+	path: &str,
+	report: &mut CompatibilityReport,
+) {
+	if current != latest {
+		report.push(
+			path,
+			ChangeKind::Modified,
+			CompatibilityLevel::Full,
+			format!(
+				"blockLength changed from {} to {}",
+				describe(latest.as_ref()),
+				describe(current.as_ref())
+			),
+		);
+	}
+}
+
+fn compare_encoding_type<T: PartialEq + Debug>(
+	current: Option<&T>,
+	latest: Option<&T>,
+	path: &str,
+	report: &mut CompatibilityReport,
+) {
+	if current != latest {
+		report.push(
+			path,
+			ChangeKind::TypeChanged,
+			CompatibilityLevel::None,
+			format!("encoding type changed from {} to {}", describe(latest), describe(current)),
+		);
 	}
 }
 
-/// Check the compatibility of two vectors of types.
-pub fn check_vec<T>(current: Option<&Vec<T>>, latest: Option<&Vec<T>>) -> CompatibilityLevel
-where
-	T: PartialEq + Optional,
+/// Making a required element optional is like deleting it and making an optional element
+/// required is like adding it. Constant elements are not encoded at all, therefore any change
+/// from or to constant breaks the wire format.
+fn compare_presence(
+	current: Option<&Presence>,
+	latest: Option<&Presence>,
+	path: &str,
+	report: &mut CompatibilityReport,
+) {
+	let (current, latest) =
+		(current.unwrap_or(&Presence::Required), latest.unwrap_or(&Presence::Required));
+	let level = match (current, latest) {
+		_ if current == latest => return,
+		(Presence::Constant, _) | (_, Presence::Constant) => CompatibilityLevel::None,
+		(Presence::Optional, _) => CompatibilityLevel::Backward,
+		(Presence::Required, _) => CompatibilityLevel::Forward,
+	};
+	report.push(
+		path,
+		ChangeKind::PresenceChanged,
+		level,
+		format!("presence changed from {latest:?} to {current:?}"),
+	);
+}
+
+/// Compare the members of a composite, message or group: types, fields, groups or var data. SBE
+/// encodes them positionally, therefore besides the compatibility of the matched members their
+/// order is checked as well.
+fn compare_members<T>(
+	current: Option<&Vec<T>>,
+	latest: Option<&Vec<T>>,
+	path: &str,
+	report: &mut CompatibilityReport,
+) where
+	T: Optional + PartialCompatibility + Element,
 {
-	match (current, latest) {
-		(Some(current), Some(latest)) => {
-			// if vecs are the same, we are good for no change
-			if current == latest {
-				return CompatibilityLevel::NoChange;
-			}
-			// if current has more types than latest, but all latest are present in current
-			// then we are good for full compatibility
-			// 1. let check if all latest types are present in current
-			let mut level = CompatibilityLevel::NoChange;
-			let mut found = 0;
-			for latest_item in latest {
-				if current.contains(latest_item) {
-					found += 1;
-					// we found the type, now we just need to update the compatibility level
-					// against the current level so we return lowest compatibility level
-					level = get_compatibility_from_current_and_new_level(
-						level,
-						CompatibilityLevel::NoChange,
-					);
-				} else {
-					// not found, we should check if the field was optional
-					// if it was optional, then Forward compatibility is allowed
-					// if it was not optional, then we are backward compatible
-					level = get_compatibility_from_current_level_and_deleted_field_optionality(
-						level,
-						latest_item.is_optional(),
-					);
-				}
-			}
-
-			// 2. if we have the same number of types, we return NoChange
-			if found == current.len() {
-				// no change
-				return CompatibilityLevel::NoChange;
-			}
-
-			// 3. if we found less types, so some types were removed
-			// and we already checked if the types were optional
-			// therefore level is properly set, so we return level
-			if found < latest.len() {
-				return level;
-			}
-
-			// 3. we have more types then last time
-			// all latest types are present in current with no changes, therefore we just need to
-			// assert that all new types are optional to have full compatibility otherwise we are
-			// forward compatible
-			for current_item in current {
-				if latest.contains(current_item) {
-					continue;
-				}
-				level = get_compatibility_from_current_level_and_added_field_optionality(
-					level,
-					current_item.is_optional(),
-				);
-			}
+	compare_order(current, latest, path, report);
+	compare_vec(current, latest, path, report);
+}
 
-			level
-		},
-		// check if the added types are optional. If they are optional then we are backward
-		// compatible otherwise we are forward compatible
-		(Some(current_items), None) =>
-			if current_items.iter().all(|t| t.is_optional()) {
+/// Compare two vectors of elements matched by [`Element::key`], e.g. message id. Elements present
+/// in both vectors are compared using [`PartialCompatibility`], the remaining ones are reported
+/// as added or removed.
+pub fn compare_vec<T>(
+	current: Option<&Vec<T>>,
+	latest: Option<&Vec<T>>,
+	path: &str,
+	report: &mut CompatibilityReport,
+) where
+	T: Optional + PartialCompatibility + Element,
+{
+	// a vector introduced or dropped as a whole is rated as a whole: introducing only optional
+	// elements is backward compatible and dropping only optional elements forward compatible
+	let (added_level, deleted_level) = match (current, latest) {
+		(Some(items), None) => {
+			let level = if items.iter().all(Optional::is_optional) {
 				CompatibilityLevel::Backward
 			} else {
 				CompatibilityLevel::Forward
-			},
-		// check if the removed types were optional. If they were optional then we are Forward
-		// compatible, otherwise Backward
-		(None, Some(latest_items)) =>
-			if latest_items.iter().all(|t| t.is_optional()) {
+			};
+			(Some(level), None)
+		},
+		(None, Some(items)) => {
+			let level = if items.iter().all(Optional::is_optional) {
 				CompatibilityLevel::Forward
 			} else {
 				CompatibilityLevel::Backward
-			},
-		(None, None) => CompatibilityLevel::NoChange,
+			};
+			(None, Some(level))
+		},
+		_ => (None, None),
+	};
+	let empty = Vec::new();
+	let (current, latest) = (current.unwrap_or(&empty), latest.unwrap_or(&empty));
+
+	for latest_item in latest {
+		let key = latest_item.key();
+		match current.iter().find(|c| c.key() == key) {
+			Some(current_item) =>
+				current_item.compare(latest_item, &join(path, &current_item.segment()), report),
+			None => report.push(
+				join(path, &latest_item.segment()),
+				ChangeKind::Removed,
+				deleted_level.unwrap_or_else(|| deleted_item_level(latest_item.is_optional())),
+				if latest_item.is_optional() {
+					"optional element removed"
+				} else {
+					"required element removed"
+				},
+			),
+		}
+	}
+
+	for current_item in current {
+		let key = current_item.key();
+		if !latest.iter().any(|l| l.key() == key) {
+			report.push(
+				join(path, &current_item.segment()),
+				ChangeKind::Added,
+				added_level.unwrap_or_else(|| added_item_level(current_item.is_optional())),
+				if current_item.is_optional() {
+					"optional element added"
+				} else {
+					"required element added"
+				},
+			);
+		}
 	}
 }
 
-fn get_compatibility_from_current_and_new_level(
-	level: CompatibilityLevel,
-	new_level: CompatibilityLevel,
-) -> CompatibilityLevel {
-	match (level, new_level) {
-		(CompatibilityLevel::None, _) => CompatibilityLevel::None,
-		(_, CompatibilityLevel::None) => CompatibilityLevel::None,
-		(CompatibilityLevel::NoChange, _) => new_level,
-		(_, CompatibilityLevel::NoChange) => level,
-		(CompatibilityLevel::Full, _) => new_level,
-		(_, CompatibilityLevel::Full) => level,
-		(CompatibilityLevel::Backward, _) => CompatibilityLevel::Backward,
-		(_, CompatibilityLevel::Backward) => CompatibilityLevel::Backward,
-		(CompatibilityLevel::Forward, CompatibilityLevel::Forward) => CompatibilityLevel::Forward,
+/// Check that the elements present in both vectors keep their relative order and that new
+/// elements are only appended after them.
+fn compare_order<T: Element>(
+	current: Option<&Vec<T>>,
+	latest: Option<&Vec<T>>,
+	path: &str,
+	report: &mut CompatibilityReport,
+) {
+	let (Some(current), Some(latest)) = (current, latest) else {
+		return;
+	};
+
+	let mut last_position = None;
+	let mut added: Vec<String> = Vec::new();
+	for current_item in current {
+		let key = current_item.key();
+		match latest.iter().position(|l| l.key() == key) {
+			Some(position) => {
+				for added_item in added.drain(..) {
+					report.push(
+						join(path, &added_item),
+						ChangeKind::Reordered,
+						CompatibilityLevel::None,
+						"added before existing elements, new elements must be appended",
+					);
+				}
+				if last_position.is_some_and(|last| position < last) {
+					report.push(
+						join(path, &current_item.segment()),
+						ChangeKind::Reordered,
+						CompatibilityLevel::None,
+						"moved before an element preceding it in the latest schema",
+					);
+				}
+				last_position = Some(position);
+			},
+			None => added.push(current_item.segment()),
+		}
 	}
 }
 
-fn get_compatibility_from_current_level_and_deleted_field_optionality(
-	level: CompatibilityLevel,
-	is_optional: bool,
-) -> CompatibilityLevel {
-	match (level, is_optional) {
-		(CompatibilityLevel::None, _) => CompatibilityLevel::None,
-		(_, false) => CompatibilityLevel::Backward,
-		(_, true) => CompatibilityLevel::Forward,
+/// Deleting an optional item is forward compatible, old consumers will treat it as not set.
+/// Deleting a required item is only backward compatible.
+fn deleted_item_level(is_optional: bool) -> CompatibilityLevel {
+	if is_optional {
+		CompatibilityLevel::Forward
+	} else {
+		CompatibilityLevel::Backward
 	}
 }
 
-fn get_compatibility_from_current_level_and_added_field_optionality(
-	level: CompatibilityLevel,
-	is_optional: bool,
-) -> CompatibilityLevel {
-	match (level, is_optional) {
-		(CompatibilityLevel::None, _) => CompatibilityLevel::None,
-		(_, false) => CompatibilityLevel::Forward,
-		(_, true) => CompatibilityLevel::Full,
+/// Adding an optional item is fully compatible, adding a required item is only forward
+/// compatible.
+fn added_item_level(is_optional: bool) -> CompatibilityLevel {
+	if is_optional {
+		CompatibilityLevel::Full
+	} else {
+		CompatibilityLevel::Forward
 	}
 }
+
+fn describe<T: Debug>(value: Option<&T>) -> String {
+	value.map_or_else(|| "unset".to_string(), |v| format!("{v:?}"))
+}
@@ -0,0 +1,121 @@
+//! Compatibility report listing every change found between two schemas.
+
+use serde::{ser::SerializeStruct, Serialize, Serializer};
+
+use crate::CompatibilityLevel;
+
+/// The kind of change found between the latest and the current schema.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+	/// Element was added in the current schema.
+	Added,
+	/// Element was removed from the current schema.
+	Removed,
+	/// Element was renamed, the id remains the same.
+	Renamed,
+	/// Type or encoding of the element was changed.
+	TypeChanged,
+	/// Presence of the element was changed.
+	PresenceChanged,
+	/// Element was moved to a different position.
+	Reordered,
+	/// Other attribute of the element was changed.
+	Modified,
+	/// Schema version was changed or not bumped.
+	Version,
+}
+
+impl std::fmt::Display for ChangeKind {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let kind = match self {
+			ChangeKind::Added => "added",
+			ChangeKind::Removed => "removed",
+			ChangeKind::Renamed => "renamed",
+			ChangeKind::TypeChanged => "type changed",
+			ChangeKind::PresenceChanged => "presence changed",
+			ChangeKind::Reordered => "reordered",
+			ChangeKind::Modified => "modified",
+			ChangeKind::Version => "version",
+		};
+		f.write_str(kind)
+	}
+}
+
+/// Single change found between the latest and the current schema.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Finding {
+	/// Path of the changed element, e.g. `NewOrder/field[id=38]`.
+	pub path: String,
+	/// The kind of the change.
+	pub kind: ChangeKind,
+	/// The compatibility level the change implies.
+	pub level: CompatibilityLevel,
+	/// Human readable reason of the change.
+	pub reason: String,
+}
+
+/// Report of all changes found between the latest and the current schema.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CompatibilityReport {
+	findings: Vec<Finding>,
+}
+
+impl CompatibilityReport {
+	/// Create an empty report.
+	pub fn new() -> Self {
+		Self { findings: Vec::new() }
+	}
+
+	/// Record a change found at the given path.
+	pub fn push(
+		&mut self,
+		path: impl Into<String>,
+		kind: ChangeKind,
+		level: CompatibilityLevel,
+		reason: impl Into<String>,
+	) {
+		self.findings
+			.push(Finding { path: path.into(), kind, level, reason: reason.into() });
+	}
+
+	/// Append all findings of the other report.
+	pub fn extend(&mut self, other: CompatibilityReport) {
+		self.findings.extend(other.findings);
+	}
+
+	/// All changes found.
+	pub fn findings(&self) -> &[Finding] {
+		&self.findings
+	}
+
+	/// Check if no changes were found.
+	pub fn is_empty(&self) -> bool {
+		self.findings.is_empty()
+	}
+
+	/// The overall compatibility level computed from all findings.
+	pub fn level(&self) -> CompatibilityLevel {
+		self.findings
+			.iter()
+			.fold(CompatibilityLevel::NoChange, |level, finding| level.combine(finding.level))
+	}
+}
+
+impl Serialize for CompatibilityReport {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		let mut report = serializer.serialize_struct("CompatibilityReport", 2)?;
+		report.serialize_field("level", &self.level())?;
+		report.serialize_field("findings", &self.findings)?;
+		report.end()
+	}
+}
+
+/// Join path of the parent element with the path segment of the child element.
+pub(crate) fn join(path: &str, segment: &str) -> String {
+	if path.is_empty() {
+		segment.to_string()
+	} else {
+		format!("{path}/{segment}")
+	}
+}
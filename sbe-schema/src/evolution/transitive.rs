@@ -0,0 +1,231 @@
+//! Transitive compatibility strategy
+//!
+//! Consumers may still run any released version of the schema, so the candidate is checked
+//! against every historical schema instead of only the latest one.
+
+use serde::Serialize;
+
+use crate::{
+	evolution::join, ChangeKind, CompatibilityLevel, CompatibilityReport, EvolutionError,
+	EvolutionStrategy, SbeSchemaValidator, Schema, SchemaValidator,
+};
+
+/// Checks a candidate schema against every schema of the version history with the same
+/// required compatibility level.
+pub struct TransitiveCompatibility<'a> {
+	level: CompatibilityLevel,
+	current: &'a Schema,
+	history: Vec<(String, &'a Schema)>,
+}
+
+impl<'a> TransitiveCompatibility<'a> {
+	/// Create a strategy requiring the level between the current schema and each historical one.
+	pub fn new(level: CompatibilityLevel, current: &'a Schema) -> Self {
+		Self { level, current, history: Vec::new() }
+	}
+
+	/// Add a historical schema named by the label, e.g. a file or a git revision. Versions are
+	/// added from the oldest to the latest.
+	pub fn version(mut self, label: impl Into<String>, schema: &'a Schema) -> Self {
+		self.history.push((label.into(), schema));
+		self
+	}
+
+	/// Compare the current schema with each historical schema.
+	pub fn versions(&self) -> Result<TransitiveReport, EvolutionError> {
+		let latest = self.history.len().saturating_sub(1);
+		let versions = self
+			.history
+			.iter()
+			.enumerate()
+			.map(|(i, (label, schema))| {
+				let validator = SbeSchemaValidator::new(schema, self.current);
+				let report =
+					if i == latest { validator.compare_all()? } else { compare_older(&validator)? };
+				let compatible = report.level().satisfies(self.level);
+				Ok(VersionReport {
+					label: label.clone(),
+					version: schema.version,
+					compatible,
+					report,
+				})
+			})
+			.collect::<Result<_, EvolutionError>>()?;
+		Ok(TransitiveReport { required: self.level, versions })
+	}
+}
+
+impl EvolutionStrategy for TransitiveCompatibility<'_> {
+	type SchemaType = Schema;
+
+	fn compatibility_level(&self) -> CompatibilityLevel {
+		self.level
+	}
+
+	/// The findings against every historical schema, their paths start with the label of the
+	/// schema.
+	fn report(&self) -> Result<CompatibilityReport, EvolutionError> {
+		Ok(self.versions()?.combined())
+	}
+
+	/// The error names the oldest historical schema the current one is not compatible with.
+	fn verify(&self, report: &CompatibilityReport) -> Result<CompatibilityLevel, EvolutionError> {
+		let level = report.level();
+		if level.satisfies(self.level) {
+			return Ok(level);
+		}
+		match self.versions()?.breaking().next() {
+			Some(broken) => Err(EvolutionError::NotCompatibleWith {
+				label: broken.label.clone(),
+				version: broken.version,
+				level: self.level,
+			}),
+			None => Err(EvolutionError::SchemaNotCompatible(self.level)),
+		}
+	}
+}
+
+/// Compare with a schema older than the latest one, any later version of the current schema is
+/// accepted as the versions in between were released too.
+fn compare_older(validator: &SbeSchemaValidator) -> Result<CompatibilityReport, EvolutionError> {
+	let (older, current) = (validator.latest().version, validator.current().version);
+	let mut report = CompatibilityReport::new();
+	let mut changes = validator.compare_message_header()?;
+	changes.extend(validator.compare_types()?);
+	changes.extend(validator.compare_messages()?);
+
+	if current < older {
+		report.push(
+			"version",
+			ChangeKind::Version,
+			CompatibilityLevel::None,
+			format!("version decreased from {older} to {current}"),
+		);
+	} else if current > older {
+		report.push(
+			"version",
+			ChangeKind::Version,
+			CompatibilityLevel::Full,
+			format!("version bumped from {older} to {current}"),
+		);
+	} else if !changes.is_empty() {
+		report.push(
+			"version",
+			ChangeKind::Version,
+			CompatibilityLevel::None,
+			"schema changed without bumping the version",
+		);
+	}
+	report.extend(changes);
+	Ok(report)
+}
+
+/// Changes found between the current schema and one historical schema.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VersionReport {
+	/// Label of the historical schema, e.g. a file or a git revision.
+	pub label: String,
+	/// Version of the historical schema.
+	pub version: u32,
+	/// Whether the changes satisfy the required compatibility level.
+	pub compatible: bool,
+	/// The changes found.
+	pub report: CompatibilityReport,
+}
+
+/// Changes found between the current schema and every historical schema, from the oldest.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TransitiveReport {
+	required: CompatibilityLevel,
+	versions: Vec<VersionReport>,
+}
+
+impl TransitiveReport {
+	/// The compatibility level required against every historical schema.
+	pub fn required(&self) -> CompatibilityLevel {
+		self.required
+	}
+
+	/// Reports for every historical schema, from the oldest.
+	pub fn versions(&self) -> &[VersionReport] {
+		&self.versions
+	}
+
+	/// Historical schemas the current schema is not compatible with.
+	pub fn breaking(&self) -> impl Iterator<Item = &VersionReport> {
+		self.versions.iter().filter(|v| !v.compatible)
+	}
+
+	/// The compatibility level that holds against every historical schema.
+	pub fn level(&self) -> CompatibilityLevel {
+		self.versions
+			.iter()
+			.fold(CompatibilityLevel::NoChange, |level, v| level.combine(v.report.level()))
+	}
+
+	/// The findings of every historical schema in one report, their paths start with the label
+	/// of the schema.
+	pub fn combined(&self) -> CompatibilityReport {
+		let mut combined = CompatibilityReport::new();
+		for version in &self.versions {
+			for finding in version.report.findings() {
+				combined.push(
+					join(&version.label, &finding.path),
+					finding.kind,
+					finding.level,
+					finding.reason.clone(),
+				);
+			}
+		}
+		combined
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::fixtures;
+
+	fn schema(version: u32, fields: &str) -> Schema {
+		fixtures::schema(
+			version,
+			"",
+			&format!(r#"<sbe:message name="Order" id="1">{fields}</sbe:message>"#),
+		)
+	}
+
+	const PRICE: &str = r#"<field name="price" id="1" type="int64"/>"#;
+	const QTY: &str = r#"<field name="qty" id="2" type="int64" presence="optional"/>"#;
+
+	#[test]
+	fn test_older_versions_may_be_skipped() {
+		let v1 = schema(1, PRICE);
+		let v2 = schema(2, &format!("{PRICE}{QTY}"));
+		let v3 = schema(3, &format!("{PRICE}{QTY}"));
+		let strategy = TransitiveCompatibility::new(CompatibilityLevel::Backward, &v3)
+			.version("v1", &v1)
+			.version("v2", &v2);
+		assert_eq!(strategy.versions().unwrap().versions().len(), 2);
+		assert_eq!(strategy.check().unwrap(), CompatibilityLevel::Full);
+	}
+
+	#[test]
+	fn test_names_breaking_version() {
+		// v2 removed the required price, consumers of v1 still read it
+		let v1 = schema(1, &format!("{PRICE}{QTY}"));
+		let v2 = schema(2, QTY);
+		let v3 = schema(3, QTY);
+		let strategy = TransitiveCompatibility::new(CompatibilityLevel::Forward, &v3)
+			.version("v1", &v1)
+			.version("v2", &v2);
+		let versions = strategy.versions().unwrap();
+		let breaking = versions.breaking().map(|v| v.label.as_str()).collect::<Vec<_>>();
+		assert_eq!(breaking, ["v1"]);
+		let report = strategy.report().unwrap();
+		assert!(report.findings().iter().any(|f| f.path == "v1/Order/field[id=1]"));
+		assert!(matches!(
+			strategy.verify(&report),
+			Err(EvolutionError::NotCompatibleWith { ref label, version: 1, .. }) if label == "v1"
+		));
+	}
+}
@@ -1,33 +1,22 @@
 //! SBE Schema Validator
 
-use core::panic;
-
 use crate::{
-	evolution::check_vec,
-	types::{Composite, EnumType, SetType, Types},
-	CompatibilityLevel, EvolutionError, PartialCompatibility, Schema, SchemaValidator,
+	evolution::compare_vec,
+	types::{Composite, EnumType, SetType, Type},
+	ChangeKind, CompatibilityLevel, CompatibilityReport, EvolutionError, PartialCompatibility,
+	Schema, SchemaValidator,
 };
 
 /// A validator for SBE schema versions.
 pub struct SbeSchemaValidator<'a> {
 	latest_schema: &'a Schema,
 	current_schema: &'a Schema,
-	// latest_vtable: VTable<'a>,
-	// current_vtable: VTable<'a>,
 }
 
 impl<'a> SbeSchemaValidator<'a> {
 	/// Create a new `SbeSchemaValidator` with the given schemas.
 	pub fn new(latest_schema: &'a Schema, current_schema: &'a Schema) -> Self {
-		// let latest_vtable = crate::build_vtable(latest_schema);
-		// let current_vtable = crate::build_vtable(latest_schema);
-
-		Self {
-			latest_schema,
-			current_schema,
-			// latest_vtable,
-			// current_vtable,
-		}
+		Self { latest_schema, current_schema }
 	}
 }
 
@@ -42,99 +31,112 @@ impl<'a> SchemaValidator for SbeSchemaValidator<'a> {
 		self.current_schema
 	}
 
-	fn compare_version(&self) -> Result<CompatibilityLevel, EvolutionError> {
+	fn compare_version(&self) -> Result<CompatibilityReport, EvolutionError> {
 		let (latest, current) = (self.latest().version, self.current().version);
-		match current.cmp(&latest) {
-			std::cmp::Ordering::Less => Ok(CompatibilityLevel::None),
-			std::cmp::Ordering::Equal => Ok(CompatibilityLevel::NoChange),
+		let mut report = CompatibilityReport::new();
+		let (level, reason) = match current.cmp(&latest) {
+			std::cmp::Ordering::Less =>
+				(CompatibilityLevel::None, format!("version decreased from {latest} to {current}")),
+			std::cmp::Ordering::Equal => return Ok(report),
 			std::cmp::Ordering::Greater =>
 				if current - latest == 1 {
-					Ok(CompatibilityLevel::Full)
+					(CompatibilityLevel::Full, format!("version bumped from {latest} to {current}"))
 				} else {
-					Ok(CompatibilityLevel::None)
+					(
+						CompatibilityLevel::None,
+						format!("version skipped from {latest} to {current}"),
+					)
 				},
-		}
+		};
+		report.push("version", ChangeKind::Version, level, reason);
+		Ok(report)
 	}
 
-	fn compare_message_header(&self) -> Result<CompatibilityLevel, EvolutionError> {
+	fn compare_message_header(&self) -> Result<CompatibilityReport, EvolutionError> {
 		let latest = self.latest().message_header();
 		let current = self.current().message_header();
 
 		match (latest, current) {
-			(Some(latest), Some(current)) =>
-				Ok(PartialCompatibility::partial_compatibility(current, latest)),
+			(Some(latest), Some(current)) => {
+				let mut report = CompatibilityReport::new();
+				current.compare(latest, &current.name, &mut report);
+				Ok(report)
+			},
 			// SBE requires message header to be defined
 			_ => Err(EvolutionError::MissingMessageHeader),
 		}
 	}
 
-	fn compare_types(&self) -> Result<CompatibilityLevel, EvolutionError> {
-		let latest_flat_types = flat_types(self.latest().types.as_ref());
-		let current_flat_types = flat_types(self.current().types.as_ref());
+	fn compare_types(&self) -> Result<CompatibilityReport, EvolutionError> {
+		let latest_flat_types = flat_types(self.latest());
+		let current_flat_types = flat_types(self.current());
+		let mut report = CompatibilityReport::new();
 
-		let composites = check_vec::<&Composite>(
-			latest_flat_types.composites.as_ref(),
-			current_flat_types.composites.as_ref(),
+		// types are matched by name so that a modified type is compared member by member
+		compare_vec::<&Type>(
+			current_flat_types.types.as_ref(),
+			latest_flat_types.types.as_ref(),
+			"types",
+			&mut report,
 		);
 
-		if composites == CompatibilityLevel::None {
-			return Ok(CompatibilityLevel::None);
-		}
+		compare_vec::<&Composite>(
+			current_flat_types.composites.as_ref(),
+			latest_flat_types.composites.as_ref(),
+			"types",
+			&mut report,
+		);
 
-		let enums = check_vec::<&EnumType>(
-			latest_flat_types.enums.as_ref(),
+		compare_vec::<&EnumType>(
 			current_flat_types.enums.as_ref(),
+			latest_flat_types.enums.as_ref(),
+			"types",
+			&mut report,
 		);
 
-		if enums == CompatibilityLevel::None {
-			return Ok(CompatibilityLevel::None);
-		}
-
-		let sets = check_vec::<&SetType>(
-			latest_flat_types.sets.as_ref(),
+		compare_vec::<&SetType>(
 			current_flat_types.sets.as_ref(),
+			latest_flat_types.sets.as_ref(),
+			"types",
+			&mut report,
 		);
 
-		if sets == CompatibilityLevel::None {
-			return Ok(CompatibilityLevel::None);
-		}
-
-		match (composites, enums, sets) {
-			(CompatibilityLevel::None, _, _) => Ok(CompatibilityLevel::None),
-			(_, CompatibilityLevel::None, _) => Ok(CompatibilityLevel::None),
-			(_, _, CompatibilityLevel::None) => Ok(CompatibilityLevel::None),
-			(CompatibilityLevel::Backward, _, _) => Ok(CompatibilityLevel::Backward),
-			(_, CompatibilityLevel::Backward, _) => Ok(CompatibilityLevel::Backward),
-			(_, _, CompatibilityLevel::Backward) => Ok(CompatibilityLevel::Backward),
-			(CompatibilityLevel::Forward, _, _) => Ok(CompatibilityLevel::Forward),
-			(_, CompatibilityLevel::Forward, _) => Ok(CompatibilityLevel::Forward),
-			(_, _, CompatibilityLevel::Forward) => Ok(CompatibilityLevel::Forward),
-			(CompatibilityLevel::Full, _, _) => Ok(CompatibilityLevel::Full),
-			(_, CompatibilityLevel::Full, _) => Ok(CompatibilityLevel::Full),
-			(_, _, CompatibilityLevel::Full) => Ok(CompatibilityLevel::Full),
-			(
-				CompatibilityLevel::NoChange,
-				CompatibilityLevel::NoChange,
-				CompatibilityLevel::NoChange,
-			) => Ok(CompatibilityLevel::NoChange),
-		}
+		Ok(report)
 	}
 
-	fn compare_messages(&self) -> Result<CompatibilityLevel, EvolutionError> {
-		panic!("Not implemented")
+	fn compare_messages(&self) -> Result<CompatibilityReport, EvolutionError> {
+		// messages are matched by template id, the name is not part of the wire format
+		let mut report = CompatibilityReport::new();
+		compare_vec(
+			self.current().messages.as_ref(),
+			self.latest().messages.as_ref(),
+			"",
+			&mut report,
+		);
+		Ok(report)
 	}
 }
 
-fn flat_types(types: Option<&Vec<Types>>) -> FlatTypes {
-	let mut flat_types = FlatTypes { enums: None, composites: None, sets: None };
+/// Types of all `<types>` sections, except the message header which is compared on its own.
+fn flat_types(schema: &Schema) -> FlatTypes {
+	let types = schema.types.as_ref();
+	let header = schema.header_type_name();
+	let mut flat_types = FlatTypes { types: None, enums: None, composites: None, sets: None };
 
 	if let Some(types) = types {
+		flat_types.types = Some(
+			types
+				.iter()
+				.filter(|t| t.types.is_some())
+				.flat_map(|t| t.types.as_ref().unwrap())
+				.collect::<Vec<_>>(),
+		);
 		flat_types.composites = Some(
 			types
 				.iter()
 				.filter(|t| t.composites.is_some())
 				.flat_map(|t| t.composites.as_ref().unwrap())
-				.filter(|c| c.name != "messageHeader")
+				.filter(|c| c.name != header)
 				.collect::<Vec<_>>(),
 		);
 		flat_types.enums = Some(
@@ -159,7 +161,239 @@ fn flat_types(types: Option<&Vec<Types>>) -> FlatTypes {
 /// SBE allows to have multiple <types> secions in the schema file.
 /// this structure is to flatten the types into a single structure to make it easier to compare.
 struct FlatTypes<'a> {
+	types: Option<Vec<&'a Type>>,
 	enums: Option<Vec<&'a EnumType>>,
 	composites: Option<Vec<&'a Composite>>,
 	sets: Option<Vec<&'a SetType>>,
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::fixtures;
+	use quick_xml::de::from_str;
+
+	fn schema(messages: &str) -> Schema {
+		fixtures::schema(1, "", messages)
+	}
+
+	fn compare(latest: &str, current: &str) -> CompatibilityLevel {
+		let (latest, current) = (schema(latest), schema(current));
+		SbeSchemaValidator::new(&latest, &current).compare_messages().unwrap().level()
+	}
+
+	const ORDER: &str = r#"
+		<sbe:message name="Order" id="1">
+			<field name="price" id="1" type="int64"/>
+			<field name="qty" id="2" type="int32"/>
+			<group name="legs" id="3" dimensionType="groupSizeEncoding">
+				<field name="leg" id="4" type="int32"/>
+			</group>
+			<data name="text" id="5" type="varDataEncoding"/>
+		</sbe:message>"#;
+
+	#[test]
+	fn test_compare_messages_no_change() {
+		assert_eq!(compare(ORDER, ORDER), CompatibilityLevel::NoChange);
+	}
+
+	#[test]
+	fn test_compare_messages_matched_by_id() {
+		let renamed = ORDER.replace(r#"name="Order""#, r#"name="NewOrder""#);
+		assert_eq!(compare(ORDER, &renamed), CompatibilityLevel::Full);
+
+		let new_id = ORDER.replace(r#"name="Order" id="1""#, r#"name="Order" id="2""#);
+		assert_eq!(compare(ORDER, &new_id), CompatibilityLevel::None);
+	}
+
+	#[test]
+	fn test_compare_messages_field_added() {
+		let added = ORDER.replace(
+			r#"<field name="qty" id="2" type="int32"/>"#,
+			r#"<field name="qty" id="2" type="int32"/>
+			<field name="side" id="6" type="char" sinceVersion="1"/>"#,
+		);
+		assert_eq!(compare(ORDER, &added), CompatibilityLevel::Forward);
+	}
+
+	#[test]
+	fn test_compare_messages_field_deleted() {
+		let deleted = ORDER.replace(r#"<field name="qty" id="2" type="int32"/>"#, "");
+		assert_eq!(compare(ORDER, &deleted), CompatibilityLevel::Backward);
+	}
+
+	#[test]
+	fn test_compare_messages_fields_added_to_empty_message() {
+		let empty = r#"<sbe:message name="Heartbeat" id="1"/>"#;
+		let optional = r#"
+			<sbe:message name="Heartbeat" id="1">
+				<field name="seq" id="1" type="int64" presence="optional"/>
+			</sbe:message>"#;
+		assert_eq!(compare(empty, optional), CompatibilityLevel::Backward);
+		assert_eq!(compare(optional, empty), CompatibilityLevel::Forward);
+
+		let required = optional.replace(r#" presence="optional""#, "");
+		assert_eq!(compare(empty, &required), CompatibilityLevel::Forward);
+		assert_eq!(compare(&required, empty), CompatibilityLevel::Backward);
+	}
+
+	#[test]
+	fn test_compare_messages_field_type_changed() {
+		let changed = ORDER.replace(
+			r#"<field name="qty" id="2" type="int32"/>"#,
+			r#"<field name="qty" id="2" type="int64"/>"#,
+		);
+		assert_eq!(compare(ORDER, &changed), CompatibilityLevel::None);
+	}
+
+	#[test]
+	fn test_compare_messages_cosmetic_changes() {
+		let annotated = ORDER
+			.replace(r#"name="Order" id="1""#, r#"name="Order" id="1" semanticType="D" deprecated="1""#)
+			.replace(
+				r#"<field name="price" id="1" type="int64"/>"#,
+				r#"<field name="price" id="1" type="int64" semanticType="Price" epoch="unix" timeUnit="nanosecond" deprecated="1"/>"#,
+			);
+		let (latest, current) = (schema(ORDER), schema(&annotated));
+		let report = SbeSchemaValidator::new(&latest, &current).compare_all().unwrap();
+		assert!(report.is_empty(), "{report:?}");
+	}
+
+	#[test]
+	fn test_compare_messages_field_presence_changed() {
+		let optional = ORDER.replace(
+			r#"<field name="qty" id="2" type="int32"/>"#,
+			r#"<field name="qty" id="2" type="int32" presence="optional"/>"#,
+		);
+		assert_eq!(compare(ORDER, &optional), CompatibilityLevel::Backward);
+		assert_eq!(compare(&optional, ORDER), CompatibilityLevel::Forward);
+
+		let constant = ORDER.replace(
+			r#"<field name="qty" id="2" type="int32"/>"#,
+			r#"<field name="qty" id="2" type="int32" presence="constant" valueRef="Qty.One"/>"#,
+		);
+		assert_eq!(compare(ORDER, &constant), CompatibilityLevel::None);
+	}
+
+	#[test]
+	fn test_compare_messages_report() {
+		let changed = ORDER.replace(
+			r#"<field name="qty" id="2" type="int32"/>"#,
+			r#"<field name="qty" id="2" type="int64"/>"#,
+		);
+		let (latest, current) = (schema(ORDER), schema(&changed));
+		let report = SbeSchemaValidator::new(&latest, &current).compare_messages().unwrap();
+		assert_eq!(report.findings().len(), 1);
+
+		let finding = &report.findings()[0];
+		assert_eq!(finding.path, "Order/field[id=2]");
+		assert_eq!(finding.kind, ChangeKind::TypeChanged);
+		assert_eq!(finding.level, CompatibilityLevel::None);
+		assert_eq!(finding.reason, "type changed from int32 to int64");
+	}
+
+	#[test]
+	fn test_compare_messages_field_reordered() {
+		let reordered = ORDER.replace(
+			r#"<field name="price" id="1" type="int64"/>
+			<field name="qty" id="2" type="int32"/>"#,
+			r#"<field name="qty" id="2" type="int32"/>
+			<field name="price" id="1" type="int64"/>"#,
+		);
+		assert_eq!(compare(ORDER, &reordered), CompatibilityLevel::None);
+	}
+
+	#[test]
+	fn test_compare_messages_group_changed() {
+		let changed = ORDER.replace(
+			r#"<field name="leg" id="4" type="int32"/>"#,
+			r#"<field name="leg" id="4" type="int64"/>"#,
+		);
+		assert_eq!(compare(ORDER, &changed), CompatibilityLevel::None);
+
+		let added = ORDER.replace(
+			r#"<field name="leg" id="4" type="int32"/>"#,
+			r#"<field name="leg" id="4" type="int32"/>
+				<field name="ratio" id="7" type="int32" sinceVersion="1"/>"#,
+		);
+		assert_eq!(compare(ORDER, &added), CompatibilityLevel::Forward);
+	}
+
+	#[test]
+	fn test_compare_messages_nested_group_changed() {
+		let nested = ORDER.replace(
+			r#"<field name="leg" id="4" type="int32"/>"#,
+			r#"<field name="leg" id="4" type="int32"/>
+				<group name="fills" id="8" dimensionType="groupSizeEncoding">
+					<field name="fillQty" id="9" type="int32"/>
+				</group>"#,
+		);
+		let changed = nested.replace(
+			r#"<field name="fillQty" id="9" type="int32"/>"#,
+			r#"<field name="fillQty" id="9" type="int64"/>"#,
+		);
+		let (latest, current) = (schema(&nested), schema(&changed));
+		let report = SbeSchemaValidator::new(&latest, &current).compare_messages().unwrap();
+		assert_eq!(report.level(), CompatibilityLevel::None);
+		assert_eq!(report.findings()[0].path, "Order/group[id=3]/group[id=8]/field[id=9]");
+	}
+
+	#[test]
+	fn test_compare_messages_data_changed() {
+		let changed = ORDER.replace(
+			r#"<data name="text" id="5" type="varDataEncoding"/>"#,
+			r#"<data name="text" id="5" type="varStringEncoding"/>"#,
+		);
+		assert_eq!(compare(ORDER, &changed), CompatibilityLevel::None);
+	}
+
+	#[test]
+	fn test_compare_types_nested_and_standalone() {
+		let types = |encoding: &str, length: &str| {
+			format!(
+				r#"<messageSchema version="1"><types>
+				<type name="Symbol" primitiveType="char" length="{length}"/>
+				<composite name="outer">
+					<enum name="side" encodingType="{encoding}"><validValue name="Buy">1</validValue></enum>
+				</composite>
+				</types></messageSchema>"#
+			)
+		};
+		let latest: Schema = from_str(&types("uint8", "8")).unwrap();
+		let current: Schema = from_str(&types("char", "4")).unwrap();
+		let report = SbeSchemaValidator::new(&latest, &current).compare_types().unwrap();
+		let paths = report.findings().iter().map(|f| f.path.as_str()).collect::<Vec<_>>();
+		assert_eq!(
+			paths,
+			["types/type[name=Symbol]", "types/composite[name=outer]/enum[name=side]"]
+		);
+	}
+
+	#[test]
+	fn test_custom_header_type() {
+		let xml = |version: &str| {
+			format!(
+				r#"<messageSchema version="1" headerType="header"><types>
+				<composite name="header"><type name="version" primitiveType="{version}"/></composite>
+				</types></messageSchema>"#
+			)
+		};
+		let latest: Schema = from_str(&xml("uint16")).unwrap();
+		let current: Schema = from_str(&xml("uint32")).unwrap();
+		let validator = SbeSchemaValidator::new(&latest, &current);
+		assert_eq!(validator.compare_message_header().unwrap().level(), CompatibilityLevel::None);
+		assert!(validator.compare_types().unwrap().findings().is_empty());
+	}
+
+	#[test]
+	fn test_compare_messages_message_added() {
+		let added = format!(
+			r#"{ORDER}
+			<sbe:message name="Cancel" id="2">
+				<field name="orderId" id="1" type="uint64"/>
+			</sbe:message>"#
+		);
+		assert_eq!(compare(ORDER, &added), CompatibilityLevel::Forward);
+		assert_eq!(compare(&added, ORDER), CompatibilityLevel::Backward);
+	}
+}
@@ -0,0 +1,353 @@
+//! Stable fingerprints of the wire format of a schema.
+//!
+//! The fingerprint is a SHA-256 over a canonical binary form of everything that affects how
+//! messages are encoded: ids, primitive types, lengths, offsets, block lengths, presence,
+//! null and constant values, since versions, the byte order and the message header. Names,
+//! descriptions, semantic types and deprecation are cosmetic and left out, so two schemas
+//! with the same fingerprint encode and decode the same bytes.
+
+use crate::{
+	types::{
+		ByteOrder, Composite, CompositeMember, Data, EncodingType, EnumType, Field, Group, Message,
+		Presence, SetType, Type,
+	},
+	vtable::{build_vtable, VTable, VTableObject},
+	Schema,
+};
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+/// SHA-256 fingerprint of the wire format of a schema or a message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Fingerprint([u8; 32]);
+
+impl Fingerprint {
+	/// The raw digest.
+	pub fn as_bytes(&self) -> &[u8; 32] {
+		&self.0
+	}
+}
+
+/// Lowercase hex digest.
+impl fmt::Display for Fingerprint {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		self.0.iter().try_for_each(|byte| write!(f, "{byte:02x}"))
+	}
+}
+
+impl Schema {
+	/// Fingerprint of the whole schema: id, version, byte order, message header and all
+	/// messages ordered by id.
+	pub fn fingerprint(&self) -> Fingerprint {
+		let vtable = build_vtable(self);
+		let mut canonical = Canonical::new(&vtable);
+		canonical.text("schema");
+		canonical.number(self.id.map(|id| id as u64));
+		canonical.number(Some(self.version.into()));
+		canonical.preamble(self);
+		let mut messages = self.messages().collect::<Vec<_>>();
+		messages.sort_by_key(|message| message.id);
+		for message in messages {
+			canonical.message(message);
+		}
+		canonical.finish()
+	}
+
+	/// Fingerprint of the message with the given template id, covering its members, the
+	/// byte order and the message header. It does not change when other messages or the
+	/// schema version change.
+	pub fn message_fingerprint(&self, id: u32) -> Option<Fingerprint> {
+		let message = self.message(id)?;
+		Some(message_fingerprint(self, &build_vtable(self), message))
+	}
+
+	/// Fingerprints of all messages keyed by template id, in the order of the schema.
+	pub fn message_fingerprints(&self) -> Vec<(u32, Fingerprint)> {
+		let vtable = build_vtable(self);
+		self.messages()
+			.map(|message| (message.id, message_fingerprint(self, &vtable, message)))
+			.collect()
+	}
+}
+
+fn message_fingerprint(schema: &Schema, vtable: &VTable, message: &Message) -> Fingerprint {
+	let mut canonical = Canonical::new(vtable);
+	canonical.preamble(schema);
+	canonical.message(message);
+	canonical.finish()
+}
+
+/// Writes the canonical form into the digest. Every value is tagged or length prefixed so
+/// that different schemas cannot produce the same byte stream.
+struct Canonical<'v, 'a> {
+	vtable: &'v VTable<'a>,
+	digest: Sha256,
+	/// Composites being written, a composite referring to itself is written as a cycle.
+	stack: Vec<&'a str>,
+}
+
+impl<'v, 'a> Canonical<'v, 'a> {
+	fn new(vtable: &'v VTable<'a>) -> Self {
+		Self { vtable, digest: Sha256::new(), stack: Vec::new() }
+	}
+
+	fn finish(self) -> Fingerprint {
+		Fingerprint(self.digest.finalize().into())
+	}
+
+	fn text(&mut self, text: &str) {
+		self.digest.update((text.len() as u64).to_be_bytes());
+		self.digest.update(text.as_bytes());
+	}
+
+	fn optional_text(&mut self, text: Option<&str>) {
+		match text {
+			Some(text) => {
+				self.digest.update([1]);
+				// surrounding whitespace of element text is not significant
+				self.text(text.trim());
+			},
+			None => self.digest.update([0]),
+		}
+	}
+
+	fn number(&mut self, number: Option<u64>) {
+		match number {
+			Some(number) => {
+				self.digest.update([1]);
+				self.digest.update(number.to_be_bytes());
+			},
+			None => self.digest.update([0]),
+		}
+	}
+
+	fn presence(&mut self, presence: Option<&Presence>) {
+		self.text(match presence {
+			Some(Presence::Constant) => "constant",
+			Some(Presence::Optional) => "optional",
+			Some(Presence::Required) | None => "required",
+		});
+	}
+
+	fn since_version(&mut self, since_version: Option<u32>) {
+		// sinceVersion 0 is the default
+		self.number(Some(since_version.unwrap_or_default().into()));
+	}
+
+	fn preamble(&mut self, schema: &Schema) {
+		self.text(match schema.byte_order {
+			Some(ByteOrder::BigEndian) => "bigEndian",
+			Some(ByteOrder::LittleEndian) | None => "littleEndian",
+		});
+		self.text("header");
+		self.reference(schema.header_type_name());
+	}
+
+	fn message(&mut self, message: &Message) {
+		self.text("message");
+		self.number(Some(message.id.into()));
+		self.number(message.block_length.map(u64::from));
+		self.since_version(message.since_version);
+		self.members(&message.fields, &message.groups, &message.data);
+	}
+
+	fn members(
+		&mut self,
+		fields: &Option<Vec<Field>>,
+		groups: &Option<Vec<Group>>,
+		data: &Option<Vec<Data>>,
+	) {
+		for field in fields.iter().flatten() {
+			self.field(field);
+		}
+		for group in groups.iter().flatten() {
+			self.group(group);
+		}
+		for data in data.iter().flatten() {
+			self.data(data);
+		}
+		self.text("end");
+	}
+
+	fn field(&mut self, field: &Field) {
+		self.text("field");
+		self.number(Some(field.id.into()));
+		self.number(field.offset.map(u64::from));
+		self.presence(field.presence.as_ref());
+		self.optional_text(field.value_ref.as_deref());
+		self.since_version(field.since_version);
+		self.reference(&field.r#type);
+	}
+
+	fn group(&mut self, group: &Group) {
+		self.text("group");
+		self.number(Some(group.id.into()));
+		self.number(group.block_length.map(u64::from));
+		self.since_version(group.since_version);
+		self.reference(&group.dimension_type);
+		self.members(&group.fields, &group.groups, &group.data);
+	}
+
+	fn data(&mut self, data: &Data) {
+		self.text("data");
+		self.number(Some(data.id.into()));
+		self.since_version(data.since_version);
+		self.reference(&data.r#type);
+	}
+
+	/// Writes the definition of the referenced type in place of its name.
+	fn reference(&mut self, name: &str) {
+		match self.vtable.resolve(name).copied() {
+			Some(VTableObject::Primitive(primitive)) => {
+				self.text("primitive");
+				self.text(primitive.name());
+			},
+			Some(VTableObject::Type(r#type)) => self.r#type(r#type),
+			Some(VTableObject::Composite(composite)) => self.composite(composite),
+			Some(VTableObject::Enum(enum_type)) => self.enum_type(enum_type),
+			Some(VTableObject::Set(set)) => self.set(set),
+			// the name is all there is to an unresolved or misplaced reference
+			None => {
+				self.text("unresolved");
+				self.text(name);
+			},
+		}
+	}
+
+	fn r#type(&mut self, r#type: &Type) {
+		self.text("type");
+		self.text(r#type.primitive_type.map(|primitive| primitive.name()).unwrap_or_default());
+		self.number(r#type.length.map(u64::from));
+		self.number(r#type.offset.map(u64::from));
+		self.presence(r#type.presence.as_ref());
+		self.optional_text(r#type.null_value.as_deref());
+		self.optional_text(r#type.value.as_deref());
+		self.optional_text(r#type.value_ref.as_deref());
+		self.optional_text(r#type.character_encoding.as_deref());
+		self.since_version(r#type.since_version);
+	}
+
+	fn composite(&mut self, composite: &'a Composite) {
+		if self.stack.contains(&composite.name.as_str()) {
+			self.text("cycle");
+			return;
+		}
+		self.stack.push(&composite.name);
+		self.text("composite");
+		self.number(composite.offset.map(u64::from));
+		self.since_version(composite.since_version);
+		for member in composite.members() {
+			match member {
+				CompositeMember::Type(r#type) => self.r#type(r#type),
+				CompositeMember::Ref(r#ref) => {
+					self.text("ref");
+					self.number(r#ref.offset.map(u64::from));
+					self.presence(r#ref.presence.as_ref());
+					self.optional_text(r#ref.value_ref.as_deref());
+					self.since_version(r#ref.since_version);
+					self.reference(&r#ref.ref_type);
+				},
+				CompositeMember::Enum(enum_type) => self.enum_type(enum_type),
+				CompositeMember::Set(set) => self.set(set),
+				CompositeMember::Composite(composite) => self.composite(composite),
+			}
+		}
+		self.text("end");
+		self.stack.pop();
+	}
+
+	fn encoding(&mut self, encoding_type: Option<&EncodingType>) {
+		match encoding_type {
+			Some(EncodingType::Primitive(primitive)) => {
+				self.text("primitive");
+				self.text(primitive.name());
+			},
+			Some(EncodingType::Type(name)) => self.reference(name),
+			None => self.text("none"),
+		}
+	}
+
+	fn enum_type(&mut self, enum_type: &EnumType) {
+		self.text("enum");
+		self.encoding(enum_type.encoding_type.as_ref());
+		self.number(enum_type.offset.map(u64::from));
+		self.optional_text(enum_type.null_value.as_deref());
+		self.since_version(enum_type.since_version);
+		for value in enum_type.valid_values() {
+			self.text(value.value.trim());
+			self.since_version(value.since_version);
+		}
+		self.text("end");
+	}
+
+	fn set(&mut self, set: &SetType) {
+		self.text("set");
+		self.encoding(set.encoding_type.as_ref());
+		self.number(set.offset.map(u64::from));
+		self.since_version(set.since_version);
+		for choice in set.choices() {
+			self.text(choice.value.trim());
+			self.since_version(choice.since_version);
+		}
+		self.text("end");
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::fixtures;
+
+	fn schema(message: &str) -> Schema {
+		let side = r#"<enum name="Side" encodingType="char">
+			<validValue name="Buy">B</validValue>
+			<validValue name="Sell">S</validValue>
+		</enum>"#;
+		fixtures::schema(0, side, message)
+	}
+
+	#[test]
+	fn test_cosmetic_changes_keep_fingerprint() {
+		let base = schema(
+			r#"<sbe:message name="Order" id="1"><field name="side" id="1" type="Side"/></sbe:message>"#,
+		);
+		let renamed = schema(
+			r#"<sbe:message name="NewOrder" id="1" description="an order" semanticType="D">
+				<field name="orderSide" id="1" type="Side" description="side" deprecated="1"/>
+			</sbe:message>"#,
+		);
+		assert_eq!(base.fingerprint(), renamed.fingerprint());
+		assert_eq!(base.message_fingerprint(1), renamed.message_fingerprint(1));
+		assert_eq!(
+			base.fingerprint().to_string(),
+			"5f5dd5beb8cd54d11598bb37e572bcbf91236c79fefc33962f0045bb7b09dc74"
+		);
+	}
+
+	#[test]
+	fn test_wire_changes_change_fingerprint() {
+		let base = schema(
+			r#"<sbe:message name="Order" id="1"><field name="side" id="1" type="Side"/></sbe:message>"#,
+		);
+		let retyped = schema(
+			r#"<sbe:message name="Order" id="1"><field name="side" id="1" type="char"/></sbe:message>"#,
+		);
+		assert_ne!(base.fingerprint(), retyped.fingerprint());
+		assert_ne!(base.message_fingerprint(1), retyped.message_fingerprint(1));
+	}
+
+	#[test]
+	fn test_message_fingerprint_ignores_other_messages() {
+		let single = schema(
+			r#"<sbe:message name="Order" id="1"><field name="side" id="1" type="Side"/></sbe:message>"#,
+		);
+		let both = schema(
+			r#"<sbe:message name="Order" id="1"><field name="side" id="1" type="Side"/></sbe:message>
+			<sbe:message name="Cancel" id="2"><field name="side" id="1" type="Side"/></sbe:message>"#,
+		);
+		assert_ne!(single.fingerprint(), both.fingerprint());
+		assert_eq!(single.message_fingerprint(1), both.message_fingerprint(1));
+		assert_eq!(both.message_fingerprints().len(), 2);
+		assert_eq!(both.message_fingerprint(3), None);
+	}
+}
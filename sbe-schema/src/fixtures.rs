@@ -0,0 +1,24 @@
+//! Schemas shared by the tests.
+
+use crate::Schema;
+
+/// Schema of the version with the usual message header, the types are added next to the header
+/// and the messages after the types.
+pub(crate) fn schema(version: u32, types: &str, messages: &str) -> Schema {
+	let xml = format!(
+		r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+		<sbe:messageSchema xmlns:sbe="http://fixprotocol.io/2016/sbe" id="1" version="{version}">
+			<types>
+				<composite name="messageHeader">
+					<type name="blockLength" primitiveType="uint16"/>
+					<type name="templateId" primitiveType="uint16"/>
+					<type name="schemaId" primitiveType="uint16"/>
+					<type name="version" primitiveType="uint16"/>
+				</composite>
+				{types}
+			</types>
+			{messages}
+		</sbe:messageSchema>"#
+	);
+	quick_xml::de::from_str(&xml).expect("Failed to parse XML")
+}
@@ -0,0 +1,677 @@
+//! Wire layout of the messages of a schema: offsets, block lengths and encoded sizes.
+
+use thiserror::Error;
+
+use crate::{
+	build_vtable,
+	evolution::{join, Element},
+	types::{
+		Composite, CompositeMember, Data, EncodingType, Field, Group, Message, Presence, Type,
+	},
+	Result, Schema, VTable, VTableObject,
+};
+
+/// Name of the member of the dimension composite holding the number of entries of a group.
+const NUM_IN_GROUP: &str = "numInGroup";
+/// Name of the member of the var data composite holding the length of the data.
+const LENGTH: &str = "length";
+
+/// Problems that prevent computing the layout.
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum LayoutError {
+	/// The referenced type is not defined.
+	#[error("{path}: unresolved type `{name}`")]
+	Unresolved {
+		/// The referenced name.
+		name: String,
+		/// Path of the element with the reference.
+		path: String,
+	},
+	/// The composite contains itself.
+	#[error("{path}: composite `{name}` contains itself")]
+	Cycle {
+		/// Name of the composite.
+		name: String,
+		/// Path of the ref closing the cycle.
+		path: String,
+	},
+	/// The explicit offset lies within the previous member.
+	#[error("{path}: offset {offset} overlaps the previous member ending at {end}")]
+	Overlap {
+		/// The explicit offset.
+		offset: u32,
+		/// End of the previous member.
+		end: u32,
+		/// Path of the member.
+		path: String,
+	},
+	/// The explicit block length is too small for the fields.
+	#[error("{path}: block length {block_length} is less than the {required} bytes of the fields")]
+	BlockLength {
+		/// The explicit block length.
+		block_length: u32,
+		/// Length required by the fields.
+		required: u32,
+		/// Path of the message or group.
+		path: String,
+	},
+	/// The element cannot be laid out.
+	#[error("{path}: {message}")]
+	Invalid {
+		/// Description of the problem.
+		message: String,
+		/// Path of the element.
+		path: String,
+	},
+}
+
+/// Layout of all messages and composites of a schema.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaLayout {
+	/// Encoded length of the message header.
+	pub header_length: u32,
+	/// Layouts of the messages, in schema order.
+	pub messages: Vec<MessageLayout>,
+	/// Layouts of the composites of all `<types>` blocks, in schema order.
+	pub composites: Vec<CompositeLayout>,
+}
+
+impl SchemaLayout {
+	/// Layout of the message with the given template id.
+	pub fn message(&self, id: u32) -> Option<&MessageLayout> {
+		self.messages.iter().find(|m| m.id == id)
+	}
+
+	/// Layout of the composite with the given name.
+	pub fn composite(&self, name: &str) -> Option<&CompositeLayout> {
+		self.composites.iter().find(|c| c.name == name)
+	}
+}
+
+/// Layout of a composite.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompositeLayout {
+	/// Name of the composite.
+	pub name: String,
+	/// Encoded size of the composite.
+	pub size: u32,
+	/// Members in the order they are encoded.
+	pub members: Vec<MemberLayout>,
+}
+
+/// Layout of a member of a composite.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemberLayout {
+	/// Name of the member.
+	pub name: String,
+	/// Offset from the start of the composite.
+	pub offset: u32,
+	/// Encoded size, zero for constants.
+	pub size: u32,
+	/// Members of a composite nested in place, empty for other members.
+	pub members: Vec<MemberLayout>,
+}
+
+/// Layout of a message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessageLayout {
+	/// Name of the message.
+	pub name: String,
+	/// Template id of the message.
+	pub id: u32,
+	/// Length of the root block.
+	pub block_length: u32,
+	/// Fields of the root block.
+	pub fields: Vec<FieldLayout>,
+	/// Repeating groups following the root block.
+	pub groups: Vec<GroupLayout>,
+	/// Var data following the groups.
+	pub data: Vec<DataLayout>,
+	/// Encoded length including the header, with empty groups and var data.
+	pub min_length: u64,
+	/// Encoded length including the header, with all groups and var data at their maximum.
+	pub max_length: u64,
+}
+
+/// Layout of a field within a block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldLayout {
+	/// Name of the field.
+	pub name: String,
+	/// Id of the field.
+	pub id: u32,
+	/// Offset from the start of the block.
+	pub offset: u32,
+	/// Encoded size, zero for constants.
+	pub size: u32,
+}
+
+/// Layout of a repeating group.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupLayout {
+	/// Name of the group.
+	pub name: String,
+	/// Id of the group.
+	pub id: u32,
+	/// Encoded length of the dimension composite.
+	pub dimension_length: u32,
+	/// Maximum number of entries the dimension can hold.
+	pub max_count: u64,
+	/// Length of the block of each entry.
+	pub block_length: u32,
+	/// Fields of the block of each entry.
+	pub fields: Vec<FieldLayout>,
+	/// Groups nested in each entry.
+	pub groups: Vec<GroupLayout>,
+	/// Var data of each entry.
+	pub data: Vec<DataLayout>,
+	/// Encoded length without entries.
+	pub min_length: u64,
+	/// Encoded length with the maximum number of entries.
+	pub max_length: u64,
+}
+
+/// Layout of a var data element.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataLayout {
+	/// Name of the data.
+	pub name: String,
+	/// Id of the data.
+	pub id: u32,
+	/// Size of the length prefix.
+	pub length_size: u32,
+	/// Maximum length of the data, without the length prefix.
+	pub max_length: u64,
+}
+
+/// Compute the wire layout of all messages of the schema.
+pub fn layout(schema: &Schema) -> Result<SchemaLayout> {
+	let layouter = Layouter::new(schema);
+	let header_length = layouter.header_length(schema)?;
+	let messages = schema
+		.messages()
+		.map(|message| layouter.message(message, header_length))
+		.collect::<std::result::Result<_, _>>()?;
+	let composites = schema
+		.composites()
+		.map(|composite| {
+			let path = join("types", &composite.key());
+			let (size, members) = layouter.composite_members(composite, &path, &mut Vec::new())?;
+			Ok(CompositeLayout { name: composite.name.clone(), size, members })
+		})
+		.collect::<std::result::Result<_, LayoutError>>()?;
+	Ok(SchemaLayout { header_length, messages, composites })
+}
+
+/// Computes sizes of types and layouts of messages by resolving types in the vtable.
+pub(crate) struct Layouter<'a> {
+	vtable: VTable<'a>,
+}
+
+impl<'a> Layouter<'a> {
+	pub(crate) fn new(schema: &'a Schema) -> Self {
+		Self { vtable: build_vtable(schema) }
+	}
+
+	pub(crate) fn header_length(
+		&self,
+		schema: &'a Schema,
+	) -> std::result::Result<u32, LayoutError> {
+		match schema.message_header() {
+			Some(header) => self.composite_size(header, &mut Vec::new()),
+			None => Err(LayoutError::Unresolved {
+				name: schema.header_type_name().to_string(),
+				path: "types".to_string(),
+			}),
+		}
+	}
+
+	pub(crate) fn message(
+		&self,
+		message: &Message,
+		header_length: u32,
+	) -> std::result::Result<MessageLayout, LayoutError> {
+		let path = message.segment();
+		let (block_length, fields) = self.block(&path, message.fields(), message.block_length)?;
+		let groups = message
+			.groups()
+			.map(|group| self.group(&path, group))
+			.collect::<std::result::Result<Vec<_>, _>>()?;
+		let data = self.data(&path, message.data())?;
+
+		let fixed = header_length as u64 + block_length as u64;
+		let min_length = fixed +
+			groups.iter().map(|g| g.min_length).sum::<u64>() +
+			data.iter().map(|d| d.length_size as u64).sum::<u64>();
+		let max_length = groups
+			.iter()
+			.map(|g| g.max_length)
+			.chain(data.iter().map(DataLayout::encoded_max))
+			.fold(fixed, u64::saturating_add);
+
+		Ok(MessageLayout {
+			name: message.name.clone(),
+			id: message.id,
+			block_length,
+			fields,
+			groups,
+			data,
+			min_length,
+			max_length,
+		})
+	}
+
+	fn group(&self, parent: &str, group: &Group) -> std::result::Result<GroupLayout, LayoutError> {
+		let path = join(parent, &group.key());
+		let (dimension_length, count) = self.prefix(&path, &group.dimension_type, NUM_IN_GROUP)?;
+		let (block_length, fields) = self.block(&path, group.fields(), group.block_length)?;
+		let groups = group
+			.groups()
+			.map(|nested| self.group(&path, nested))
+			.collect::<std::result::Result<Vec<_>, _>>()?;
+		let data = self.data(&path, group.data())?;
+
+		let entry_max = groups
+			.iter()
+			.map(|g| g.max_length)
+			.chain(data.iter().map(DataLayout::encoded_max))
+			.fold(block_length as u64, u64::saturating_add);
+		Ok(GroupLayout {
+			name: group.name.clone(),
+			id: group.id,
+			dimension_length,
+			max_count: count.max,
+			block_length,
+			fields,
+			groups,
+			data,
+			min_length: dimension_length as u64,
+			max_length: (dimension_length as u64)
+				.saturating_add(count.max.saturating_mul(entry_max)),
+		})
+	}
+
+	/// Lay out the fields of a block, the block length is the explicit one if given.
+	fn block<'f>(
+		&self,
+		path: &str,
+		fields: impl Iterator<Item = &'f Field>,
+		explicit: Option<u32>,
+	) -> std::result::Result<(u32, Vec<FieldLayout>), LayoutError> {
+		let mut end = 0;
+		let mut layouts = Vec::new();
+		for field in fields {
+			let field_path = join(path, &field.key());
+			let size = self.type_size(&field.r#type, &field_path, &mut Vec::new())?;
+			// constant fields are not encoded
+			let size = if field.presence == Some(Presence::Constant) { 0 } else { size };
+			let offset = place(end, field.offset, &field_path)?;
+			end = offset + size;
+			layouts.push(FieldLayout { name: field.name.clone(), id: field.id, offset, size });
+		}
+		match explicit {
+			Some(block_length) if block_length < end => Err(LayoutError::BlockLength {
+				block_length,
+				required: end,
+				path: path.to_string(),
+			}),
+			Some(block_length) => Ok((block_length, layouts)),
+			None => Ok((end, layouts)),
+		}
+	}
+
+	fn data<'d>(
+		&self,
+		path: &str,
+		data: impl Iterator<Item = &'d Data>,
+	) -> std::result::Result<Vec<DataLayout>, LayoutError> {
+		data.map(|data| {
+			let (_, length) = self.prefix(&join(path, &data.key()), &data.r#type, LENGTH)?;
+			Ok(DataLayout {
+				name: data.name.clone(),
+				id: data.id,
+				length_size: length.size,
+				max_length: length.max,
+			})
+		})
+		.collect()
+	}
+
+	/// Size of the composite prefixing groups and var data, and the range of its counter member.
+	fn prefix(
+		&self,
+		path: &str,
+		type_name: &str,
+		counter: &str,
+	) -> std::result::Result<(u32, Counter), LayoutError> {
+		let composite = match self.vtable.resolve(type_name) {
+			Some(VTableObject::Composite(composite)) => composite,
+			Some(_) =>
+				return Err(LayoutError::Invalid {
+					message: format!("`{type_name}` is not a composite"),
+					path: path.to_string(),
+				}),
+			None =>
+				return Err(LayoutError::Unresolved {
+					name: type_name.to_string(),
+					path: path.to_string(),
+				}),
+		};
+		let size = self.composite_size(composite, &mut Vec::new())?;
+		let counter = composite
+			.members()
+			.into_iter()
+			.find_map(|member| match member {
+				CompositeMember::Type(t) if t.name == counter => Some(t),
+				CompositeMember::Ref(r) if r.name == counter =>
+					match self.vtable.resolve(&r.ref_type) {
+						Some(VTableObject::Type(t)) => Some(*t),
+						_ => None,
+					},
+				_ => None,
+			})
+			.and_then(Counter::of)
+			.ok_or_else(|| LayoutError::Invalid {
+				message: format!("`{type_name}` has no integer `{counter}` member"),
+				path: path.to_string(),
+			})?;
+		Ok((size, counter))
+	}
+
+	/// Encoded size of the named type.
+	fn type_size(
+		&self,
+		name: &str,
+		path: &str,
+		stack: &mut Vec<&'a str>,
+	) -> std::result::Result<u32, LayoutError> {
+		let object = self.vtable.resolve(name).ok_or_else(|| LayoutError::Unresolved {
+			name: name.to_string(),
+			path: path.to_string(),
+		})?;
+		match *object {
+			VTableObject::Primitive(primitive) => Ok(primitive.size()),
+			VTableObject::Type(t) => type_size(t, &join("types", &t.key())),
+			VTableObject::Composite(composite) => {
+				if stack.contains(&composite.name.as_str()) {
+					return Err(LayoutError::Cycle {
+						name: composite.name.clone(),
+						path: path.to_string(),
+					});
+				}
+				self.composite_size(composite, stack)
+			},
+			VTableObject::Enum(e) => {
+				let path = join("types", &e.key());
+				self.encoding_size(e.encoding_type.as_ref(), &path, stack)
+			},
+			VTableObject::Set(s) => {
+				let path = join("types", &s.key());
+				self.encoding_size(s.encoding_type.as_ref(), &path, stack)
+			},
+		}
+	}
+
+	fn encoding_size(
+		&self,
+		encoding_type: Option<&EncodingType>,
+		path: &str,
+		stack: &mut Vec<&'a str>,
+	) -> std::result::Result<u32, LayoutError> {
+		match encoding_type {
+			Some(EncodingType::Primitive(primitive)) => Ok(primitive.size()),
+			Some(EncodingType::Type(name)) => self.type_size(name, path, stack),
+			None => Err(LayoutError::Invalid {
+				message: "missing encodingType".to_string(),
+				path: path.to_string(),
+			}),
+		}
+	}
+
+	/// Encoded size of the composite, honouring explicit offsets of its members.
+	pub(crate) fn composite_size(
+		&self,
+		composite: &'a Composite,
+		stack: &mut Vec<&'a str>,
+	) -> std::result::Result<u32, LayoutError> {
+		self.nested_composite_size(composite, &join("types", &composite.key()), stack)
+	}
+
+	fn nested_composite_size(
+		&self,
+		composite: &'a Composite,
+		path: &str,
+		stack: &mut Vec<&'a str>,
+	) -> std::result::Result<u32, LayoutError> {
+		Ok(self.composite_members(composite, path, stack)?.0)
+	}
+
+	/// Encoded size and layouts of the members of the composite.
+	fn composite_members(
+		&self,
+		composite: &'a Composite,
+		path: &str,
+		stack: &mut Vec<&'a str>,
+	) -> std::result::Result<(u32, Vec<MemberLayout>), LayoutError> {
+		stack.push(&composite.name);
+		let mut end = 0;
+		let mut layouts = Vec::new();
+		for member in composite.members() {
+			let mut members = Vec::new();
+			let (name, size, offset, member_path) = match member {
+				CompositeMember::Type(t) => {
+					let member_path = join(path, &t.key());
+					(&t.name, type_size(t, &member_path)?, t.offset, member_path)
+				},
+				CompositeMember::Ref(r) => {
+					let member_path = join(path, &r.key());
+					let size = if r.presence == Some(Presence::Constant) {
+						0
+					} else {
+						self.type_size(&r.ref_type, &member_path, stack)?
+					};
+					(&r.name, size, r.offset, member_path)
+				},
+				CompositeMember::Enum(e) => {
+					let member_path = join(path, &e.key());
+					let size = self.encoding_size(e.encoding_type.as_ref(), &member_path, stack)?;
+					(&e.name, size, e.offset, member_path)
+				},
+				CompositeMember::Set(s) => {
+					let member_path = join(path, &s.key());
+					let size = self.encoding_size(s.encoding_type.as_ref(), &member_path, stack)?;
+					(&s.name, size, s.offset, member_path)
+				},
+				CompositeMember::Composite(c) => {
+					let member_path = join(path, &c.key());
+					let (size, nested) = self.composite_members(c, &member_path, stack)?;
+					members = nested;
+					(&c.name, size, c.offset, member_path)
+				},
+			};
+			let offset = place(end, offset, &member_path)?;
+			end = offset + size;
+			layouts.push(MemberLayout { name: name.clone(), offset, size, members });
+		}
+		stack.pop();
+		Ok((end, layouts))
+	}
+}
+
+impl DataLayout {
+	/// Encoded length of the data at its maximum, including the length prefix.
+	fn encoded_max(&self) -> u64 {
+		(self.length_size as u64).saturating_add(self.max_length)
+	}
+}
+
+/// Range of the member counting group entries or var data bytes.
+struct Counter {
+	size: u32,
+	max: u64,
+}
+
+impl Counter {
+	fn of(t: &Type) -> Option<Counter> {
+		let primitive = t.primitive_type?;
+		let max = t.max().unwrap_or_else(|_| primitive.max_value()).as_i128()?;
+		Some(Counter { size: primitive.size(), max: max.clamp(0, u64::MAX as i128) as u64 })
+	}
+}
+
+/// Encoded size of a `<type>`, constants take no space.
+fn type_size(t: &Type, path: &str) -> std::result::Result<u32, LayoutError> {
+	if t.presence == Some(Presence::Constant) {
+		return Ok(0);
+	}
+	match t.primitive_type {
+		Some(primitive) => Ok(primitive.size() * t.length.unwrap_or(1)),
+		None => Err(LayoutError::Invalid {
+			message: format!("type `{}` has no primitiveType", t.name),
+			path: path.to_string(),
+		}),
+	}
+}
+
+/// Offset of a member following a member ending at `end`.
+fn place(end: u32, explicit: Option<u32>, path: &str) -> std::result::Result<u32, LayoutError> {
+	match explicit {
+		Some(offset) if offset < end =>
+			Err(LayoutError::Overlap { offset, end, path: path.to_string() }),
+		Some(offset) => Ok(offset),
+		None => Ok(end),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::path::Path;
+
+	use serde_json::json;
+
+	use super::*;
+	use crate::{decode, encode, fixtures, SbeSchemaError, SchemaLoader};
+
+	fn load(file: &str) -> Schema {
+		let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/resources").join(file);
+		SchemaLoader::new().load(path).unwrap()
+	}
+
+	#[test]
+	fn test_explicit_offsets() {
+		let layout = layout(&load("composite-offsets-schema.xml")).unwrap();
+		assert_eq!(layout.header_length, 12);
+
+		let group = &layout.message(1).unwrap().groups[0];
+		assert_eq!((group.dimension_length, group.block_length, group.max_count), (8, 16, 254));
+		assert_eq!(group.max_length, 8 + 254 * 16);
+
+		let message = layout.message(2).unwrap();
+		let offsets = message.fields.iter().map(|f| (f.offset, f.size)).collect::<Vec<_>>();
+		assert_eq!(offsets, [(0, 4), (8, 16), (24, 8)]);
+		assert_eq!((message.block_length, message.min_length, message.max_length), (32, 44, 44));
+
+		let composite = layout.composite("testComposite").unwrap();
+		let offsets = composite.members.iter().map(|m| (m.offset, m.size)).collect::<Vec<_>>();
+		assert_eq!((composite.size, offsets), (16, vec![(0, 1), (8, 8)]));
+	}
+
+	#[test]
+	fn test_block_length_and_data() {
+		let layout = layout(&load("block-length-schema.xml")).unwrap();
+		let lengths = layout.messages.iter().map(|m| m.block_length).collect::<Vec<_>>();
+		assert_eq!(lengths, [8, 64, 64, 64]);
+		assert_eq!(layout.message(3).unwrap().groups[0].block_length, 16);
+
+		let message = layout.message(4).unwrap();
+		assert_eq!(message.data[0].length_size, 1);
+		assert_eq!((message.min_length, message.max_length), (8 + 64 + 1, 8 + 64 + 1 + 254));
+	}
+
+	#[test]
+	fn test_nested_groups() {
+		let layout = layout(&load("nested-group-schema.xml")).unwrap();
+		let message = layout.message(1).unwrap();
+		let x = &message.groups[0];
+		let y = &x.groups[0];
+		let z = &y.groups[0];
+		assert_eq!((z.block_length, z.max_length), (1, 2 + 254));
+		assert_eq!(y.max_length, 2 + 254 * (1 + z.max_length));
+		assert_eq!(x.max_length, 2 + 254 * (1 + y.max_length));
+		assert_eq!((message.min_length, message.max_length), (8 + 16 + 2, 8 + 16 + x.max_length));
+	}
+
+	#[test]
+	fn test_composite_elements() {
+		let layout = layout(&load("composite-elements-schema-rc4.xml")).unwrap();
+		assert_eq!(layout.message(1).unwrap().block_length, 1 + 1 + 4 + 16);
+		assert_eq!(layout.message(2).unwrap().block_length, 16 + 16);
+	}
+
+	#[test]
+	fn test_constant_fields() {
+		let layout = layout(&load("constant-enum-fields.xml")).unwrap();
+		let message = layout.message(1).unwrap();
+		assert_eq!((message.block_length, message.fields[0].size), (0, 0));
+		assert_eq!(message.groups[0].block_length, 0);
+	}
+
+	#[test]
+	fn test_counter_refs() {
+		let schema = fixtures::schema(
+			0,
+			r#"<type name="Count" primitiveType="uint16"/>
+			<type name="Length" primitiveType="uint8" maxValue="100"/>
+			<composite name="groupSizeEncoding">
+				<type name="blockLength" primitiveType="uint16"/>
+				<ref name="numInGroup" type="Count"/>
+			</composite>
+			<composite name="varDataEncoding">
+				<ref name="length" type="Length"/>
+				<type name="varData" primitiveType="uint8" length="0"/>
+			</composite>"#,
+			r#"<sbe:message name="Order" id="1">
+				<group name="legs" id="1" dimensionType="groupSizeEncoding">
+					<field name="qty" id="2" type="int32"/>
+				</group>
+				<data name="text" id="3" type="varDataEncoding"/>
+			</sbe:message>"#,
+		);
+		let layout = layout(&schema).unwrap();
+		let message = layout.message(1).unwrap();
+		assert_eq!((message.groups[0].dimension_length, message.groups[0].max_count), (4, 65534));
+		assert_eq!((message.data[0].length_size, message.data[0].max_length), (1, 100));
+
+		let order = json!({ "legs": [{ "qty": 5 }], "text": "616263" });
+		let buf = encode(&schema, "Order", &order).unwrap();
+		assert_eq!(decode(&schema, &buf).unwrap().value, order);
+	}
+
+	#[test]
+	fn test_layout_errors() {
+		let schema = load("error-handler-invalid-composite-offsets-schema.xml");
+		assert!(matches!(
+			layout(&schema),
+			Err(SbeSchemaError::Layout(LayoutError::Overlap { offset: 1, end: 5, .. }))
+		));
+		assert!(matches!(
+			layout(&load("cyclic-refs-schema.xml")),
+			Err(SbeSchemaError::Layout(LayoutError::Cycle { .. }))
+		));
+
+		let schema: Schema = r#"<messageSchema version="0"><types>
+			<composite name="messageHeader"><type name="blockLength" primitiveType="uint16"/></composite>
+			</types><message name="Order" id="1" blockLength="2">
+			<field name="a" id="1" type="uint32"/></message></messageSchema>"#
+			.parse()
+			.unwrap();
+		assert!(matches!(
+			layout(&schema),
+			Err(SbeSchemaError::Layout(LayoutError::BlockLength {
+				block_length: 2,
+				required: 4,
+				..
+			}))
+		));
+	}
+}
@@ -2,19 +2,55 @@
 //!
 //! `sbe-schema` is a library for working with SBE schema files.
 
+mod codec;
+mod diff;
 mod evolution;
+mod fingerprint;
+#[cfg(test)]
+mod fixtures;
+mod layout;
+mod loader;
 mod schema;
 mod types;
+mod validate;
+mod value;
+mod vtable;
+mod walk;
 
 use evolution::EvolutionError;
-use std::result::Result as StdResult;
+use std::{path::PathBuf, result::Result as StdResult};
 use thiserror::Error;
 
+pub use codec::{
+	annotate, decode, encode, Annotated, CodecError, Decoded, Decoder, Encoder, Span, SpanKind,
+};
+pub use diff::{diff, Difference, SchemaDiff};
 pub use evolution::{
-	CompatibilityLevel, EvolutionStrategy, FullCompatibility, NoneCompatibility, Optional,
-	PartialCompatibility, SbeSchemaValidator, SchemaValidator, Validator,
+	BackwardCompatibility, ChangeKind, CompatibilityLevel, CompatibilityReport, Element,
+	EvolutionStrategy, Finding, ForwardCompatibility, FullCompatibility, NoneCompatibility,
+	Optional, PartialCompatibility, SbeSchemaValidator, SchemaValidator, TransitiveCompatibility,
+	TransitiveReport, Validator, VersionReport,
+};
+pub use fingerprint::Fingerprint;
+pub use layout::{
+	layout, CompositeLayout, DataLayout, FieldLayout, GroupLayout, LayoutError, MemberLayout,
+	MessageLayout, SchemaLayout,
+};
+pub use loader::{normalize_path, FileSource, FileSystem, SchemaLoader};
+#[allow(deprecated)]
+pub use types::SematicVersion;
+pub use types::{
+	ByteOrder, Choice, Composite, CompositeMember, Data, EncodingType, EnumType, Field, Group,
+	Include, Message, Presence, PrimitiveType, Ref, Schema, SemanticVersion, SetType, Type, Types,
+	ValidValue,
 };
-pub use types::{build_vtable, Schema, VTable};
+pub use validate::{
+	validate, ConstantValueRule, Diagnostic, Diagnostics, EncodingValuesRule, LayoutRule,
+	MessageHeaderRule, NamesRule, Rule, RuleSet, Severity, SinceVersionRule, TypeReferencesRule,
+	UniqueIdsRule, UniqueNamesRule,
+};
+pub use value::{Value, ValueError};
+pub use vtable::{build_vtable, TypeReference, VTable, VTableError, VTableObject};
 
 /// Result type returned from methods that have [`enum@self::Error`].
 pub type Result<T> = StdResult<T, SbeSchemaError>;
@@ -25,4 +61,85 @@ pub enum SbeSchemaError {
 	/// An error occurred while validating schema evolution.
 	#[error(transparent)]
 	Evolution(#[from] EvolutionError),
+	/// A value in the schema does not match its type.
+	#[error(transparent)]
+	Value(#[from] ValueError),
+	/// The wire layout of the schema could not be computed.
+	#[error(transparent)]
+	Layout(#[from] LayoutError),
+	/// A message could not be encoded or decoded with the schema.
+	#[error(transparent)]
+	Codec(#[from] CodecError),
+	/// The schema could not be read.
+	#[error("failed to read {}: {source}", display_path(.path))]
+	Io {
+		/// Path of the schema file, if read from a file.
+		path: Option<PathBuf>,
+		/// The underlying IO error.
+		source: std::io::Error,
+	},
+	/// The schema is not a valid XML or does not match the SBE model.
+	#[error("{}:{line}:{column}: {message}", display_path(.path))]
+	Parse {
+		/// Path of the schema file, if read from a file.
+		path: Option<PathBuf>,
+		/// 1-based line of the broken element.
+		line: usize,
+		/// 1-based column of the broken element.
+		column: usize,
+		/// Description of the problem.
+		message: String,
+	},
+	/// The schema file has a comment, which formatting would lose.
+	#[error("{}:{line}:{column}: comments are not kept by formatting, remove them first", display_path(.path))]
+	Comment {
+		/// Path of the schema file, if read from a file.
+		path: Option<PathBuf>,
+		/// 1-based line of the comment.
+		line: usize,
+		/// 1-based column of the comment.
+		column: usize,
+	},
+	/// The schema file has an attribute or element the model does not know, which formatting
+	/// would lose.
+	#[error("{}:{line}:{column}: {name} is not kept by formatting, remove it first", display_path(.path))]
+	Unmodelled {
+		/// Path of the schema file, if read from a file.
+		path: Option<PathBuf>,
+		/// 1-based line of the element.
+		line: usize,
+		/// 1-based column of the element.
+		column: usize,
+		/// Description of the attribute or element, e.g. ``attribute `xsi:schemaLocation` ``.
+		name: String,
+	},
+	/// The schema could not be serialized to XML.
+	#[error("failed to serialize schema: {0}")]
+	Serialize(#[source] quick_xml::DeError),
+	/// The included file was found neither next to the including file nor in the search paths.
+	#[error("{}: included file {href} not found", .parent.display())]
+	IncludeNotFound {
+		/// The href of the include.
+		href: String,
+		/// The file containing the include.
+		parent: PathBuf,
+	},
+	/// The files include each other.
+	#[error("include cycle: {}", .chain.iter().map(|p| p.display().to_string()).collect::<Vec<_>>().join(" -> "))]
+	IncludeCycle {
+		/// The chain of included files, the last one closes the cycle.
+		chain: Vec<PathBuf>,
+	},
+	/// The included file has a root element that cannot be included.
+	#[error("{}: cannot include <{root}>, expected <types>, <message> or <messageSchema>", .path.display())]
+	UnsupportedInclude {
+		/// The included file.
+		path: PathBuf,
+		/// Name of the root element.
+		root: String,
+	},
+}
+
+fn display_path(path: &Option<PathBuf>) -> String {
+	path.as_ref().map_or_else(|| "<input>".to_string(), |p| p.display().to_string())
 }
@@ -0,0 +1,372 @@
+//! Loading of schemas split into several files with `<xi:include>`.
+
+use std::{
+	collections::HashSet,
+	fmt::Debug,
+	io,
+	path::{Component, Path, PathBuf},
+	sync::Arc,
+};
+
+use quick_xml::{events::Event, Reader};
+
+use crate::{
+	schema::parse,
+	types::{Include, Message, Types},
+	Result, SbeSchemaError, Schema,
+};
+
+/// Files the schemas are read from, the file system unless e.g. read from a revision of a
+/// version control system.
+pub trait FileSource: Debug + Send + Sync {
+	/// Read the whole file.
+	fn read(&self, path: &Path) -> io::Result<String>;
+
+	/// Whether the file exists.
+	fn is_file(&self, path: &Path) -> bool;
+
+	/// Absolute path that is the same for every way of naming the file, used to detect include
+	/// cycles.
+	fn canonicalize(&self, path: &Path) -> io::Result<PathBuf>;
+}
+
+/// Reads schemas from the file system.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FileSystem;
+
+impl FileSource for FileSystem {
+	fn read(&self, path: &Path) -> io::Result<String> {
+		std::fs::read_to_string(path)
+	}
+
+	fn is_file(&self, path: &Path) -> bool {
+		path.is_file()
+	}
+
+	fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+		path.canonicalize()
+	}
+}
+
+/// Absolute path with `.` and `..` resolved without touching the file system, for sources whose
+/// files do not exist on disk.
+pub fn normalize_path(path: &Path) -> io::Result<PathBuf> {
+	let mut normalized = if path.is_absolute() { PathBuf::new() } else { std::env::current_dir()? };
+	for component in path.components() {
+		match component {
+			Component::CurDir => {},
+			Component::ParentDir => {
+				normalized.pop();
+			},
+			component => normalized.push(component),
+		}
+	}
+	Ok(normalized)
+}
+
+/// Loads a schema file and resolves all its includes.
+///
+/// Includes are resolved relative to the including file first and then in the search paths, in
+/// the order they were added. Included `<types>` and `<message>` elements are merged into the
+/// loaded schema, so the result no longer contains any includes.
+#[derive(Debug, Clone)]
+pub struct SchemaLoader {
+	search_paths: Vec<PathBuf>,
+	source: Arc<dyn FileSource>,
+}
+
+impl Default for SchemaLoader {
+	fn default() -> Self {
+		Self { search_paths: Vec::new(), source: Arc::new(FileSystem) }
+	}
+}
+
+impl SchemaLoader {
+	/// Create a loader without extra search paths.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Add a directory searched for included files.
+	pub fn search_path(mut self, path: impl Into<PathBuf>) -> Self {
+		self.search_paths.push(path.into());
+		self
+	}
+
+	/// Read the schema and the included files from the source instead of the file system.
+	pub fn source(mut self, source: impl FileSource + 'static) -> Self {
+		self.source = Arc::new(source);
+		self
+	}
+
+	/// Load the schema file with all its includes resolved.
+	pub fn load(&self, path: impl AsRef<Path>) -> Result<Schema> {
+		let path = path.as_ref();
+		let mut schema: Schema = parse(&self.read(path)?, Some(path))?;
+		let mut stack = vec![self.canonical(path)?];
+		let mut visited = stack.iter().cloned().collect();
+		let includes = take_includes(&mut schema);
+		self.resolve(path, includes, &mut schema, &mut stack, &mut visited)?;
+		Ok(schema)
+	}
+
+	fn read(&self, path: &Path) -> Result<String> {
+		self.source.read(path).map_err(|source| io_error(path, source))
+	}
+
+	fn canonical(&self, path: &Path) -> Result<PathBuf> {
+		self.source.canonicalize(path).map_err(|source| io_error(path, source))
+	}
+
+	fn resolve(
+		&self,
+		parent: &Path,
+		includes: Vec<Include>,
+		schema: &mut Schema,
+		stack: &mut Vec<PathBuf>,
+		visited: &mut HashSet<PathBuf>,
+	) -> Result<()> {
+		for include in includes {
+			let path = self.find(parent, &include.href)?;
+			let canonical = self.canonical(&path)?;
+			if stack.contains(&canonical) {
+				let mut chain = stack.clone();
+				chain.push(canonical);
+				return Err(SbeSchemaError::IncludeCycle { chain });
+			}
+			// a file included on several paths, e.g. by two included files, is merged once
+			if !visited.insert(canonical.clone()) {
+				continue;
+			}
+			stack.push(canonical);
+
+			let xml = self.read(&path)?;
+			match root(&xml).as_deref() {
+				Some("types") => {
+					let mut types: Types = parse(&xml, Some(&path))?;
+					let nested = types.include.take().unwrap_or_default();
+					schema.types.get_or_insert_with(Vec::new).push(types);
+					self.resolve(&path, nested, schema, stack, visited)?;
+				},
+				Some("message") => {
+					let message: Message = parse(&xml, Some(&path))?;
+					schema.messages.get_or_insert_with(Vec::new).push(message);
+				},
+				Some("messageSchema") => {
+					let mut included: Schema = parse(&xml, Some(&path))?;
+					let nested = take_includes(&mut included);
+					schema
+						.types
+						.get_or_insert_with(Vec::new)
+						.extend(included.types.unwrap_or_default());
+					schema
+						.messages
+						.get_or_insert_with(Vec::new)
+						.extend(included.messages.unwrap_or_default());
+					self.resolve(&path, nested, schema, stack, visited)?;
+				},
+				root =>
+					return Err(SbeSchemaError::UnsupportedInclude {
+						path,
+						root: root.unwrap_or_default().to_string(),
+					}),
+			}
+
+			stack.pop();
+		}
+		Ok(())
+	}
+
+	/// Find the included file relative to the including file or in the search paths.
+	fn find(&self, parent: &Path, href: &str) -> Result<PathBuf> {
+		let relative = parent.parent().unwrap_or_else(|| Path::new("")).join(href);
+		std::iter::once(relative)
+			.chain(self.search_paths.iter().map(|dir| dir.join(href)))
+			.find(|candidate| self.source.is_file(candidate))
+			.ok_or_else(|| SbeSchemaError::IncludeNotFound {
+				href: href.to_string(),
+				parent: parent.to_path_buf(),
+			})
+	}
+}
+
+/// Take the includes of the schema and of its `<types>` blocks out of the schema.
+fn take_includes(schema: &mut Schema) -> Vec<Include> {
+	let mut includes = schema.include.take().unwrap_or_default();
+	for types in schema.types.iter_mut().flatten() {
+		includes.extend(types.include.take().unwrap_or_default());
+	}
+	includes
+}
+
+pub(crate) fn read(path: &Path) -> Result<String> {
+	FileSystem.read(path).map_err(|source| io_error(path, source))
+}
+
+fn io_error(path: &Path, source: io::Error) -> SbeSchemaError {
+	SbeSchemaError::Io { path: Some(path.to_path_buf()), source }
+}
+
+/// Local name of the root element of the document.
+pub(crate) fn root(xml: &str) -> Option<String> {
+	let mut reader = Reader::from_str(xml);
+	loop {
+		match reader.read_event().ok()? {
+			Event::Start(e) | Event::Empty(e) =>
+				return Some(String::from_utf8_lossy(e.local_name().as_ref()).into_owned()),
+			Event::Eof => return None,
+			_ => {},
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+
+	use super::*;
+
+	fn resources() -> PathBuf {
+		Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/resources")
+	}
+
+	/// A directory unique to the test and the process, removed when the test ends.
+	struct TempDir(PathBuf);
+
+	impl TempDir {
+		fn new(name: &str) -> Self {
+			let dir = std::env::temp_dir()
+				.join(format!("sbe-schema-loader-{name}-{}", std::process::id()));
+			fs::create_dir_all(&dir).unwrap();
+			Self(dir)
+		}
+	}
+
+	impl Drop for TempDir {
+		fn drop(&mut self) {
+			let _ = fs::remove_dir_all(&self.0);
+		}
+	}
+
+	#[test]
+	fn test_load_with_includes() {
+		let schema = SchemaLoader::new().load(resources().join("sub/basic-schema.xml")).unwrap();
+		assert!(schema.include.is_none());
+		assert!(schema.message_header().is_some());
+		assert_eq!(schema.messages.as_ref().map(Vec::len), Some(1));
+	}
+
+	#[test]
+	fn test_search_path() {
+		let dir = TempDir::new("search");
+		let dir = &dir.0;
+		let xml = fs::read_to_string(resources().join("sub/basic-schema.xml"))
+			.unwrap()
+			.replace("sub2/common.xml", "common.xml");
+		fs::write(dir.join("basic-schema.xml"), xml).unwrap();
+
+		let path = dir.join("basic-schema.xml");
+		assert!(matches!(
+			SchemaLoader::new().load(&path),
+			Err(SbeSchemaError::IncludeNotFound { ref href, .. }) if href == "common.xml"
+		));
+		let schema = SchemaLoader::new().search_path(resources().join("sub/sub2")).load(&path);
+		assert!(schema.unwrap().message_header().is_some());
+	}
+
+	#[test]
+	fn test_include_cycle() {
+		let dir = TempDir::new("cycle");
+		let dir = &dir.0;
+		fs::write(
+			dir.join("a.xml"),
+			r#"<messageSchema version="0"><xi:include href="b.xml"/></messageSchema>"#,
+		)
+		.unwrap();
+		fs::write(dir.join("b.xml"), r#"<types><xi:include href="a.xml"/></types>"#).unwrap();
+
+		match SchemaLoader::new().load(dir.join("a.xml")) {
+			Err(SbeSchemaError::IncludeCycle { chain }) => assert_eq!(chain.len(), 3),
+			other => panic!("unexpected result: {other:?}"),
+		}
+	}
+
+	/// Files kept in memory, as if read from an older revision.
+	#[derive(Debug)]
+	struct Memory(Vec<(PathBuf, String)>);
+
+	impl FileSource for Memory {
+		fn read(&self, path: &Path) -> io::Result<String> {
+			let path = normalize_path(path)?;
+			self.0
+				.iter()
+				.find_map(|(p, xml)| (*p == path).then(|| xml.clone()))
+				.ok_or_else(|| io::ErrorKind::NotFound.into())
+		}
+
+		fn is_file(&self, path: &Path) -> bool {
+			normalize_path(path).is_ok_and(|path| self.0.iter().any(|(p, _)| *p == path))
+		}
+
+		fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+			normalize_path(path)
+		}
+	}
+
+	#[test]
+	fn test_file_source() {
+		let dir = PathBuf::from("/schemas");
+		let source = Memory(vec![
+			(
+				dir.join("a.xml"),
+				r#"<messageSchema version="1"><xi:include href="common/../types.xml"/></messageSchema>"#
+					.to_string(),
+			),
+			(dir.join("types.xml"), r#"<types><type name="Id" primitiveType="uint64"/></types>"#.to_string()),
+		]);
+		let schema = SchemaLoader::new().source(source).load(dir.join("./a.xml")).unwrap();
+		assert_eq!(schema.version, 1);
+		assert_eq!(schema.types().map(|t| t.name.as_str()).collect::<Vec<_>>(), ["Id"]);
+	}
+
+	#[test]
+	fn test_include_in_types() {
+		let dir = PathBuf::from("/schemas");
+		let source = Memory(vec![
+			(
+				dir.join("a.xml"),
+				r#"<messageSchema version="1"><types><xi:include href="b.xml"/></types><xi:include href="sub/c.xml"/></messageSchema>"#
+					.to_string(),
+			),
+			(dir.join("b.xml"), r#"<types><type name="Id" primitiveType="uint64"/></types>"#.to_string()),
+			(
+				dir.join("sub/c.xml"),
+				r#"<messageSchema version="1"><types><xi:include href="d.xml"/></types></messageSchema>"#
+					.to_string(),
+			),
+			(dir.join("sub/d.xml"), r#"<types><type name="Qty" primitiveType="int32"/></types>"#.to_string()),
+		]);
+		let schema = SchemaLoader::new().source(source).load(dir.join("a.xml")).unwrap();
+		let mut names = schema.types().map(|t| t.name.as_str()).collect::<Vec<_>>();
+		names.sort();
+		assert_eq!(names, ["Id", "Qty"]);
+		assert!(schema.types.iter().flatten().all(|types| types.include.is_none()));
+	}
+
+	#[test]
+	fn test_diamond_include() {
+		let dir = PathBuf::from("/schemas");
+		let source = Memory(vec![
+			(
+				dir.join("a.xml"),
+				r#"<messageSchema version="1"><xi:include href="b.xml"/><xi:include href="c.xml"/></messageSchema>"#
+					.to_string(),
+			),
+			(dir.join("b.xml"), r#"<types><xi:include href="d.xml"/></types>"#.to_string()),
+			(dir.join("c.xml"), r#"<types><xi:include href="./d.xml"/></types>"#.to_string()),
+			(dir.join("d.xml"), r#"<types><type name="Id" primitiveType="uint64"/></types>"#.to_string()),
+		]);
+		let schema = SchemaLoader::new().source(source).load(dir.join("a.xml")).unwrap();
+		assert_eq!(schema.types().map(|t| t.name.as_str()).collect::<Vec<_>>(), ["Id"]);
+	}
+}
@@ -1,14 +1,378 @@
-use std::path::PathBuf;
+use std::{
+	collections::HashSet,
+	io::{BufRead, Read},
+	path::{Path, PathBuf},
+	str::FromStr,
+};
 
-use crate::{types::Schema, SbeSchemaError};
-use quick_xml::de::from_str;
+use crate::{
+	loader::{read, root},
+	types::Schema,
+	SbeSchemaError, SchemaLoader,
+};
+use quick_xml::{de::Deserializer, events::Event, se::Serializer, DeError, Reader};
+use serde::{de::DeserializeOwned, Serialize};
 
+const XML_DECLARATION: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#;
+const SBE_NAMESPACE: &str = "http://fixprotocol.io/2016/sbe";
+const XINCLUDE_NAMESPACE: &str = "http://www.w3.org/2001/XInclude";
+const ROOT: &str = "sbe:messageSchema";
+const INDENT: usize = 4;
+
+impl Schema {
+	/// Parse the schema file without resolving its includes.
+	pub fn from_file(path: impl AsRef<Path>) -> Result<Self, SbeSchemaError> {
+		let path = path.as_ref();
+		let xml = read(path)?;
+		match root(&xml).as_deref() {
+			Some("messageSchema") => parse(&xml, Some(path)),
+			other => Err(SbeSchemaError::Parse {
+				path: Some(path.to_path_buf()),
+				line: 1,
+				column: 1,
+				message: format!(
+					"expected <messageSchema> root element, found <{}>",
+					other.unwrap_or_default()
+				),
+			}),
+		}
+	}
+
+	/// Serialize the schema to XML in the canonical style: attributes in a fixed order, elements
+	/// grouped by kind and indented by four spaces. Comments are not part of the model and are
+	/// therefore not written.
+	pub fn to_xml(&self) -> Result<String, SbeSchemaError> {
+		let mut body = String::new();
+		let mut serializer =
+			Serializer::with_root(&mut body, Some(ROOT)).map_err(SbeSchemaError::Serialize)?;
+		serializer.indent(' ', INDENT);
+		self.serialize(serializer).map_err(SbeSchemaError::Serialize)?;
+
+		// namespaces are not part of the model, declare them on the root element
+		let mut namespaces = format!(r#" xmlns:sbe="{SBE_NAMESPACE}""#);
+		let includes = self.include.is_some() ||
+			self.types.iter().flatten().any(|types| types.include.is_some());
+		if includes {
+			namespaces.push_str(&format!(r#" xmlns:xi="{XINCLUDE_NAMESPACE}""#));
+		}
+		let body = body.replacen(ROOT, &format!("{ROOT}{namespaces}"), 1);
+		Ok(format!("{XML_DECLARATION}\n{}\n", inline_text(&body)))
+	}
+
+	/// Rewrite the XML of a schema file in the canonical style of [`Schema::to_xml`]. Comments,
+	/// attributes and elements are refused instead of losing them if they are not part of the
+	/// model.
+	pub fn format_xml(xml: &str, path: Option<&Path>) -> Result<String, SbeSchemaError> {
+		if let Some(offset) = first_comment(xml) {
+			let (line, column) = location(xml, offset + 1, false);
+			return Err(SbeSchemaError::Comment { path: path.map(Path::to_path_buf), line, column });
+		}
+		let formatted = parse::<Schema>(xml, path)?.to_xml()?;
+		if let Some((offset, name)) = first_unmodelled(xml, &formatted) {
+			let (line, column) = location(xml, offset + 1, false);
+			return Err(SbeSchemaError::Unmodelled {
+				path: path.map(Path::to_path_buf),
+				line,
+				column,
+				name,
+			});
+		}
+		Ok(formatted)
+	}
+
+	/// Parse the schema from the reader, e.g. an open file or stdin.
+	///
+	/// Includes are not resolved, use [`SchemaLoader`] to load a schema split into several files.
+	pub fn from_reader<R: Read>(mut reader: R) -> Result<Self, SbeSchemaError> {
+		let mut xml = String::new();
+		reader
+			.read_to_string(&mut xml)
+			.map_err(|source| SbeSchemaError::Io { path: None, source })?;
+		xml.parse()
+	}
+}
+
+impl FromStr for Schema {
+	type Err = SbeSchemaError;
+
+	fn from_str(xml: &str) -> Result<Self, Self::Err> {
+		parse(xml, None)
+	}
+}
+
+/// Load the schema file with all its includes resolved, see [`SchemaLoader`].
 impl TryFrom<PathBuf> for Schema {
 	type Error = SbeSchemaError;
 
 	fn try_from(path: PathBuf) -> Result<Self, Self::Error> {
-		let xml = std::fs::read_to_string(path).expect("Failed to read file");
-		let schema: Schema = from_str(&xml).expect("Failed to parse XML");
-		Ok(schema)
+		SchemaLoader::new().load(path)
+	}
+}
+
+/// Put the text of elements without children on the line of the element, the serializer indents
+/// it like a child element, e.g. `<validValue name="A">1</validValue>`.
+fn inline_text(xml: &str) -> String {
+	let lines = xml.lines().collect::<Vec<_>>();
+	let mut inlined = Vec::with_capacity(lines.len());
+	let mut i = 0;
+	while i < lines.len() {
+		let text_only = lines.get(i..i + 3).is_some_and(|window| {
+			window[0].ends_with('>') &&
+				!window[0].ends_with("/>") &&
+				!window[1].contains('<') &&
+				window[2].trim_start().starts_with("</")
+		});
+		if text_only {
+			inlined.push(format!("{}{}{}", lines[i], lines[i + 1].trim(), lines[i + 2].trim()));
+			i += 3;
+		} else {
+			inlined.push(lines[i].to_string());
+			i += 1;
+		}
+	}
+	inlined.join("\n")
+}
+
+/// Byte offset of the first comment of the XML.
+fn first_comment(xml: &str) -> Option<usize> {
+	let mut reader = Reader::from_str(xml);
+	loop {
+		let start = reader.buffer_position() as usize;
+		match reader.read_event().ok()? {
+			Event::Comment(_) => return Some(start),
+			Event::Eof => return None,
+			_ => {},
+		}
+	}
+}
+
+/// Byte offset and description of the first element or attribute of the source that is missing
+/// from the formatted XML. Elements are identified by the local names of their ancestors, as
+/// formatting regroups them.
+fn first_unmodelled(source: &str, formatted: &str) -> Option<(usize, String)> {
+	let kept = nodes(formatted).into_iter().map(|(_, node, _)| node).collect::<HashSet<_>>();
+	nodes(source)
+		.into_iter()
+		.find(|(_, node, _)| !kept.contains(node))
+		.map(|(offset, _, name)| (offset, name))
+}
+
+/// Byte offset, path and description of every element and attribute of the XML. Declarations
+/// of the namespaces [`Schema::to_xml`] declares itself are left out.
+fn nodes(xml: &str) -> Vec<(usize, String, String)> {
+	let mut reader = Reader::from_str(xml);
+	let mut ancestors: Vec<String> = Vec::new();
+	let mut nodes = Vec::new();
+	loop {
+		let offset = reader.buffer_position() as usize;
+		let (element, empty) = match reader.read_event() {
+			Ok(Event::Start(e)) => (e, false),
+			Ok(Event::Empty(e)) => (e, true),
+			Ok(Event::End(_)) => {
+				ancestors.pop();
+				continue;
+			},
+			Ok(Event::Eof) | Err(_) => return nodes,
+			_ => continue,
+		};
+		let name = String::from_utf8_lossy(element.local_name().as_ref()).into_owned();
+		ancestors.push(name.clone());
+		let path = ancestors.join("/");
+		nodes.push((offset, path.clone(), format!("element <{name}>")));
+		for attribute in element.attributes().flatten() {
+			let key = String::from_utf8_lossy(attribute.key.as_ref()).into_owned();
+			if key == "xmlns:sbe" || key == "xmlns:xi" {
+				continue;
+			}
+			nodes.push((offset, format!("{path}@{key}"), format!("attribute `{key}`")));
+		}
+		if empty {
+			ancestors.pop();
+		}
+	}
+}
+
+/// Deserialize the XML, locating the broken element on error.
+pub(crate) fn parse<T: DeserializeOwned>(
+	xml: &str,
+	path: Option<&Path>,
+) -> Result<T, SbeSchemaError> {
+	let mut reader = CountingReader { inner: xml.as_bytes(), consumed: 0 };
+	let mut deserializer = Deserializer::from_reader(&mut reader);
+	T::deserialize(&mut deserializer).map_err(|e| {
+		let (line, column) = location(xml, reader.consumed, !matches!(e, DeError::InvalidXml(_)));
+		SbeSchemaError::Parse {
+			path: path.map(Path::to_path_buf),
+			line,
+			column,
+			message: e.to_string(),
+		}
+	})
+}
+
+/// Keeps track of how many bytes the deserializer consumed, so an error can be located.
+struct CountingReader<'a> {
+	inner: &'a [u8],
+	consumed: usize,
+}
+
+impl Read for CountingReader<'_> {
+	fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+		let read = self.inner.read(buf)?;
+		self.consumed += read;
+		Ok(read)
+	}
+}
+
+impl BufRead for CountingReader<'_> {
+	fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+		Ok(self.inner)
+	}
+
+	fn consume(&mut self, amount: usize) {
+		self.inner.consume(amount);
+		self.consumed += amount;
+	}
+}
+
+/// 1-based line and column of the last tag read before the given byte offset.
+///
+/// The deserializer looks one event ahead, so for errors in the data closing tags are skipped to
+/// land on the element that was being deserialized.
+fn location(xml: &str, consumed: usize, skip_end_tags: bool) -> (usize, usize) {
+	let mut offset = consumed.min(xml.len());
+	while !xml.is_char_boundary(offset) {
+		offset -= 1;
+	}
+	let mut offset = xml[..offset].rfind('<').unwrap_or(offset);
+	while skip_end_tags && xml[offset..].starts_with("</") {
+		match xml[..offset].rfind('<') {
+			Some(start) => offset = start,
+			None => break,
+		}
+	}
+	let before = &xml[..offset];
+	let line = before.matches('\n').count() + 1;
+	let column = before[before.rfind('\n').map_or(0, |i| i + 1)..].chars().count() + 1;
+	(line, column)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Resources that are not valid schemas, e.g. with a semanticVersion that is not semver.
+	const UNPARSABLE: &[&str] = &[
+		"FixBinary.xml",
+		"error-handler-invalid-composite.xml",
+		"ilinkbinary.xml",
+		"issue835.xml",
+		"npe-small-header.xml",
+	];
+
+	#[test]
+	fn test_to_xml_round_trip() {
+		let resources = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/resources");
+		let mut files = std::fs::read_dir(resources)
+			.unwrap()
+			.map(|entry| entry.unwrap().path())
+			.filter(|path| path.extension().is_some_and(|ext| ext == "xml"))
+			.collect::<Vec<_>>();
+		files.sort();
+		for file in files {
+			let name = file.file_name().unwrap().to_string_lossy();
+			let schema = Schema::from_file(&file);
+			if UNPARSABLE.contains(&name.as_ref()) {
+				assert!(matches!(schema, Err(SbeSchemaError::Parse { .. })), "{name}");
+				continue;
+			}
+			let schema = schema.unwrap_or_else(|e| panic!("{e}"));
+			let xml = schema.to_xml().unwrap();
+			let parsed: Schema = xml.parse().unwrap_or_else(|e| panic!("{name}: {e}\n{xml}"));
+			// compare every attribute, equality ignores e.g. descriptions
+			assert_eq!(
+				serde_json::to_value(&parsed).unwrap(),
+				serde_json::to_value(&schema).unwrap(),
+				"{name}"
+			);
+			assert_eq!(parsed.to_xml().unwrap(), xml, "{name}");
+		}
+	}
+
+	#[test]
+	fn test_format_xml_refuses_comments() {
+		let xml = format!("{HEADER}\n<!-- orders -->\n</sbe:messageSchema>");
+		let error = Schema::format_xml(&xml, None).unwrap_err();
+		assert!(matches!(error, SbeSchemaError::Comment { line: 7, column: 1, .. }), "{error:?}");
+
+		let formatted = Schema::format_xml(&xml.replace("<!-- orders -->", ""), None).unwrap();
+		assert_eq!(Schema::format_xml(&formatted, None).unwrap(), formatted);
+	}
+
+	#[test]
+	fn test_format_xml_refuses_unmodelled() {
+		let xml = HEADER.replace(
+			r#"version="0">"#,
+			r#"version="0" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:schemaLocation="sbe.xsd">"#,
+		) + "</sbe:messageSchema>";
+		let error = Schema::format_xml(&xml, None).unwrap_err();
+		assert!(
+			matches!(error, SbeSchemaError::Unmodelled { line: 2, column: 1, ref name, .. } if name == "attribute `xmlns:xsi`"),
+			"{error:?}"
+		);
+
+		let xml = format!("{HEADER}<extra/>\n</sbe:messageSchema>");
+		let error = Schema::format_xml(&xml, None).unwrap_err();
+		assert!(
+			matches!(error, SbeSchemaError::Unmodelled { line: 6, column: 1, ref name, .. } if name == "element <extra>"),
+			"{error:?}"
+		);
+	}
+
+	const HEADER: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<sbe:messageSchema xmlns:sbe="http://fixprotocol.io/2016/sbe" id="1" version="0">
+<types>
+	<type name="Price" primitiveType="int64"/>
+</types>
+"#;
+
+	#[test]
+	fn test_from_str() {
+		let xml = format!(
+			"{HEADER}<sbe:message name=\"Order\" id=\"1\"><field name=\"px\" id=\"1\" \
+			 type=\"Price\"/></sbe:message></sbe:messageSchema>"
+		);
+		let schema: Schema = xml.parse().unwrap();
+		assert_eq!(schema.messages.as_ref().map(Vec::len), Some(1));
+		assert_eq!(Schema::from_reader(xml.as_bytes()).unwrap(), schema);
+	}
+
+	#[test]
+	fn test_parse_error_location() {
+		let xml = format!(
+			"{HEADER}<sbe:message name=\"Order\" id=\"1\">\n\t<field name=\"px\" type=\"Price\"/>\n\
+			 </sbe:message>\n</sbe:messageSchema>"
+		);
+		match xml.parse::<Schema>() {
+			Err(SbeSchemaError::Parse { path, line, column, message }) => {
+				assert_eq!((path, line, column), (None, 7, 2));
+				assert!(message.contains("id"), "{message}");
+			},
+			other => panic!("unexpected result: {other:?}"),
+		}
+	}
+
+	#[test]
+	fn test_invalid_xml_location() {
+		let xml = format!("{HEADER}<sbe:message name=\"Order\" id=\"1\">\n</oops>");
+		let error = xml.parse::<Schema>().unwrap_err();
+		assert!(matches!(error, SbeSchemaError::Parse { line: 7, .. }), "{error:?}");
+	}
+
+	#[test]
+	fn test_missing_file() {
+		let error = Schema::try_from(PathBuf::from("missing.xml")).unwrap_err();
+		assert!(matches!(error, SbeSchemaError::Io { path: Some(_), .. }), "{error:?}");
+		assert!(error.to_string().contains("missing.xml"));
 	}
 }
@@ -1,7 +1,7 @@
 //! Types that represent the SBE schema.
-use std::{collections::HashMap, hash::Hash};
+use std::hash::Hash;
 
-use serde::{Deserialize, Serialize};
+use serde::{ser::SerializeStruct, Deserialize, Serialize, Serializer};
 use serde_with::skip_serializing_none;
 
 /// Structure that represent top level SBE schema.
@@ -15,12 +15,12 @@ pub struct Schema {
 	/// The id of the schema.
 	#[serde(rename = "@id")]
 	pub id: Option<i32>,
-	/// The version of the schema.
-	#[serde(rename = "@version")]
+	/// The version of the schema, 0 if not set.
+	#[serde(rename = "@version", default)]
 	pub version: u32,
 	/// The semantic version of the schema.
 	#[serde(rename = "@semanticVersion")]
-	pub semantic_version: Option<SematicVersion>,
+	pub semantic_version: Option<SemanticVersion>,
 	/// The description of the schema.
 	#[serde(rename = "@description")]
 	pub description: Option<String>,
@@ -31,18 +31,18 @@ pub struct Schema {
 	#[serde(rename = "@byteOrder")]
 	pub byte_order: Option<ByteOrder>,
 	/// The include section of the schema.
-	#[serde(rename = "include")]
+	#[serde(rename(serialize = "xi:include", deserialize = "include"))]
 	pub include: Option<Vec<Include>>,
 	/// The types of the schema.
 	#[serde(rename = "types")]
 	pub types: Option<Vec<Types>>,
 	/// The messages of the schema.
-	#[serde(rename = "message")]
+	#[serde(rename(serialize = "sbe:message", deserialize = "message"))]
 	pub messages: Option<Vec<Message>>,
 	/// header type name
 	/// default to "messageHeader" but we need to know if it was set or not
 	/// therefore use of Option
-	#[serde(rename = "headerType")]
+	#[serde(rename = "@headerType")]
 	pub header_type: Option<String>,
 }
 
@@ -88,94 +88,115 @@ impl Hash for Schema {
 }
 
 const DEFAULT_HEADER_TYPE: &str = "messageHeader";
+const DEFAULT_DIMENSION_TYPE: &str = "groupSizeEncoding";
 
 impl Schema {
+	/// Name of the message header composite, `messageHeader` unless set by `headerType`.
+	pub fn header_type_name(&self) -> &str {
+		self.header_type.as_deref().unwrap_or(DEFAULT_HEADER_TYPE)
+	}
+
 	/// Get the message header composite type.
 	pub fn message_header(&self) -> Option<&Composite> {
-		self.types.as_ref().and_then(|types| {
-			types.iter().find_map(|t| {
-				t.composites
-					.as_ref()
-					.iter()
-					.find_map(|c| c.iter().find(|c| c.name == DEFAULT_HEADER_TYPE))
-			})
-		})
+		let name = self.header_type_name();
+		self.composites().find(|c| c.name == name)
 	}
-}
 
-/// build vtable for lookups
-/// type name -> composite/enum/set/type
-pub fn build_vtable(schema: &Schema) -> VTable {
-	let mut vtable = VTable::new();
-	if let Some(types) = schema.types.as_ref() {
-		for t in types {
-			if let Some(composites) = t.composites.as_ref() {
-				for c in composites {
-					vtable.add(c.name.clone(), VTableObject::Composite(c));
-				}
-			}
-		}
+	/// Byte order of the encoded values, little endian unless set.
+	pub fn byte_order(&self) -> ByteOrder {
+		self.byte_order.unwrap_or_default()
 	}
-	vtable
-}
 
-#[derive(Debug, PartialEq)]
-pub enum VTableObject<'a> {
-	Composite(&'a Composite),
-	Enum(&'a EnumType),
-	Set(&'a SetType),
-	Type(&'a Type),
-	Message(&'a Message),
-}
+	/// Files included by the schema, not including those of the `<types>` blocks.
+	pub fn includes(&self) -> impl Iterator<Item = &Include> {
+		self.include.iter().flatten()
+	}
 
-/// VTable for lookups
-#[derive(Debug, Default, PartialEq)]
-pub struct VTable<'a> {
-	objects: HashMap<String, VTableObject<'a>>,
-}
+	/// Messages in the order of the schema.
+	pub fn messages(&self) -> impl Iterator<Item = &Message> {
+		self.messages.iter().flatten()
+	}
 
-impl<'a> VTable<'a> {
-	/// Create a new VTable
-	pub fn new() -> Self {
-		Self { objects: HashMap::new() }
+	/// The message with the given template id.
+	pub fn message(&self, id: u32) -> Option<&Message> {
+		self.messages().find(|m| m.id == id)
 	}
-	/// Add object to the vtable
-	pub fn add(&mut self, name: String, obj: VTableObject<'a>) {
-		self.objects.insert(name, obj);
+
+	/// Standalone `<type>`s of all `<types>` blocks.
+	pub fn types(&self) -> impl Iterator<Item = &Type> {
+		self.types.iter().flatten().flat_map(|t| t.types.iter().flatten())
+	}
+
+	/// Composites of all `<types>` blocks, including the message header.
+	pub fn composites(&self) -> impl Iterator<Item = &Composite> {
+		self.types.iter().flatten().flat_map(|t| t.composites.iter().flatten())
+	}
+
+	/// Enums of all `<types>` blocks.
+	pub fn enums(&self) -> impl Iterator<Item = &EnumType> {
+		self.types.iter().flatten().flat_map(|t| t.enums.iter().flatten())
+	}
+
+	/// Sets of all `<types>` blocks.
+	pub fn sets(&self) -> impl Iterator<Item = &SetType> {
+		self.types.iter().flatten().flat_map(|t| t.sets.iter().flatten())
 	}
 }
 
+/// `<xi:include>` of another schema file.
 #[derive(Debug, PartialEq, Default, Deserialize, Serialize)]
 #[serde(rename = "include")]
 pub struct Include {
+	/// Location of the included file, relative to the including file.
 	#[serde(rename = "@href")]
 	pub href: String,
 }
 
+/// `<message>` with its root block fields, repeating groups and variable length data.
 #[skip_serializing_none]
 #[derive(Debug, Default, Deserialize, Serialize)]
 pub struct Message {
+	/// Name of the message.
 	#[serde(rename = "@name")]
 	pub name: String,
+	/// Description of the message for documentation.
 	#[serde(rename = "@description")]
-	pub desciption: Option<String>,
+	pub description: Option<String>,
+	/// Template id, unique within the schema.
 	#[serde(rename = "@id")]
 	pub id: u32,
+	/// Explicit length of the root block, may be larger than the fields need.
+	#[serde(rename = "@blockLength")]
+	pub block_length: Option<u32>,
+	/// Fields of the block, in the order they are encoded.
 	#[serde(rename = "field")]
 	pub fields: Option<Vec<Field>>,
+	/// Repeating groups, encoded after the fields.
 	#[serde(rename = "group")]
 	pub groups: Option<Vec<Group>>,
-	#[serde(rename = "semanticType")]
+	/// Variable length data, encoded after the groups.
+	#[serde(rename = "data")]
+	pub data: Option<Vec<Data>>,
+	/// Semantic type of the message, e.g. a FIX `MsgType` value like `D`.
+	#[serde(rename = "@semanticType")]
 	pub semantic_type: Option<String>,
+	/// Schema version the message was added in, 0 if not set.
+	#[serde(rename = "@sinceVersion")]
+	pub since_version: Option<u32>,
+	/// Schema version the message is deprecated since.
+	#[serde(rename = "@deprecated")]
+	pub deprecated: Option<u32>,
 }
 
 impl PartialEq for Message {
 	fn eq(&self, other: &Self) -> bool {
 		self.name == other.name &&
 			self.id == other.id &&
+			self.block_length == other.block_length &&
 			self.fields == other.fields &&
 			self.groups == other.groups &&
-			self.semantic_type == other.semantic_type
+			self.data == other.data &&
+			self.since_version == other.since_version
 	}
 }
 
@@ -186,33 +207,83 @@ impl Hash for Message {
 		// self.name.hash(state); // name might change, protocl will not break since id must remain
 		// the same
 		self.id.hash(state);
+		if self.block_length.is_some() {
+			self.block_length.hash(state);
+		}
 		if let Some(fields) = self.fields.as_ref() {
 			fields.iter().for_each(|f| f.hash(state));
 		};
 		if let Some(groups) = self.groups.as_ref() {
 			groups.iter().for_each(|f| f.hash(state));
 		};
-		self.semantic_type.hash(state);
+		if let Some(data) = self.data.as_ref() {
+			data.iter().for_each(|f| f.hash(state));
+		};
+		// semantic type and deprecation do not change the wire format
+		if self.since_version.is_some() {
+			self.since_version.hash(state);
+		}
 	}
 }
 
+impl Message {
+	/// Fields in the order they are encoded.
+	pub fn fields(&self) -> impl Iterator<Item = &Field> {
+		self.fields.iter().flatten()
+	}
+
+	/// Repeating groups in the order they are encoded.
+	pub fn groups(&self) -> impl Iterator<Item = &Group> {
+		self.groups.iter().flatten()
+	}
+
+	/// Variable length data in the order they are encoded.
+	pub fn data(&self) -> impl Iterator<Item = &Data> {
+		self.data.iter().flatten()
+	}
+}
+
+/// `<group>` of entries repeated a number of times given by its dimension.
 #[skip_serializing_none]
 #[derive(Debug, Default, Deserialize, Serialize)]
 pub struct Group {
+	/// Name of the group.
 	#[serde(rename = "@name")]
 	pub name: String,
+	/// Description of the group for documentation.
 	#[serde(rename = "@description")]
-	pub desciption: Option<String>,
+	pub description: Option<String>,
+	/// Id of the group, unique within the message.
 	#[serde(rename = "@id")]
 	pub id: u32,
-	#[serde(rename = "@dimensionType")]
+	/// Composite encoding the block length and the number of entries.
+	#[serde(rename = "@dimensionType", default = "default_dimension_type")]
 	pub dimension_type: String,
+	/// Explicit length of the block of each entry, may be larger than the fields need.
+	#[serde(rename = "@blockLength")]
+	pub block_length: Option<u32>,
+	/// Fields of the block, in the order they are encoded.
 	#[serde(rename = "field")]
 	pub fields: Option<Vec<Field>>,
+	/// Groups repeated within each entry of this group.
+	#[serde(rename = "group")]
+	pub groups: Option<Vec<Group>>,
+	/// Variable length data, encoded after the groups.
 	#[serde(rename = "data")]
 	pub data: Option<Vec<Data>>,
+	/// Schema version the group was added in, 0 if not set.
 	#[serde(rename = "@sinceVersion")]
 	pub since_version: Option<u32>,
+	/// Semantic type of the group, e.g. `NumInGroup`.
+	#[serde(rename = "@semanticType")]
+	pub semantic_type: Option<String>,
+	/// Schema version the group is deprecated since.
+	#[serde(rename = "@deprecated")]
+	pub deprecated: Option<u32>,
+}
+
+fn default_dimension_type() -> String {
+	DEFAULT_DIMENSION_TYPE.to_string()
 }
 
 impl PartialEq for Group {
@@ -220,7 +291,9 @@ impl PartialEq for Group {
 		self.name == other.name &&
 			self.id == other.id &&
 			self.dimension_type == other.dimension_type &&
+			self.block_length == other.block_length &&
 			self.fields == other.fields &&
+			self.groups == other.groups &&
 			self.data == other.data &&
 			self.since_version == other.since_version
 	}
@@ -232,9 +305,15 @@ impl Hash for Group {
 	fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
 		self.id.hash(state);
 		self.dimension_type.hash(state);
+		if self.block_length.is_some() {
+			self.block_length.hash(state);
+		}
 		if let Some(fields) = self.fields.as_ref() {
 			fields.iter().for_each(|f| f.hash(state));
 		};
+		if let Some(groups) = self.groups.as_ref() {
+			groups.iter().for_each(|g| g.hash(state));
+		};
 		if let Some(data) = self.data.as_ref() {
 			data.iter().for_each(|f| f.hash(state));
 		};
@@ -242,19 +321,63 @@ impl Hash for Group {
 	}
 }
 
+impl Group {
+	/// Fields in the order they are encoded.
+	pub fn fields(&self) -> impl Iterator<Item = &Field> {
+		self.fields.iter().flatten()
+	}
+
+	/// Repeating groups in the order they are encoded.
+	pub fn groups(&self) -> impl Iterator<Item = &Group> {
+		self.groups.iter().flatten()
+	}
+
+	/// Variable length data in the order they are encoded.
+	pub fn data(&self) -> impl Iterator<Item = &Data> {
+		self.data.iter().flatten()
+	}
+}
+
+/// `<field>` of a fixed size in the block of a message or a group.
 #[skip_serializing_none]
 #[derive(Debug, Default, Deserialize, Serialize)]
 pub struct Field {
+	/// Name of the field.
 	#[serde(rename = "@name")]
 	pub name: String,
+	/// Description of the field for documentation.
 	#[serde(rename = "@description")]
-	pub desciption: Option<String>,
+	pub description: Option<String>,
+	/// Id of the field, unique within the message.
 	#[serde(rename = "@id")]
 	pub id: u32,
+	/// Name of the type encoding the field.
 	#[serde(rename = "@type")]
 	pub r#type: String,
+	/// Schema version the field was added in, 0 if not set.
 	#[serde(rename = "@sinceVersion")]
 	pub since_version: Option<u32>,
+	/// Explicit offset of the field within the block.
+	#[serde(rename = "@offset")]
+	pub offset: Option<u32>,
+	/// Presence of the field, overrides the presence of the type.
+	#[serde(rename = "@presence")]
+	pub presence: Option<Presence>,
+	/// Reference to the enum value of a constant field, e.g. `Model.C`.
+	#[serde(rename = "@valueRef")]
+	pub value_ref: Option<String>,
+	/// Epoch of timestamp fields, `unix` if not given.
+	#[serde(rename = "@epoch")]
+	pub epoch: Option<String>,
+	/// Unit of timestamp fields, e.g. `nanosecond`.
+	#[serde(rename = "@timeUnit")]
+	pub time_unit: Option<String>,
+	/// Semantic type of the field, e.g. `Price` or `UTCTimestamp`, overrides the one of its type.
+	#[serde(rename = "@semanticType")]
+	pub semantic_type: Option<String>,
+	/// Schema version the field is deprecated since.
+	#[serde(rename = "@deprecated")]
+	pub deprecated: Option<u32>,
 }
 
 impl PartialEq for Field {
@@ -262,7 +385,10 @@ impl PartialEq for Field {
 		self.name == other.name &&
 			self.id == other.id &&
 			self.r#type == other.r#type &&
-			self.since_version == other.since_version
+			self.since_version == other.since_version &&
+			self.offset == other.offset &&
+			self.presence == other.presence &&
+			self.value_ref == other.value_ref
 	}
 }
 
@@ -273,22 +399,50 @@ impl Hash for Field {
 		self.id.hash(state);
 		self.r#type.hash(state);
 		self.since_version.hash(state);
+		if self.offset.is_some() {
+			self.offset.hash(state);
+		}
+		if self.presence.is_some() {
+			self.presence.hash(state);
+		}
+		if self.value_ref.is_some() {
+			self.value_ref.hash(state);
+		}
 	}
 }
 
+impl Field {
+	/// Presence of the field, required unless set.
+	pub fn presence(&self) -> Presence {
+		self.presence.unwrap_or_default()
+	}
+}
+
+/// `<data>` of variable length at the end of a message or a group entry.
 #[skip_serializing_none]
 #[derive(Debug, Default, Deserialize, Serialize)]
 pub struct Data {
+	/// Name of the data.
 	#[serde(rename = "@name")]
 	pub name: String,
+	/// Description of the var data for documentation.
 	#[serde(rename = "@description")]
-	pub desciption: Option<String>,
+	pub description: Option<String>,
+	/// Id of the data, unique within the message.
 	#[serde(rename = "@id")]
 	pub id: u32,
+	/// Name of the composite encoding the length and the bytes.
 	#[serde(rename = "@type")]
 	pub r#type: String,
+	/// Schema version the data was added in, 0 if not set.
 	#[serde(rename = "@sinceVersion")]
 	pub since_version: Option<u32>,
+	/// Semantic type of the var data, e.g. `data` or `String`.
+	#[serde(rename = "@semanticType")]
+	pub semantic_type: Option<String>,
+	/// Schema version the data is deprecated since.
+	#[serde(rename = "@deprecated")]
+	pub deprecated: Option<u32>,
 }
 
 impl PartialEq for Data {
@@ -310,19 +464,32 @@ impl Hash for Data {
 	}
 }
 
+/// `<types>` block; a schema may have several of them.
 #[skip_serializing_none]
 #[derive(Debug, PartialEq, Default, Deserialize, Serialize)]
 pub struct Types {
+	/// Files included into the block.
+	#[serde(rename(serialize = "xi:include", deserialize = "include"))]
+	pub include: Option<Vec<Include>>,
+	/// Standalone `<type>`s.
+	#[serde(rename = "type")]
+	pub types: Option<Vec<Type>>,
+	/// Composites.
 	#[serde(rename = "composite")]
 	pub composites: Option<Vec<Composite>>,
+	/// Enums.
 	#[serde(rename = "enum")]
 	pub enums: Option<Vec<EnumType>>,
+	/// Sets.
 	#[serde(rename = "set")]
 	pub sets: Option<Vec<SetType>>,
 }
 
 impl Hash for Types {
 	fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+		if let Some(types) = self.types.as_ref() {
+			types.iter().for_each(|t| t.hash(state));
+		};
 		if let Some(composites) = self.composites.as_ref() {
 			composites.iter().for_each(|f| f.hash(state));
 		};
@@ -335,24 +502,47 @@ impl Hash for Types {
 	}
 }
 
+/// `<enum>` of named values encoded as a primitive type.
 #[skip_serializing_none]
 #[derive(Debug, Default, Deserialize, Serialize)]
 pub struct EnumType {
+	/// Name of the enum.
 	#[serde(rename = "@name")]
 	pub name: String,
+	/// Description of the enum for documentation.
 	#[serde(rename = "@description")]
 	pub description: Option<String>,
+	/// Encoding of the values, a primitive or a standalone type.
 	#[serde(rename = "@encodingType")]
-	pub encoding_type: Option<PrimitiveType>,
+	pub encoding_type: Option<EncodingType>,
+	/// Value marking an optional enum as not set.
+	#[serde(rename = "@nullValue")]
+	pub null_value: Option<String>,
+	/// Explicit offset of the enum within the enclosing composite.
+	#[serde(rename = "@offset")]
+	pub offset: Option<u32>,
+	/// The named values.
 	#[serde(rename = "validValue")]
 	pub valid_values: Option<Vec<ValidValue>>,
+	/// Semantic type of the enum, e.g. `char` or `int`.
+	#[serde(rename = "@semanticType")]
+	pub semantic_type: Option<String>,
+	/// Schema version the enum was added in, 0 if not set.
+	#[serde(rename = "@sinceVersion")]
+	pub since_version: Option<u32>,
+	/// Schema version the enum is deprecated since.
+	#[serde(rename = "@deprecated")]
+	pub deprecated: Option<u32>,
 }
 
 impl PartialEq for EnumType {
 	fn eq(&self, other: &Self) -> bool {
 		self.name == other.name &&
 			self.encoding_type == other.encoding_type &&
-			self.valid_values == other.valid_values
+			self.null_value == other.null_value &&
+			self.offset == other.offset &&
+			self.valid_values == other.valid_values &&
+			self.since_version == other.since_version
 	}
 }
 
@@ -364,26 +554,54 @@ impl Hash for EnumType {
 		if self.encoding_type.is_some() {
 			self.encoding_type.hash(state);
 		}
+		if self.null_value.is_some() {
+			self.null_value.hash(state);
+		}
+		if self.offset.is_some() {
+			self.offset.hash(state);
+		}
 		if let Some(valid_values) = self.valid_values.as_ref() {
 			valid_values.iter().for_each(|f| f.hash(state));
 		};
+		if self.since_version.is_some() {
+			self.since_version.hash(state);
+		}
+	}
+}
+
+impl EnumType {
+	/// The named values in the order of the schema.
+	pub fn valid_values(&self) -> impl Iterator<Item = &ValidValue> {
+		self.valid_values.iter().flatten()
 	}
 }
 
+/// `<validValue>` of an enum.
 #[skip_serializing_none]
 #[derive(Debug, Default, Deserialize, Serialize)]
 pub struct ValidValue {
+	/// Name of the value.
 	#[serde(rename = "@name")]
 	pub name: String,
+	/// Description of the valid value for documentation.
 	#[serde(rename = "@description")]
 	pub description: Option<String>,
+	/// The encoded value.
 	#[serde(rename = "$text")]
 	pub value: String,
+	/// Schema version the value was added in, 0 if not set.
+	#[serde(rename = "@sinceVersion")]
+	pub since_version: Option<u32>,
+	/// Schema version the value is deprecated since.
+	#[serde(rename = "@deprecated")]
+	pub deprecated: Option<u32>,
 }
 
 impl PartialEq for ValidValue {
 	fn eq(&self, other: &Self) -> bool {
-		self.name == other.name && self.value == other.value
+		self.name == other.name &&
+			self.value == other.value &&
+			self.since_version == other.since_version
 	}
 }
 
@@ -393,27 +611,49 @@ impl Hash for ValidValue {
 	fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
 		self.name.hash(state);
 		self.value.hash(state);
+		if self.since_version.is_some() {
+			self.since_version.hash(state);
+		}
 	}
 }
 
+/// `<set>` of choices encoded as bits of an unsigned integer.
 #[skip_serializing_none]
 #[derive(Debug, Default, Deserialize, Serialize)]
 pub struct SetType {
+	/// Name of the set.
 	#[serde(rename = "@name")]
 	pub name: String,
+	/// Description of the set for documentation.
 	#[serde(rename = "@description")]
 	pub description: Option<String>,
+	/// Encoding of the bits, an unsigned primitive or a standalone type.
 	#[serde(rename = "@encodingType")]
-	pub encoding_type: Option<PrimitiveType>,
+	pub encoding_type: Option<EncodingType>,
+	/// Explicit offset of the set within the enclosing composite.
+	#[serde(rename = "@offset")]
+	pub offset: Option<u32>,
+	/// The choices.
 	#[serde(rename = "choice")]
 	pub choices: Option<Vec<Choice>>,
+	/// Semantic type of the set, e.g. `MultipleCharValue`.
+	#[serde(rename = "@semanticType")]
+	pub semantic_type: Option<String>,
+	/// Schema version the set was added in, 0 if not set.
+	#[serde(rename = "@sinceVersion")]
+	pub since_version: Option<u32>,
+	/// Schema version the set is deprecated since.
+	#[serde(rename = "@deprecated")]
+	pub deprecated: Option<u32>,
 }
 
 impl PartialEq for SetType {
 	fn eq(&self, other: &Self) -> bool {
 		self.name == other.name &&
 			self.encoding_type == other.encoding_type &&
-			self.choices == other.choices
+			self.offset == other.offset &&
+			self.choices == other.choices &&
+			self.since_version == other.since_version
 	}
 }
 
@@ -425,26 +665,51 @@ impl Hash for SetType {
 		if self.encoding_type.is_some() {
 			self.encoding_type.hash(state);
 		}
+		if self.offset.is_some() {
+			self.offset.hash(state);
+		}
 		if let Some(choices) = self.choices.as_ref() {
 			choices.iter().for_each(|f| f.hash(state));
 		};
+		if self.since_version.is_some() {
+			self.since_version.hash(state);
+		}
 	}
 }
 
+impl SetType {
+	/// The choices in the order of the schema.
+	pub fn choices(&self) -> impl Iterator<Item = &Choice> {
+		self.choices.iter().flatten()
+	}
+}
+
+/// `<choice>` of a set, the value is the bit position.
 #[skip_serializing_none]
 #[derive(Debug, Default, Deserialize, Serialize)]
 pub struct Choice {
+	/// Name of the choice.
 	#[serde(rename = "@name")]
 	pub name: String,
+	/// Description of the choice for documentation.
 	#[serde(rename = "@description")]
 	pub description: Option<String>,
+	/// Position of the bit, 0 is the least significant.
 	#[serde(rename = "$text")]
 	pub value: String,
+	/// Schema version the choice was added in, 0 if not set.
+	#[serde(rename = "@sinceVersion")]
+	pub since_version: Option<u32>,
+	/// Schema version the choice is deprecated since.
+	#[serde(rename = "@deprecated")]
+	pub deprecated: Option<u32>,
 }
 
 impl PartialEq for Choice {
 	fn eq(&self, other: &Self) -> bool {
-		self.name == other.name && self.value == other.value
+		self.name == other.name &&
+			self.value == other.value &&
+			self.since_version == other.since_version
 	}
 }
 
@@ -454,26 +719,252 @@ impl Hash for Choice {
 	fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
 		self.name.hash(state);
 		self.value.hash(state);
+		if self.since_version.is_some() {
+			self.since_version.hash(state);
+		}
 	}
 }
 
-#[skip_serializing_none]
-#[derive(Debug, Default, Deserialize, Serialize)]
+/// `<composite>` of members encoded one after another.
+///
+/// Serialized by hand to keep the members in order.
+#[derive(Debug, Default, Deserialize)]
+#[serde(from = "RawComposite")]
 pub struct Composite {
+	/// Name of the composite.
 	#[serde(rename = "@name")]
 	pub name: String,
+	/// Description of the composite for documentation.
 	#[serde(rename = "@description")]
 	pub description: Option<String>,
+	/// `<type>` members.
 	#[serde(rename = "type")]
 	pub types: Option<Vec<Type>>,
+	/// `<ref>` members.
 	#[serde(rename = "ref")]
 	pub refs: Option<Vec<Ref>>,
+	/// Nested `<enum>` members.
+	#[serde(rename = "enum")]
+	pub enums: Option<Vec<EnumType>>,
+	/// Nested `<set>` members.
+	#[serde(rename = "set")]
+	pub sets: Option<Vec<SetType>>,
+	/// Nested `<composite>` members.
+	#[serde(rename = "composite")]
+	pub composites: Option<Vec<Composite>>,
+	/// Explicit offset of a composite nested within the enclosing composite.
+	#[serde(rename = "@offset")]
+	pub offset: Option<u32>,
+	/// Semantic type of the composite, e.g. `Price` or `MonthYear`.
+	#[serde(rename = "@semanticType")]
+	pub semantic_type: Option<String>,
+	/// Schema version the composite was added in, 0 if not set.
+	#[serde(rename = "@sinceVersion")]
+	pub since_version: Option<u32>,
+	/// Schema version the composite is deprecated since.
+	#[serde(rename = "@deprecated")]
+	pub deprecated: Option<u32>,
+	/// Order of the members in the schema, they are encoded in this order.
+	#[serde(skip)]
+	order: Vec<MemberKind>,
+}
+
+/// Member of a composite, in the order they are encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CompositeMember<'a> {
+	/// `<type>` member.
+	Type(&'a Type),
+	/// `<ref>` member.
+	Ref(&'a Ref),
+	/// Nested `<enum>` member.
+	Enum(&'a EnumType),
+	/// Nested `<set>` member.
+	Set(&'a SetType),
+	/// Nested `<composite>` member.
+	Composite(&'a Composite),
+}
+
+impl<'a> CompositeMember<'a> {
+	/// Name of the member.
+	pub fn name(&self) -> &'a str {
+		match self {
+			CompositeMember::Type(t) => &t.name,
+			CompositeMember::Ref(r) => &r.name,
+			CompositeMember::Enum(e) => &e.name,
+			CompositeMember::Set(s) => &s.name,
+			CompositeMember::Composite(c) => &c.name,
+		}
+	}
+
+	/// Schema version the member was added in, 0 if not set.
+	pub fn since_version(&self) -> u32 {
+		match self {
+			CompositeMember::Type(t) => t.since_version,
+			CompositeMember::Ref(r) => r.since_version,
+			CompositeMember::Enum(e) => e.since_version,
+			CompositeMember::Set(s) => s.since_version,
+			CompositeMember::Composite(c) => c.since_version,
+		}
+		.unwrap_or_default()
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MemberKind {
+	Type,
+	Ref,
+	Enum,
+	Set,
+	Composite,
+}
+
+impl Composite {
+	/// All members in the order they are encoded.
+	pub fn members(&self) -> Vec<CompositeMember<'_>> {
+		let mut types = self.types.iter().flatten();
+		let mut refs = self.refs.iter().flatten();
+		let mut enums = self.enums.iter().flatten();
+		let mut sets = self.sets.iter().flatten();
+		let mut composites = self.composites.iter().flatten();
+		let mut members = self
+			.order
+			.iter()
+			.filter_map(|kind| match kind {
+				MemberKind::Type => types.next().map(CompositeMember::Type),
+				MemberKind::Ref => refs.next().map(CompositeMember::Ref),
+				MemberKind::Enum => enums.next().map(CompositeMember::Enum),
+				MemberKind::Set => sets.next().map(CompositeMember::Set),
+				MemberKind::Composite => composites.next().map(CompositeMember::Composite),
+			})
+			.collect::<Vec<_>>();
+		// members added without the order are encoded after the ordered ones
+		members.extend(types.map(CompositeMember::Type));
+		members.extend(refs.map(CompositeMember::Ref));
+		members.extend(enums.map(CompositeMember::Enum));
+		members.extend(sets.map(CompositeMember::Set));
+		members.extend(composites.map(CompositeMember::Composite));
+		members
+	}
+}
+
+/// Composite as it appears in the schema, the elements are kept in order.
+#[derive(Deserialize)]
+struct RawComposite {
+	#[serde(rename = "@name")]
+	name: String,
+	#[serde(rename = "@description")]
+	description: Option<String>,
+	#[serde(rename = "@offset")]
+	offset: Option<u32>,
+	#[serde(rename = "@semanticType")]
+	semantic_type: Option<String>,
+	#[serde(rename = "@sinceVersion")]
+	since_version: Option<u32>,
+	#[serde(rename = "@deprecated")]
+	deprecated: Option<u32>,
+	#[serde(rename = "$value", default)]
+	elements: Vec<CompositeElement>,
+}
+
+#[derive(Deserialize)]
+enum CompositeElement {
+	#[serde(rename = "type")]
+	Type(Type),
+	#[serde(rename = "ref")]
+	Ref(Ref),
+	#[serde(rename = "enum")]
+	Enum(EnumType),
+	#[serde(rename = "set")]
+	Set(SetType),
+	#[serde(rename = "composite")]
+	Composite(Composite),
+}
+
+impl From<RawComposite> for Composite {
+	fn from(raw: RawComposite) -> Self {
+		let (mut types, mut refs, mut order) = (Vec::new(), Vec::new(), Vec::new());
+		let (mut enums, mut sets, mut composites) = (Vec::new(), Vec::new(), Vec::new());
+		for element in raw.elements {
+			let kind = match element {
+				CompositeElement::Type(t) => {
+					types.push(t);
+					MemberKind::Type
+				},
+				CompositeElement::Ref(r) => {
+					refs.push(r);
+					MemberKind::Ref
+				},
+				CompositeElement::Enum(e) => {
+					enums.push(e);
+					MemberKind::Enum
+				},
+				CompositeElement::Set(s) => {
+					sets.push(s);
+					MemberKind::Set
+				},
+				CompositeElement::Composite(c) => {
+					composites.push(c);
+					MemberKind::Composite
+				},
+			};
+			order.push(kind);
+		}
+		Composite {
+			name: raw.name,
+			description: raw.description,
+			types: (!types.is_empty()).then_some(types),
+			refs: (!refs.is_empty()).then_some(refs),
+			enums: (!enums.is_empty()).then_some(enums),
+			sets: (!sets.is_empty()).then_some(sets),
+			composites: (!composites.is_empty()).then_some(composites),
+			offset: raw.offset,
+			semantic_type: raw.semantic_type,
+			since_version: raw.since_version,
+			deprecated: raw.deprecated,
+			order,
+		}
+	}
+}
+
+impl Serialize for Composite {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		let mut state = serializer.serialize_struct("composite", 8)?;
+		state.serialize_field("@name", &self.name)?;
+		let attributes = [
+			("@description", self.description.as_ref()),
+			("@semanticType", self.semantic_type.as_ref()),
+		];
+		for (key, value) in attributes {
+			match value {
+				Some(value) => state.serialize_field(key, value)?,
+				None => state.skip_field(key)?,
+			}
+		}
+		let numbers = [
+			("@offset", self.offset),
+			("@sinceVersion", self.since_version),
+			("@deprecated", self.deprecated),
+		];
+		for (key, value) in numbers {
+			match value {
+				Some(value) => state.serialize_field(key, &value)?,
+				None => state.skip_field(key)?,
+			}
+		}
+		state.serialize_field("$value", &self.members())?;
+		state.end()
+	}
 }
 
 impl PartialEq for Composite {
-	// compare is two composite are the same, i.e. contain the same list of types and refs
+	// compare is two composite are the same, i.e. contain the same list of types and refs in the
+	// same order
 	fn eq(&self, other: &Self) -> bool {
-		self.name == other.name && self.types == other.types && self.refs == other.refs
+		self.name == other.name &&
+			self.offset == other.offset &&
+			self.since_version == other.since_version &&
+			self.members() == other.members()
 	}
 }
 
@@ -482,28 +973,52 @@ impl Eq for Composite {}
 impl Hash for Composite {
 	fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
 		self.name.hash(state);
-		if let Some(types) = self.types.as_ref() {
-			types.iter().for_each(|f| f.hash(state));
-		};
-		if let Some(refs) = self.refs.as_ref() {
-			refs.iter().for_each(|f| f.hash(state));
-		};
+		if self.offset.is_some() {
+			self.offset.hash(state);
+		}
+		if self.since_version.is_some() {
+			self.since_version.hash(state);
+		}
+		for member in self.members() {
+			match member {
+				CompositeMember::Type(t) => t.hash(state),
+				CompositeMember::Ref(r) => r.hash(state),
+				CompositeMember::Enum(e) => e.hash(state),
+				CompositeMember::Set(s) => s.hash(state),
+				CompositeMember::Composite(c) => c.hash(state),
+			}
+		}
 	}
 }
 
+/// `<ref>` member of a composite referring to another type.
 #[skip_serializing_none]
 #[derive(Debug, Default, Deserialize, Serialize)]
 pub struct Ref {
+	/// Name of the ref.
 	#[serde(rename = "@name")]
 	pub name: String,
+	/// Description of the ref, the referenced type keeps its own.
 	#[serde(rename = "@description")]
 	pub description: Option<String>,
+	/// Name of the referenced type.
 	#[serde(rename = "@type")]
 	pub ref_type: String,
+	/// Presence of the member, overrides the presence of the type.
 	#[serde(rename = "@presence")]
 	pub presence: Option<Presence>,
+	/// Reference to the enum value of a constant member, e.g. `Model.C`.
 	#[serde(rename = "@valueRef")]
 	pub value_ref: Option<String>,
+	/// Explicit offset of the member within the composite.
+	#[serde(rename = "@offset")]
+	pub offset: Option<u32>,
+	/// Schema version the ref was added in, 0 if not set.
+	#[serde(rename = "@sinceVersion")]
+	pub since_version: Option<u32>,
+	/// Schema version the ref is deprecated since.
+	#[serde(rename = "@deprecated")]
+	pub deprecated: Option<u32>,
 }
 
 impl PartialEq for Ref {
@@ -511,7 +1026,9 @@ impl PartialEq for Ref {
 		self.name == other.name &&
 			self.ref_type == other.ref_type &&
 			self.value_ref == other.value_ref &&
-			self.presence == other.presence
+			self.presence == other.presence &&
+			self.offset == other.offset &&
+			self.since_version == other.since_version
 	}
 }
 
@@ -527,22 +1044,35 @@ impl Hash for Ref {
 		if self.value_ref.is_some() {
 			self.value_ref.hash(state);
 		}
+		if self.offset.is_some() {
+			self.offset.hash(state);
+		}
+		if self.since_version.is_some() {
+			self.since_version.hash(state);
+		}
 	}
 }
 
+/// `<type>` encoded as a primitive, an array of primitives or a constant.
 #[skip_serializing_none]
 #[derive(Debug, Default, Deserialize, Serialize)]
 pub struct Type {
+	/// Name of the type.
 	#[serde(rename = "@name")]
 	pub name: String,
+	/// Description of the type for documentation.
 	#[serde(rename = "@description")]
 	pub description: Option<String>,
+	/// Primitive encoding of the type.
 	#[serde(rename = "@primitiveType")]
 	pub primitive_type: Option<PrimitiveType>,
+	/// Number of primitives in an array, 1 if not set.
 	#[serde(rename = "@length")]
 	pub length: Option<u32>,
+	/// Largest valid value.
 	#[serde(rename = "@maxValue")]
 	pub max_value: Option<String>,
+	/// Smallest valid value.
 	#[serde(rename = "@minValue")]
 	pub min_value: Option<String>,
 	/// A special value that indicates that an optional value is not set.
@@ -550,14 +1080,30 @@ pub struct Type {
 	/// Mutually exclusive with presence=required and constant.
 	#[serde(rename = "@nullValue")]
 	pub null_value: Option<String>,
+	/// Character encoding of `char` arrays, e.g. `US-ASCII`.
 	#[serde(rename = "@characterEncoding")]
 	pub character_encoding: Option<String>,
+	/// Presence of the value, required if not set.
 	#[serde(rename = "@presence")]
 	pub presence: Option<Presence>,
+	/// Schema version the type was added in, 0 if not set.
 	#[serde(rename = "@sinceVersion")]
 	pub since_version: Option<u32>,
+	/// Reference to the enum value of a constant, e.g. `TimeUnit.nanosecond`.
+	#[serde(rename = "@valueRef")]
+	pub value_ref: Option<String>,
+	/// Explicit offset of the member within the composite.
+	#[serde(rename = "@offset")]
+	pub offset: Option<u32>,
+	/// The value of a constant type.
 	#[serde(rename = "$text")]
 	pub value: Option<String>,
+	/// Semantic type of the type, e.g. `Price` or `UTCTimestamp`.
+	#[serde(rename = "@semanticType")]
+	pub semantic_type: Option<String>,
+	/// Schema version the type is deprecated since.
+	#[serde(rename = "@deprecated")]
+	pub deprecated: Option<u32>,
 }
 
 impl PartialEq for Type {
@@ -570,6 +1116,8 @@ impl PartialEq for Type {
 			self.null_value == other.null_value &&
 			self.character_encoding == other.character_encoding &&
 			self.since_version == other.since_version &&
+			self.value_ref == other.value_ref &&
+			self.offset == other.offset &&
 			self.value == other.value
 	}
 }
@@ -603,39 +1151,142 @@ impl Hash for Type {
 		if self.since_version.is_some() {
 			self.since_version.hash(state);
 		}
+		if self.value_ref.is_some() {
+			self.value_ref.hash(state);
+		}
+		if self.offset.is_some() {
+			self.offset.hash(state);
+		}
 		if self.value.is_some() {
 			self.value.hash(state);
 		}
 	}
 }
 
-#[derive(Debug, PartialEq, Deserialize, Serialize, Hash)]
+/// Encoding of an enum or a set, either a primitive type or a name of a standalone type.
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum EncodingType {
+	/// Encoded directly as the primitive type.
+	Primitive(PrimitiveType),
+	/// Encoded as the named `<type>`.
+	Type(String),
+}
+
+impl Hash for EncodingType {
+	fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+		match self {
+			EncodingType::Primitive(primitive) => primitive.hash(state),
+			EncodingType::Type(name) => name.hash(state),
+		}
+	}
+}
+
+/// Primitive encoding of a value on the wire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, Hash)]
 pub enum PrimitiveType {
+	/// `uint8`.
 	#[serde(rename = "uint8")]
 	Uint8,
+	/// `int8`.
 	#[serde(rename = "int8")]
 	Int8,
+	/// `uint16`.
 	#[serde(rename = "uint16")]
 	Uint16,
+	/// `int16`.
 	#[serde(rename = "int16")]
 	Int16,
+	/// `uint32`.
 	#[serde(rename = "uint32")]
 	Uint32,
+	/// `int32`.
 	#[serde(rename = "int32")]
 	Int32,
+	/// `uint64`.
 	#[serde(rename = "uint64")]
 	Uint64,
+	/// `int64`.
 	#[serde(rename = "int64")]
 	Int64,
+	/// `char`.
 	#[serde(rename = "char")]
 	Char,
+	/// `float`.
 	#[serde(rename = "float")]
 	Float,
+	/// `double`.
 	#[serde(rename = "double")]
 	Double,
 }
 
-#[derive(Debug, PartialEq, Deserialize, Serialize, Hash)]
+impl PrimitiveType {
+	/// All primitive types.
+	pub const ALL: [PrimitiveType; 11] = [
+		PrimitiveType::Uint8,
+		PrimitiveType::Int8,
+		PrimitiveType::Uint16,
+		PrimitiveType::Int16,
+		PrimitiveType::Uint32,
+		PrimitiveType::Int32,
+		PrimitiveType::Uint64,
+		PrimitiveType::Int64,
+		PrimitiveType::Char,
+		PrimitiveType::Float,
+		PrimitiveType::Double,
+	];
+
+	/// The name of the primitive type used in the schema.
+	pub fn name(&self) -> &'static str {
+		match self {
+			PrimitiveType::Uint8 => "uint8",
+			PrimitiveType::Int8 => "int8",
+			PrimitiveType::Uint16 => "uint16",
+			PrimitiveType::Int16 => "int16",
+			PrimitiveType::Uint32 => "uint32",
+			PrimitiveType::Int32 => "int32",
+			PrimitiveType::Uint64 => "uint64",
+			PrimitiveType::Int64 => "int64",
+			PrimitiveType::Char => "char",
+			PrimitiveType::Float => "float",
+			PrimitiveType::Double => "double",
+		}
+	}
+
+	/// The primitive type with the given schema name.
+	pub fn from_name(name: &str) -> Option<PrimitiveType> {
+		PrimitiveType::ALL.into_iter().find(|p| p.name() == name)
+	}
+
+	/// Size of the primitive type in bytes.
+	pub fn size(&self) -> u32 {
+		match self {
+			PrimitiveType::Uint8 | PrimitiveType::Int8 | PrimitiveType::Char => 1,
+			PrimitiveType::Uint16 | PrimitiveType::Int16 => 2,
+			PrimitiveType::Uint32 | PrimitiveType::Int32 | PrimitiveType::Float => 4,
+			PrimitiveType::Uint64 | PrimitiveType::Int64 | PrimitiveType::Double => 8,
+		}
+	}
+
+	/// Default minimum, maximum and null value of the integer and char types.
+	pub(crate) fn range(&self) -> Option<(i128, i128, i128)> {
+		Some((
+			self.min_value().as_i128()?,
+			self.max_value().as_i128()?,
+			self.null_value().as_i128()?,
+		))
+	}
+}
+
+/// Written as in the schema, e.g. `uint8`.
+impl std::fmt::Display for PrimitiveType {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(self.name())
+	}
+}
+
+/// Whether a value is always set, may be null or is a constant.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, Hash)]
 #[serde(rename_all = "camelCase")]
 pub enum Presence {
 	/// The field has a constant value that need not be transmitted on the wire.
@@ -643,32 +1294,53 @@ pub enum Presence {
 	Constant,
 	/// The field must always be set. This is the default presence. Mutually exclusive with
 	/// nullValue.
+	#[default]
 	Required,
 	/// The field need not be populated. A special null value indicates that a field is not set.
 	/// The presence attribute may be specified on either on a field or its encoding.
 	Optional,
 }
 
-#[derive(PartialEq, Deserialize, Serialize, Hash)]
-pub struct SematicVersion(semver::Version);
+/// Semantic version of a schema, e.g. `5.2`.
+#[derive(PartialEq, Deserialize, Hash)]
+pub struct SemanticVersion(semver::Version);
+
+/// Former, misspelled name of [`SemanticVersion`].
+#[deprecated(note = "renamed to `SemanticVersion`")]
+pub type SematicVersion = SemanticVersion;
+
+/// Written as `major.minor` unless the patch or pre-release is set, as usual for SBE schemas.
+impl Serialize for SemanticVersion {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		let version = &self.0;
+		if version.patch == 0 && version.pre.is_empty() && version.build.is_empty() {
+			serializer.serialize_str(&format!("{}.{}", version.major, version.minor))
+		} else {
+			serializer.serialize_str(&version.to_string())
+		}
+	}
+}
 
-impl std::fmt::Debug for SematicVersion {
+impl std::fmt::Debug for SemanticVersion {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		write!(f, "{}.{}.{}", self.0.major, self.0.minor, self.0.patch)
 	}
 }
 
-impl Default for SematicVersion {
+impl Default for SemanticVersion {
 	fn default() -> Self {
-		SematicVersion(semver::Version::new(0, 0, 0))
+		SemanticVersion(semver::Version::new(0, 0, 0))
 	}
 }
 
-#[derive(Debug, Default, PartialEq, Deserialize, Serialize, Hash)]
+/// Byte order of the encoded values.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, Hash)]
 #[serde(rename_all = "camelCase")]
 pub enum ByteOrder {
+	/// Least significant byte first, the default.
 	#[default]
 	LittleEndian,
+	/// Most significant byte first.
 	BigEndian,
 }
 
@@ -678,7 +1350,6 @@ mod tests {
 
 	use super::*;
 	use quick_xml::de::from_str;
-	use std::{collections::hash_map::DefaultHasher, hash::Hasher};
 
 	const XML: &str = r#"
     <?xml version="1.0" encoding="UTF-8" standalone="yes"?>
@@ -712,21 +1383,58 @@ mod tests {
 	fn it_works() {
 		let sbe: Schema = from_str(XML).expect("Failed to parse XML");
 		dbg!("{:?}", &sbe);
-		let mut h = DefaultHasher::new();
-		sbe.hash(&mut h);
-		let hash = h.finish();
-		dbg!(hash);
+		let fingerprint = sbe.fingerprint().to_string();
+
+		let expected_fingerprint =
+			"5bcc929bbcfbcd0c157f6e54918c72f11c1ed58c3b3a3e907397da3151335218";
+		assert_eq!(fingerprint, expected_fingerprint);
 
-		let expected_hash = 15732360995906542288;
-		assert_eq!(hash, expected_hash);
+		let message = &sbe.messages.as_ref().unwrap()[0];
+		assert_eq!(message.semantic_type.as_deref(), Some("n/a"));
+		assert_eq!(message.deprecated, Some(3));
 
 		assert_eq!(sbe.byte_order, Some(ByteOrder::LittleEndian));
 		assert_eq!(
 			sbe.semantic_version,
-			Some(SematicVersion(semver::Version::from_str("5.2").unwrap()))
+			Some(SemanticVersion(semver::Version::from_str("5.2").unwrap()))
 		);
 
-		// let xml = quick_xml::se::to_string(&sbe).expect("Failed to serialize XML");
-		// dbg!("{:?}", &xml);
+		let xml = sbe.to_xml().expect("Failed to serialize XML");
+		assert!(xml.parse::<Schema>().unwrap() == sbe);
+	}
+
+	#[test]
+	fn test_accessors_flatten_types_blocks() {
+		let sbe: Schema = from_str(
+			r#"<sbe:messageSchema xmlns:sbe="http://fixprotocol.io/2016/sbe" id="1" version="0">
+				<types>
+					<composite name="messageHeader"><type name="blockLength" primitiveType="uint16"/></composite>
+					<enum name="Side" encodingType="char"><validValue name="Buy">B</validValue></enum>
+				</types>
+				<types>
+					<type name="Price" primitiveType="int64"/>
+					<composite name="Decimal"><type name="mantissa" primitiveType="int64"/></composite>
+					<set name="Flags" encodingType="uint8"><choice name="a">0</choice></set>
+				</types>
+				<sbe:message name="Order" id="7">
+					<field name="side" id="1" type="Side" presence="optional"/>
+					<group name="legs" id="2"><field name="price" id="3" type="Price"/></group>
+				</sbe:message>
+			</sbe:messageSchema>"#,
+		)
+		.expect("Failed to parse XML");
+
+		let composites = sbe.composites().map(|c| c.name.as_str()).collect::<Vec<_>>();
+		assert_eq!(composites, ["messageHeader", "Decimal"]);
+		assert_eq!(sbe.types().count(), 1);
+		assert_eq!(sbe.enums().flat_map(EnumType::valid_values).count(), 1);
+		assert_eq!(sbe.sets().flat_map(SetType::choices).count(), 1);
+		assert_eq!(sbe.byte_order(), ByteOrder::LittleEndian);
+
+		let message = sbe.message(7).expect("message 7");
+		assert_eq!(message.fields().next().map(Field::presence), Some(Presence::Optional));
+		assert_eq!(message.groups().flat_map(Group::fields).count(), 1);
+		assert_eq!(message.data().count(), 0);
+		assert!(sbe.message(8).is_none());
 	}
 }
@@ -0,0 +1,413 @@
+//! Validation of a schema against the semantic rules of SBE.
+
+mod rules;
+
+use serde::{Serialize, Serializer};
+
+use crate::Schema;
+
+pub use rules::{
+	ConstantValueRule, EncodingValuesRule, LayoutRule, MessageHeaderRule, NamesRule,
+	SinceVersionRule, TypeReferencesRule, UniqueIdsRule, UniqueNamesRule,
+};
+
+/// Severity of a diagnostic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+	/// The schema is valid, but likely not what was intended.
+	Warning,
+	/// The schema is invalid.
+	Error,
+}
+
+impl std::fmt::Display for Severity {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Severity::Warning => f.write_str("warning"),
+			Severity::Error => f.write_str("error"),
+		}
+	}
+}
+
+/// Single problem found in the schema.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Diagnostic {
+	/// How serious the problem is.
+	pub severity: Severity,
+	/// Name of the rule that found the problem.
+	pub rule: &'static str,
+	/// Path of the element, e.g. `NewOrder/field[id=38]`.
+	pub path: String,
+	/// Human readable description of the problem.
+	pub message: String,
+}
+
+/// All problems found in the schema.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Diagnostics {
+	rule: &'static str,
+	diagnostics: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+	/// Record an error found by the running rule.
+	pub fn error(&mut self, path: impl Into<String>, message: impl Into<String>) {
+		self.push(Severity::Error, path.into(), message.into());
+	}
+
+	/// Record a warning found by the running rule.
+	pub fn warning(&mut self, path: impl Into<String>, message: impl Into<String>) {
+		self.push(Severity::Warning, path.into(), message.into());
+	}
+
+	/// All problems found.
+	pub fn diagnostics(&self) -> &[Diagnostic] {
+		&self.diagnostics
+	}
+
+	/// Check if any error was found.
+	pub fn has_errors(&self) -> bool {
+		self.diagnostics.iter().any(|d| d.severity == Severity::Error)
+	}
+
+	/// Check if no problems were found.
+	pub fn is_empty(&self) -> bool {
+		self.diagnostics.is_empty()
+	}
+
+	fn push(&mut self, severity: Severity, path: String, message: String) {
+		self.diagnostics.push(Diagnostic { severity, rule: self.rule, path, message });
+	}
+}
+
+impl Serialize for Diagnostics {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.collect_seq(&self.diagnostics)
+	}
+}
+
+/// A semantic rule a schema must follow.
+pub trait Rule {
+	/// Short name of the rule, reported with each diagnostic.
+	fn name(&self) -> &'static str;
+
+	/// Check the schema and record all problems found.
+	fn check(&self, schema: &Schema, diagnostics: &mut Diagnostics);
+}
+
+/// Set of rules a schema is validated against.
+pub struct RuleSet {
+	rules: Vec<Box<dyn Rule>>,
+}
+
+impl RuleSet {
+	/// Create an empty rule set.
+	pub fn new() -> Self {
+		Self { rules: Vec::new() }
+	}
+
+	/// Add the rule to the set.
+	pub fn with(mut self, rule: impl Rule + 'static) -> Self {
+		self.rules.push(Box::new(rule));
+		self
+	}
+
+	/// Run all rules against the schema.
+	pub fn validate(&self, schema: &Schema) -> Diagnostics {
+		let mut diagnostics = Diagnostics::default();
+		for rule in &self.rules {
+			diagnostics.rule = rule.name();
+			rule.check(schema, &mut diagnostics);
+		}
+		diagnostics
+	}
+}
+
+impl Default for RuleSet {
+	/// All SBE rules.
+	fn default() -> Self {
+		Self::new()
+			.with(MessageHeaderRule)
+			.with(UniqueIdsRule)
+			.with(UniqueNamesRule)
+			.with(TypeReferencesRule)
+			.with(SinceVersionRule)
+			.with(NamesRule)
+			.with(EncodingValuesRule)
+			.with(ConstantValueRule)
+			.with(LayoutRule)
+	}
+}
+
+/// Validate the schema against all SBE rules.
+pub fn validate(schema: &Schema) -> Diagnostics {
+	RuleSet::default().validate(schema)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::path::{Path, PathBuf};
+
+	use super::*;
+	use crate::SchemaLoader;
+
+	fn resources() -> PathBuf {
+		Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/resources")
+	}
+
+	fn errors(file: &str) -> Vec<(&'static str, String)> {
+		let schema = SchemaLoader::new().load(resources().join(file)).unwrap();
+		validate(&schema)
+			.diagnostics()
+			.iter()
+			.filter(|d| d.severity == Severity::Error)
+			.map(|d| (d.rule, d.path.clone()))
+			.collect()
+	}
+
+	fn assert_errors(file: &str, expected: &[(&str, &str)]) {
+		let expected = expected
+			.iter()
+			.map(|(rule, path)| (*rule, path.to_string()))
+			.collect::<Vec<_>>();
+		assert_eq!(errors(file), expected, "{file}");
+	}
+
+	#[test]
+	fn test_valid_schemas() {
+		for file in ["example-schema.xml", "nested-group-schema.xml", "value-ref-schema.xml"] {
+			assert_errors(file, &[]);
+		}
+	}
+
+	#[test]
+	fn test_error_handler_schemas() {
+		assert_errors(
+			"error-handler-dup-message-schema.xml",
+			&[("message-header", "types"), ("unique-ids", "AnotherMessageWithId1")],
+		);
+		assert_errors(
+			"error-handler-enum-violates-min-max-value-range.xml",
+			&[
+				("encoding-values", "types/enum[name=EnumWithCustomEncodingType]/validValue[name=INVALID_ZERO]"),
+				("encoding-values", "types/enum[name=EnumWithCustomEncodingType]/validValue[name=INVALID_SIX]"),
+				(
+					"encoding-values",
+					"types/enum[name=EnumWithCustomEncodingType]/validValue[name=INVALID_NULL_VALUE]",
+				),
+				(
+					"encoding-values",
+					"types/enum[name=EnumWithCustomEncodingTypeOptional]/validValue[name=INVALID_THREE]",
+				),
+			],
+		);
+		assert_errors(
+			"error-handler-group-dimensions-schema.xml",
+			&[("type-references", "TestMessage1/group[id=2]")],
+		);
+		assert_errors(
+			"error-handler-invalid-name.xml",
+			&[
+				("names", "Message/field[id=0]"),
+				("names", "Message/group[id=2]"),
+				("names", "Message/group[id=2]/field[id=10]"),
+				("names", "Message/data[id=8]"),
+			],
+		);
+		assert_errors(
+			"error-handler-message-schema.xml",
+			&[
+				("message-header", "types/composite[name=messageHeader]"),
+				("unique-ids", "Message_1/data[id=100]"),
+				("unique-ids", "Message_1/data[id=101]"),
+				("type-references", "Message_1/field[id=4]"),
+				("type-references", "Message_1/group[id=100]"),
+				("type-references", "Message_1/data[id=101]"),
+			],
+		);
+		assert_errors(
+			"error-handler-since-version.xml",
+			&[
+				("since-version", "types/type[name=uuid_t]"),
+				("since-version", "types/enum[name=Colour]/validValue[name=GREEN]"),
+				("since-version", "TestMessage"),
+				("since-version", "TestMessage/field[id=0]"),
+				("since-version", "TestMessage/group[id=2]/field[id=4]"),
+				("since-version", "TestMessage/group[id=5]"),
+			],
+		);
+		assert_errors(
+			"error-handler-types-dup-schema.xml",
+			&[("message-header", "types"), ("unique-names", "types/type[name=TypeToTest]")],
+		);
+		assert_errors(
+			"error-handler-types-schema.xml",
+			&[
+				("message-header", "types"),
+				("names", "Message1/field[id=10]"),
+				("encoding-values", "types/enum[name=NullBoolean]/validValue[name=BOGUS]"),
+				("constant-value", "types/type[name=ConstButNoValue]"),
+			],
+		);
+		assert_errors(
+			"error-handler-invalid-composite-offsets-schema.xml",
+			&[
+				("layout", "types/composite[name=messageHeader]/type[name=schemaId]"),
+				("layout", "types/composite[name=groupSizeEncoding]/type[name=numInGroup]"),
+			],
+		);
+	}
+
+	#[test]
+	fn test_header_members() {
+		let schema: Schema = r#"<messageSchema version="0" headerType="header"><types>
+			<composite name="messageHeader"/>
+			<composite name="header"><type name="blockLength" primitiveType="uint16"/>
+			<type name="templateId" primitiveType="int16"/><type name="schemaId" primitiveType="uint16"/>
+			</composite></types></messageSchema>"#
+			.parse()
+			.unwrap();
+		let messages = validate(&schema)
+			.diagnostics()
+			.iter()
+			.map(|d| (d.path.clone(), d.message.clone()))
+			.collect::<Vec<_>>();
+		assert_eq!(
+			messages,
+			[
+				(
+					"types/composite[name=header]/type[name=templateId]".to_string(),
+					"`templateId` must be an unsigned integer".to_string()
+				),
+				(
+					"types/composite[name=header]".to_string(),
+					"message header has no `version`".to_string()
+				),
+			]
+		);
+	}
+
+	#[test]
+	fn test_header_member_refs() {
+		let schema: Schema = r#"<messageSchema version="0"><types>
+			<type name="u16" primitiveType="uint16"/><type name="i16" primitiveType="int16"/>
+			<composite name="messageHeader"><ref name="blockLength" type="u16"/>
+			<ref name="templateId" type="i16"/><ref name="schemaId" type="uint16"/>
+			<composite name="version"><type name="v" primitiveType="uint16"/></composite></composite>
+			</types></messageSchema>"#
+			.parse()
+			.unwrap();
+		let messages = validate(&schema)
+			.diagnostics()
+			.iter()
+			.map(|d| (d.path.clone(), d.message.clone()))
+			.collect::<Vec<_>>();
+		assert_eq!(
+			messages,
+			[
+				(
+					"types/composite[name=messageHeader]/ref[name=templateId]".to_string(),
+					"`templateId` must be an unsigned integer".to_string()
+				),
+				(
+					"types/composite[name=messageHeader]/composite[name=version]".to_string(),
+					"`version` must be an unsigned integer".to_string()
+				),
+			]
+		);
+	}
+
+	#[test]
+	fn test_nested_type_references() {
+		let schema: Schema = r#"<messageSchema version="0"><types>
+			<composite name="messageHeader"><type name="blockLength" primitiveType="uint16"/>
+			<type name="templateId" primitiveType="uint16"/><type name="schemaId" primitiveType="uint16"/>
+			<type name="version" primitiveType="uint16"/></composite>
+			<composite name="Leg"><composite name="price"><ref name="mantissa" type="Mantissa"/>
+			<enum name="side" encodingType="SideCode"><validValue name="Buy">1</validValue></enum>
+			</composite></composite>
+			</types></messageSchema>"#
+			.parse()
+			.unwrap();
+		let references = validate(&schema)
+			.diagnostics()
+			.iter()
+			.filter(|d| d.rule == "type-references")
+			.map(|d| (d.path.clone(), d.message.clone()))
+			.collect::<Vec<_>>();
+		assert_eq!(
+			references,
+			[
+				(
+					"types/composite[name=Leg]/composite[name=price]/ref[name=mantissa]"
+						.to_string(),
+					"unknown type `Mantissa`".to_string()
+				),
+				(
+					"types/composite[name=Leg]/composite[name=price]/enum[name=side]".to_string(),
+					"encodingType `SideCode` is not a defined <type>".to_string()
+				),
+			]
+		);
+	}
+
+	#[test]
+	fn test_keyword_warning() {
+		let schema: Schema = r#"<messageSchema version="0" headerType="header"><types>
+			<composite name="header"><type name="blockLength" primitiveType="uint16"/>
+			<type name="templateId" primitiveType="uint16"/><type name="schemaId" primitiveType="uint16"/>
+			<type name="version" primitiveType="uint16"/></composite></types><message name="Order" id="1"><field name="type" id="1" type="char"/></message>
+			</messageSchema>"#
+			.parse()
+			.unwrap();
+		let diagnostics = validate(&schema);
+		assert!(!diagnostics.has_errors());
+		assert_eq!(diagnostics.diagnostics()[0].severity, Severity::Warning);
+		assert_eq!(diagnostics.diagnostics()[0].path, "Order/field[id=1]");
+	}
+
+	#[test]
+	fn test_message_named_like_type() {
+		let schema: Schema = r#"<messageSchema version="0"><types>
+			<composite name="messageHeader"><type name="blockLength" primitiveType="uint16"/>
+			<type name="templateId" primitiveType="uint16"/><type name="schemaId" primitiveType="uint16"/>
+			<type name="version" primitiveType="uint16"/></composite>
+			<composite name="Order"><type name="qty" primitiveType="uint32"/></composite>
+			</types><message name="Order" id="1"><field name="order" id="1" type="Order"/></message>
+			</messageSchema>"#
+			.parse()
+			.unwrap();
+		let diagnostics = validate(&schema);
+		assert!(diagnostics.diagnostics().is_empty(), "{:?}", diagnostics.diagnostics());
+	}
+
+	#[test]
+	fn test_type_values() {
+		let schema: Schema = r#"<messageSchema version="0"><types>
+			<composite name="messageHeader"><type name="blockLength" primitiveType="uint16"/>
+			<type name="templateId" primitiveType="uint16"/><type name="schemaId" primitiveType="uint16"/>
+			<type name="version" primitiveType="uint16"/></composite>
+			<type name="Percent" primitiveType="uint8" minValue="10" maxValue="300"/>
+			<type name="Range" primitiveType="int8" minValue="5" maxValue="1" nullValue="x"/>
+			<type name="Code" primitiveType="char" length="2" presence="constant">ABC</type>
+			<enum name="Side" encodingType="char"><validValue name="Buy">Buy</validValue></enum>
+			</types></messageSchema>"#
+			.parse()
+			.unwrap();
+		let errors = validate(&schema)
+			.diagnostics()
+			.iter()
+			.filter(|d| d.rule == "encoding-values")
+			.map(|d| (d.path.clone(), d.message.clone()))
+			.collect::<Vec<_>>();
+		let expected = [
+			("types/type[name=Percent]", "value 300 is outside the range of uint8"),
+			("types/type[name=Range]", "`x` is not a valid int8 value"),
+			("types/type[name=Range]", "minValue 5 is greater than maxValue 1"),
+			("types/type[name=Code]", "value ABC is outside the range of char"),
+			("types/enum[name=Side]/validValue[name=Buy]", "`Buy` is not a valid char value"),
+		];
+		assert_eq!(errors, expected.map(|(path, message)| (path.to_string(), message.to_string())));
+	}
+}
@@ -0,0 +1,604 @@
+//! The SBE rules a schema is validated against.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+	build_vtable,
+	evolution::{join, Element},
+	layout::Layouter,
+	types::{
+		Composite, CompositeMember, EncodingType, EnumType, Presence, PrimitiveType, SetType, Type,
+	},
+	validate::{Diagnostics, Rule, Severity},
+	walk::{members, named_types, Member, Named},
+	LayoutError, Schema, VTable, VTableError, VTableObject, Value,
+};
+
+/// Members every message header must have, they are encoded as unsigned integers.
+const HEADER_MEMBERS: [&str; 4] = ["blockLength", "templateId", "schemaId", "version"];
+
+/// The schema must define the message header composite with all mandatory members.
+pub struct MessageHeaderRule;
+
+impl Rule for MessageHeaderRule {
+	fn name(&self) -> &'static str {
+		"message-header"
+	}
+
+	fn check(&self, schema: &Schema, diagnostics: &mut Diagnostics) {
+		let Some(header) = schema.message_header() else {
+			diagnostics.error(
+				"types",
+				format!("message header composite `{}` not found", schema.header_type_name()),
+			);
+			return;
+		};
+
+		let path = join("types", &header.key());
+		let vtable = build_vtable(schema);
+		let members = header.members();
+		let mut missing = Vec::new();
+		for name in HEADER_MEMBERS {
+			let Some(member) = members.iter().find(|m| m.name() == name) else {
+				missing.push(format!("`{name}`"));
+				continue;
+			};
+			let (key, primitive_type) = match member {
+				CompositeMember::Type(t) => (t.key(), t.primitive_type),
+				CompositeMember::Ref(r) => match vtable.resolve(&r.ref_type) {
+					// dangling refs are reported by the references rule
+					None => continue,
+					Some(VTableObject::Primitive(p)) => (r.key(), Some(*p)),
+					Some(VTableObject::Type(t)) => (r.key(), t.primitive_type),
+					Some(_) => (r.key(), None),
+				},
+				CompositeMember::Enum(e) => (e.key(), None),
+				CompositeMember::Set(s) => (s.key(), None),
+				CompositeMember::Composite(c) => (c.key(), None),
+			};
+			if !matches!(
+				primitive_type,
+				Some(
+					PrimitiveType::Uint8 |
+						PrimitiveType::Uint16 |
+						PrimitiveType::Uint32 |
+						PrimitiveType::Uint64
+				)
+			) {
+				diagnostics
+					.error(join(&path, &key), format!("`{name}` must be an unsigned integer"));
+			}
+		}
+		if !missing.is_empty() {
+			diagnostics.error(path, format!("message header has no {}", missing.join(", ")));
+		}
+	}
+}
+
+/// Message ids must be unique in the schema, member ids must be unique in the message or group
+/// they are declared in.
+pub struct UniqueIdsRule;
+
+impl Rule for UniqueIdsRule {
+	fn name(&self) -> &'static str {
+		"unique-ids"
+	}
+
+	fn check(&self, schema: &Schema, diagnostics: &mut Diagnostics) {
+		let mut message_ids = HashMap::new();
+		for message in schema.messages() {
+			if let Some(first) = message_ids.insert(message.id, &message.name) {
+				diagnostics.error(
+					message.segment(),
+					format!("duplicate message id {}, already used by `{first}`", message.id),
+				);
+			}
+
+			let mut member_ids = HashMap::new();
+			for (path, member) in members(message) {
+				let parent = path.rsplit_once('/').map(|(parent, _)| parent.to_string());
+				if let Some(first) = member_ids.insert((parent, member.id()), member.name()) {
+					diagnostics.error(
+						path,
+						format!("duplicate id {}, already used by `{first}`", member.id()),
+					);
+				}
+			}
+		}
+	}
+}
+
+/// Names of types and messages must be unique and must not shadow primitive types.
+pub struct UniqueNamesRule;
+
+impl Rule for UniqueNamesRule {
+	fn name(&self) -> &'static str {
+		"unique-names"
+	}
+
+	fn check(&self, schema: &Schema, diagnostics: &mut Diagnostics) {
+		for error in build_vtable(schema).errors() {
+			if let VTableError::Duplicate { name, path } = error {
+				diagnostics.error(path.clone(), format!("duplicate name `{name}`"));
+			}
+		}
+	}
+}
+
+/// Every referenced type must be defined and be of a kind the reference allows.
+pub struct TypeReferencesRule;
+
+impl Rule for TypeReferencesRule {
+	fn name(&self) -> &'static str {
+		"type-references"
+	}
+
+	fn check(&self, schema: &Schema, diagnostics: &mut Diagnostics) {
+		let vtable = build_vtable(schema);
+
+		for message in schema.messages() {
+			for (path, member) in members(message) {
+				match member {
+					Member::Field(field) =>
+						if vtable.resolve(&field.r#type).is_none() {
+							diagnostics.error(path, format!("unknown type `{}`", field.r#type));
+						},
+					Member::Group(group) => check_composite_ref(
+						&vtable,
+						&group.dimension_type,
+						"dimensionType",
+						&path,
+						diagnostics,
+					),
+					Member::Data(data) =>
+						check_composite_ref(&vtable, &data.r#type, "type", &path, diagnostics),
+				}
+			}
+		}
+
+		let check_value_ref = |path: String, value_ref: Option<&String>, d: &mut Diagnostics| {
+			let Some(value_ref) = value_ref else { return };
+			let valid = value_ref.split_once('.').is_some_and(|(name, value)| {
+				matches!(vtable.resolve(name), Some(VTableObject::Enum(e))
+					if e.valid_values().any(|v| v.name == value))
+			});
+			if !valid {
+				d.error(path, format!("valueRef `{value_ref}` is not a value of a defined enum"));
+			}
+		};
+
+		for (path, named) in named_types(schema) {
+			match named {
+				Named::Composite(composite) =>
+					check_members(&vtable, &path, composite, &check_value_ref, diagnostics),
+				Named::Enum(e) =>
+					check_encoding_type(&vtable, path, e.encoding_type.as_ref(), diagnostics),
+				Named::Set(s) =>
+					check_encoding_type(&vtable, path, s.encoding_type.as_ref(), diagnostics),
+				Named::Type(t) => check_value_ref(path, t.value_ref.as_ref(), diagnostics),
+			}
+		}
+	}
+}
+
+/// Check the references of the members of the composite, also of nested composites.
+fn check_members(
+	vtable: &VTable,
+	path: &str,
+	composite: &Composite,
+	check_value_ref: &dyn Fn(String, Option<&String>, &mut Diagnostics),
+	diagnostics: &mut Diagnostics,
+) {
+	for member in composite.members() {
+		match member {
+			CompositeMember::Type(t) =>
+				check_value_ref(join(path, &t.key()), t.value_ref.as_ref(), diagnostics),
+			CompositeMember::Ref(r) => {
+				let ref_path = join(path, &r.key());
+				if vtable.resolve(&r.ref_type).is_none() {
+					diagnostics.error(ref_path.clone(), format!("unknown type `{}`", r.ref_type));
+				}
+				check_value_ref(ref_path, r.value_ref.as_ref(), diagnostics);
+			},
+			CompositeMember::Enum(e) => check_encoding_type(
+				vtable,
+				join(path, &e.key()),
+				e.encoding_type.as_ref(),
+				diagnostics,
+			),
+			CompositeMember::Set(s) => check_encoding_type(
+				vtable,
+				join(path, &s.key()),
+				s.encoding_type.as_ref(),
+				diagnostics,
+			),
+			CompositeMember::Composite(c) =>
+				check_members(vtable, &join(path, &c.key()), c, check_value_ref, diagnostics),
+		}
+	}
+}
+
+fn check_encoding_type(
+	vtable: &VTable,
+	path: String,
+	encoding_type: Option<&EncodingType>,
+	diagnostics: &mut Diagnostics,
+) {
+	if let Some(EncodingType::Type(name)) = encoding_type {
+		if !matches!(vtable.resolve(name), Some(VTableObject::Type(_))) {
+			diagnostics.error(path, format!("encodingType `{name}` is not a defined <type>"));
+		}
+	}
+}
+
+fn check_composite_ref(
+	vtable: &VTable,
+	name: &str,
+	attribute: &str,
+	path: &str,
+	diagnostics: &mut Diagnostics,
+) {
+	match vtable.resolve(name) {
+		Some(VTableObject::Composite(_)) => {},
+		Some(_) => diagnostics.error(path, format!("{attribute} `{name}` is not a composite")),
+		None => diagnostics.error(path, format!("unknown type `{name}`")),
+	}
+}
+
+/// No element can be introduced in a version later than the schema version.
+pub struct SinceVersionRule;
+
+impl Rule for SinceVersionRule {
+	fn name(&self) -> &'static str {
+		"since-version"
+	}
+
+	fn check(&self, schema: &Schema, diagnostics: &mut Diagnostics) {
+		let mut check = |path: String, since_version: Option<u32>| {
+			if let Some(since_version) = since_version.filter(|v| *v > schema.version) {
+				diagnostics.error(
+					path,
+					format!(
+						"sinceVersion {since_version} is greater than the schema version {}",
+						schema.version
+					),
+				);
+			}
+		};
+
+		let mut since_versions = Vec::new();
+		for (path, named) in named_types(schema) {
+			type_since_versions(path, named, &mut since_versions);
+		}
+		for (path, since_version) in since_versions {
+			check(path, since_version);
+		}
+		for message in schema.messages() {
+			check(message.segment(), message.since_version);
+			for (path, member) in members(message) {
+				check(path, member.since_version());
+			}
+		}
+	}
+}
+
+/// Paths and `sinceVersion`s of the type and of all its elements, e.g. enum values and members
+/// of composites.
+fn type_since_versions(path: String, named: Named<'_>, found: &mut Vec<(String, Option<u32>)>) {
+	match named {
+		Named::Type(t) => found.push((path, t.since_version)),
+		Named::Enum(e) => {
+			found.push((path.clone(), e.since_version));
+			for v in e.valid_values() {
+				found.push((join(&path, &v.key()), v.since_version));
+			}
+		},
+		Named::Set(s) => {
+			found.push((path.clone(), s.since_version));
+			for c in s.choices() {
+				found.push((join(&path, &c.key()), c.since_version));
+			}
+		},
+		Named::Composite(composite) => {
+			found.push((path.clone(), composite.since_version));
+			for member in composite.members() {
+				let (key, named) = match member {
+					CompositeMember::Type(t) => (t.key(), Named::Type(t)),
+					CompositeMember::Enum(e) => (e.key(), Named::Enum(e)),
+					CompositeMember::Set(s) => (s.key(), Named::Set(s)),
+					CompositeMember::Composite(c) => (c.key(), Named::Composite(c)),
+					CompositeMember::Ref(r) => {
+						found.push((join(&path, &r.key()), r.since_version));
+						continue;
+					},
+				};
+				type_since_versions(join(&path, &key), named, found);
+			}
+		},
+	}
+}
+
+/// Names must be valid identifiers, so code can be generated for them.
+pub struct NamesRule;
+
+/// Keywords of the languages code is commonly generated for.
+#[rustfmt::skip]
+const KEYWORDS: &[&str] = &[
+	"abstract", "as", "async", "await", "break", "case", "catch", "class", "const", "continue",
+	"crate", "default", "do", "dyn", "else", "enum", "extends", "extern", "false", "final", "fn",
+	"for", "if", "impl", "import", "in", "interface", "let", "loop", "match", "mod", "move",
+	"mut", "new", "null", "package", "private", "protected", "pub", "public", "ref", "return",
+	"self", "static", "struct", "super", "switch", "this", "throw", "trait", "true", "try",
+	"type", "unsafe", "use", "where", "while",
+];
+
+impl Rule for NamesRule {
+	fn name(&self) -> &'static str {
+		"names"
+	}
+
+	fn check(&self, schema: &Schema, diagnostics: &mut Diagnostics) {
+		let mut check = |path: String, name: &str| {
+			let mut chars = name.chars();
+			let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_') &&
+				chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+			if !valid {
+				diagnostics.error(path, format!("`{name}` is not a valid name"));
+			} else if KEYWORDS.contains(&name) {
+				diagnostics.warning(path, format!("`{name}` is a keyword in generated code"));
+			}
+		};
+
+		for (path, named) in named_types(schema) {
+			check(path.clone(), named.name());
+			match named {
+				Named::Composite(composite) => {
+					for t in composite.types.iter().flatten() {
+						check(join(&path, &t.key()), &t.name);
+					}
+					for r in composite.refs.iter().flatten() {
+						check(join(&path, &r.key()), &r.name);
+					}
+				},
+				Named::Enum(e) =>
+					for v in e.valid_values() {
+						check(join(&path, &v.key()), &v.name);
+					},
+				Named::Set(s) =>
+					for c in s.choices() {
+						check(join(&path, &c.key()), &c.name);
+					},
+				Named::Type(_) => {},
+			}
+		}
+		for message in schema.messages() {
+			check(message.segment(), &message.name);
+			for (path, member) in members(message) {
+				check(path, member.name());
+			}
+		}
+	}
+}
+
+/// Values of types must be values of their primitive type, enum values must be in the range of
+/// their encoding type and differ from its null value, set choices must fit into the bits of
+/// their encoding type.
+pub struct EncodingValuesRule;
+
+impl Rule for EncodingValuesRule {
+	fn name(&self) -> &'static str {
+		"encoding-values"
+	}
+
+	fn check(&self, schema: &Schema, diagnostics: &mut Diagnostics) {
+		for (path, named) in named_types(schema) {
+			match named {
+				Named::Type(t) => check_type(t, &path, diagnostics),
+				Named::Composite(composite) =>
+					check_composite(schema, composite, &path, diagnostics),
+				Named::Enum(e) => check_enum(schema, e, &path, diagnostics),
+				Named::Set(s) => check_set(schema, s, &path, diagnostics),
+			}
+		}
+	}
+}
+
+fn check_type(t: &Type, path: &str, diagnostics: &mut Diagnostics) {
+	// a type without primitiveType is reported by the layout rule
+	if t.primitive_type.is_none() {
+		return;
+	}
+	let (min, max) = (t.min(), t.max());
+	let errors = [min.clone().err(), max.clone().err(), t.null().err(), t.constant().err()];
+	for error in errors.into_iter().flatten() {
+		diagnostics.error(path, error.to_string());
+	}
+	if let (Ok(min), Ok(max)) = (min, max) {
+		if min.as_f64() > max.as_f64() {
+			diagnostics.error(path, format!("minValue {min} is greater than maxValue {max}"));
+		}
+	}
+}
+
+fn check_composite(schema: &Schema, composite: &Composite, path: &str, d: &mut Diagnostics) {
+	for member in composite.members() {
+		match member {
+			CompositeMember::Type(t) => check_type(t, &join(path, &t.key()), d),
+			CompositeMember::Enum(e) => check_enum(schema, e, &join(path, &e.key()), d),
+			CompositeMember::Set(s) => check_set(schema, s, &join(path, &s.key()), d),
+			CompositeMember::Composite(nested) =>
+				check_composite(schema, nested, &join(path, &nested.key()), d),
+			CompositeMember::Ref(_) => {},
+		}
+	}
+}
+
+fn check_enum(schema: &Schema, e: &EnumType, path: &str, diagnostics: &mut Diagnostics) {
+	let Some(encoding_type) = &e.encoding_type else {
+		diagnostics.error(path, "enum has no encodingType");
+		return;
+	};
+	// unknown encoding type is reported by the type references rule
+	let Some(primitive) = encoding_type.primitive_type(schema) else { return };
+	if primitive.range().is_none() {
+		diagnostics.error(path, format!("enum cannot be encoded as {primitive}"));
+		return;
+	}
+
+	// invalid bounds of the encoding type are reported on the type
+	let standalone = match encoding_type {
+		EncodingType::Type(name) => schema.types().find(|t| &t.name == name),
+		EncodingType::Primitive(_) => None,
+	};
+	let bound = |value: Option<Result<Value, _>>, default: Value| {
+		value.and_then(Result::ok).unwrap_or(default).as_i128().unwrap_or_default()
+	};
+	let min = bound(standalone.map(Type::min), primitive.min_value());
+	let max = bound(standalone.map(Type::max), primitive.max_value());
+	let mut null = bound(standalone.map(Type::null), primitive.null_value());
+	match e.null(primitive) {
+		Ok(Some(value)) => null = value.as_i128().unwrap_or(null),
+		Ok(None) => {},
+		Err(error) => diagnostics.error(path, error.to_string()),
+	}
+
+	for valid_value in e.valid_values() {
+		let value_path = join(path, &valid_value.key());
+		match valid_value.parse(primitive).map(|v| v.as_i128().unwrap_or_default()) {
+			Err(error) => diagnostics.error(value_path, error.to_string()),
+			Ok(value) if value == null =>
+				diagnostics.error(value_path, format!("value {value} is the null value")),
+			Ok(value) if value < min || value > max => diagnostics
+				.error(value_path, format!("value {value} is outside the range {min}..={max}")),
+			Ok(_) => {},
+		}
+	}
+}
+
+fn check_set(schema: &Schema, s: &SetType, path: &str, diagnostics: &mut Diagnostics) {
+	let Some(encoding_type) = &s.encoding_type else {
+		diagnostics.error(path, "set has no encodingType");
+		return;
+	};
+	let Some(primitive) = encoding_type.primitive_type(schema) else { return };
+	if !matches!(
+		primitive,
+		PrimitiveType::Uint8 |
+			PrimitiveType::Uint16 |
+			PrimitiveType::Uint32 |
+			PrimitiveType::Uint64
+	) {
+		diagnostics.error(path, format!("set cannot be encoded as {primitive}"));
+		return;
+	}
+
+	for choice in s.choices() {
+		if choice.bit(primitive).is_err() {
+			diagnostics.error(
+				join(path, &choice.key()),
+				format!("`{}` is not a bit position of {primitive}", choice.value.trim()),
+			);
+		}
+	}
+}
+
+/// Constant types must have a value or reference one, constant fields of other types must
+/// reference one.
+pub struct ConstantValueRule;
+
+impl Rule for ConstantValueRule {
+	fn name(&self) -> &'static str {
+		"constant-value"
+	}
+
+	fn check(&self, schema: &Schema, diagnostics: &mut Diagnostics) {
+		let mut check = |path: String, t: &Type| {
+			if t.presence == Some(Presence::Constant) &&
+				t.value_ref.is_none() &&
+				t.value.as_deref().map_or(true, |v| v.trim().is_empty())
+			{
+				diagnostics.error(path, format!("constant type `{}` has no value", t.name));
+			}
+		};
+
+		for (path, named) in named_types(schema) {
+			match named {
+				Named::Type(t) => check(path, t),
+				Named::Composite(composite) =>
+					for t in composite.types.iter().flatten() {
+						check(join(&path, &t.key()), t);
+					},
+				Named::Enum(_) | Named::Set(_) => {},
+			}
+		}
+
+		// constant fields of a non constant type take the value from the referenced enum value
+		let vtable = build_vtable(schema);
+		for message in schema.messages() {
+			for (path, member) in members(message) {
+				let Member::Field(field) = member else { continue };
+				let constant_type = matches!(
+					vtable.definition(field),
+					Some(VTableObject::Type(t)) if t.presence == Some(Presence::Constant)
+				);
+				if field.presence == Some(Presence::Constant) &&
+					field.value_ref.is_none() &&
+					!constant_type
+				{
+					diagnostics
+						.error(path, format!("constant field `{}` has no valueRef", field.name));
+				}
+			}
+		}
+	}
+}
+
+/// Explicit offsets and block lengths must leave room for the members, composites must not
+/// contain themselves.
+pub struct LayoutRule;
+
+impl Rule for LayoutRule {
+	fn name(&self) -> &'static str {
+		"layout"
+	}
+
+	fn check(&self, schema: &Schema, diagnostics: &mut Diagnostics) {
+		let layouter = Layouter::new(schema);
+		let mut errors = Vec::new();
+		for (_, named) in named_types(schema) {
+			if let Named::Composite(composite) = named {
+				errors.extend(layouter.composite_size(composite, &mut Vec::new()).err());
+			}
+		}
+		let header_length = layouter.header_length(schema).unwrap_or_default();
+		for message in schema.messages() {
+			errors.extend(layouter.message(message, header_length).err());
+		}
+
+		// elements with errors reported by other rules are not reported again
+		let mut reported = diagnostics
+			.diagnostics()
+			.iter()
+			.filter(|d| d.severity == Severity::Error)
+			.map(|d| d.path.clone())
+			.collect::<HashSet<_>>();
+		for error in errors {
+			let (path, message) = match error {
+				// reported by the type references rule
+				LayoutError::Unresolved { .. } => continue,
+				LayoutError::Cycle { name, path } =>
+					(path, format!("composite `{name}` contains itself")),
+				LayoutError::Overlap { offset, end, path } =>
+					(path, format!("offset {offset} overlaps the previous member ending at {end}")),
+				LayoutError::BlockLength { block_length, required, path } => (
+					path,
+					format!("block length {block_length} is less than the {required} bytes of the fields"),
+				),
+				LayoutError::Invalid { message, path } => (path, message),
+			};
+			if reported.insert(path.clone()) {
+				diagnostics.error(path, message);
+			}
+		}
+	}
+}
@@ -0,0 +1,463 @@
+//! Typed values of primitive types, parsed from the text of the schema.
+
+use crate::{
+	types::{ByteOrder, Choice, EncodingType, EnumType, PrimitiveType, Type, ValidValue},
+	Schema,
+};
+use std::fmt;
+use thiserror::Error;
+
+/// A value of a primitive type, or of an array of characters.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+	/// A single character.
+	Char(u8),
+	/// `int8` value.
+	Int8(i8),
+	/// `int16` value.
+	Int16(i16),
+	/// `int32` value.
+	Int32(i32),
+	/// `int64` value.
+	Int64(i64),
+	/// `uint8` value.
+	Uint8(u8),
+	/// `uint16` value.
+	Uint16(u16),
+	/// `uint32` value.
+	Uint32(u32),
+	/// `uint64` value.
+	Uint64(u64),
+	/// `float` value.
+	Float(f32),
+	/// `double` value.
+	Double(f64),
+	/// Array of characters, e.g. the value of a constant string.
+	Chars(String),
+}
+
+/// Errors of values that do not match their primitive type.
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ValueError {
+	/// The text is not a value of the primitive type.
+	#[error("`{value}` is not a valid {primitive} value")]
+	Invalid {
+		/// The text of the value.
+		value: String,
+		/// The primitive type of the value.
+		primitive: PrimitiveType,
+	},
+	/// The value does not fit into the primitive type.
+	#[error("value {value} is outside the range of {primitive}")]
+	OutOfRange {
+		/// The text of the value.
+		value: String,
+		/// The primitive type of the value.
+		primitive: PrimitiveType,
+	},
+	/// The type has no primitive type to parse its values with.
+	#[error("`{name}` has no primitiveType")]
+	Untyped {
+		/// Name of the type.
+		name: String,
+	},
+}
+
+impl Value {
+	/// Parse the text of a single value of the primitive type, `char` values are single
+	/// characters.
+	pub fn parse(primitive: PrimitiveType, text: &str) -> Result<Value, ValueError> {
+		let text = text.trim();
+		let invalid = || ValueError::Invalid { value: text.to_string(), primitive };
+		let out_of_range = || ValueError::OutOfRange { value: text.to_string(), primitive };
+		let integer = || -> Result<i128, ValueError> { text.parse().map_err(|_| invalid()) };
+		let value = match primitive {
+			PrimitiveType::Char => {
+				let mut chars = text.chars();
+				match (chars.next(), chars.next()) {
+					(Some(c), None) => Value::Char(u8::try_from(c).map_err(|_| out_of_range())?),
+					_ => return Err(invalid()),
+				}
+			},
+			PrimitiveType::Int8 => Value::Int8(integer()?.try_into().map_err(|_| out_of_range())?),
+			PrimitiveType::Int16 =>
+				Value::Int16(integer()?.try_into().map_err(|_| out_of_range())?),
+			PrimitiveType::Int32 =>
+				Value::Int32(integer()?.try_into().map_err(|_| out_of_range())?),
+			PrimitiveType::Int64 =>
+				Value::Int64(integer()?.try_into().map_err(|_| out_of_range())?),
+			PrimitiveType::Uint8 =>
+				Value::Uint8(integer()?.try_into().map_err(|_| out_of_range())?),
+			PrimitiveType::Uint16 =>
+				Value::Uint16(integer()?.try_into().map_err(|_| out_of_range())?),
+			PrimitiveType::Uint32 =>
+				Value::Uint32(integer()?.try_into().map_err(|_| out_of_range())?),
+			PrimitiveType::Uint64 =>
+				Value::Uint64(integer()?.try_into().map_err(|_| out_of_range())?),
+			PrimitiveType::Float => {
+				let value = text.parse::<f32>().map_err(|_| invalid())?;
+				// the text parses to infinity when it is too large
+				if value.is_infinite() && !text.to_ascii_lowercase().contains("inf") {
+					return Err(out_of_range());
+				}
+				Value::Float(value)
+			},
+			PrimitiveType::Double => {
+				let value = text.parse::<f64>().map_err(|_| invalid())?;
+				if value.is_infinite() && !text.to_ascii_lowercase().contains("inf") {
+					return Err(out_of_range());
+				}
+				Value::Double(value)
+			},
+		};
+		Ok(value)
+	}
+
+	/// The primitive type of the value, `char` for arrays of characters.
+	pub fn primitive_type(&self) -> PrimitiveType {
+		match self {
+			Value::Char(_) | Value::Chars(_) => PrimitiveType::Char,
+			Value::Int8(_) => PrimitiveType::Int8,
+			Value::Int16(_) => PrimitiveType::Int16,
+			Value::Int32(_) => PrimitiveType::Int32,
+			Value::Int64(_) => PrimitiveType::Int64,
+			Value::Uint8(_) => PrimitiveType::Uint8,
+			Value::Uint16(_) => PrimitiveType::Uint16,
+			Value::Uint32(_) => PrimitiveType::Uint32,
+			Value::Uint64(_) => PrimitiveType::Uint64,
+			Value::Float(_) => PrimitiveType::Float,
+			Value::Double(_) => PrimitiveType::Double,
+		}
+	}
+
+	/// The value of an integer or a single character, `None` for other values.
+	pub fn as_i128(&self) -> Option<i128> {
+		let value = match *self {
+			Value::Char(v) => v.into(),
+			Value::Int8(v) => v.into(),
+			Value::Int16(v) => v.into(),
+			Value::Int32(v) => v.into(),
+			Value::Int64(v) => v.into(),
+			Value::Uint8(v) => v.into(),
+			Value::Uint16(v) => v.into(),
+			Value::Uint32(v) => v.into(),
+			Value::Uint64(v) => v.into(),
+			Value::Float(_) | Value::Double(_) | Value::Chars(_) => return None,
+		};
+		Some(value)
+	}
+
+	/// The value as a double, `None` for arrays of characters.
+	pub fn as_f64(&self) -> Option<f64> {
+		match *self {
+			Value::Float(v) => Some(v.into()),
+			Value::Double(v) => Some(v),
+			// integers beyond 2^53 lose precision, as in any double
+			_ => self.as_i128().map(|v| v as f64),
+		}
+	}
+
+	/// Read a value of the primitive type from the start of the bytes in the byte order, `None`
+	/// if there are too few bytes.
+	pub fn read(primitive: PrimitiveType, bytes: &[u8], order: ByteOrder) -> Option<Value> {
+		macro_rules! read {
+			($t:ty) => {{
+				let bytes = bytes.get(..std::mem::size_of::<$t>())?.try_into().ok()?;
+				match order {
+					ByteOrder::LittleEndian => <$t>::from_le_bytes(bytes),
+					ByteOrder::BigEndian => <$t>::from_be_bytes(bytes),
+				}
+			}};
+		}
+		let value = match primitive {
+			PrimitiveType::Char => Value::Char(*bytes.first()?),
+			PrimitiveType::Int8 => Value::Int8(read!(i8)),
+			PrimitiveType::Int16 => Value::Int16(read!(i16)),
+			PrimitiveType::Int32 => Value::Int32(read!(i32)),
+			PrimitiveType::Int64 => Value::Int64(read!(i64)),
+			PrimitiveType::Uint8 => Value::Uint8(read!(u8)),
+			PrimitiveType::Uint16 => Value::Uint16(read!(u16)),
+			PrimitiveType::Uint32 => Value::Uint32(read!(u32)),
+			PrimitiveType::Uint64 => Value::Uint64(read!(u64)),
+			PrimitiveType::Float => Value::Float(read!(f32)),
+			PrimitiveType::Double => Value::Double(read!(f64)),
+		};
+		Some(value)
+	}
+
+	/// The bytes of the value in the byte order, as [`Value::read`] reads them.
+	pub fn to_bytes(&self, order: ByteOrder) -> Vec<u8> {
+		macro_rules! bytes {
+			($v:expr) => {
+				match order {
+					ByteOrder::LittleEndian => $v.to_le_bytes().to_vec(),
+					ByteOrder::BigEndian => $v.to_be_bytes().to_vec(),
+				}
+			};
+		}
+		match self {
+			Value::Char(v) => vec![*v],
+			Value::Int8(v) => bytes!(v),
+			Value::Int16(v) => bytes!(v),
+			Value::Int32(v) => bytes!(v),
+			Value::Int64(v) => bytes!(v),
+			Value::Uint8(v) => bytes!(v),
+			Value::Uint16(v) => bytes!(v),
+			Value::Uint32(v) => bytes!(v),
+			Value::Uint64(v) => bytes!(v),
+			Value::Float(v) => bytes!(v),
+			Value::Double(v) => bytes!(v),
+			Value::Chars(v) => v.as_bytes().to_vec(),
+		}
+	}
+
+	/// Whether the values are equal, also if both are NaN as the null of floating point types.
+	pub fn same(&self, other: &Value) -> bool {
+		match (self.as_f64(), other.as_f64()) {
+			(Some(a), Some(b)) if a.is_nan() && b.is_nan() => true,
+			_ => self == other,
+		}
+	}
+}
+
+impl fmt::Display for Value {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Value::Char(v) => write!(f, "{}", char::from(*v)),
+			Value::Int8(v) => write!(f, "{v}"),
+			Value::Int16(v) => write!(f, "{v}"),
+			Value::Int32(v) => write!(f, "{v}"),
+			Value::Int64(v) => write!(f, "{v}"),
+			Value::Uint8(v) => write!(f, "{v}"),
+			Value::Uint16(v) => write!(f, "{v}"),
+			Value::Uint32(v) => write!(f, "{v}"),
+			Value::Uint64(v) => write!(f, "{v}"),
+			Value::Float(v) => write!(f, "{v}"),
+			Value::Double(v) => write!(f, "{v}"),
+			Value::Chars(v) => write!(f, "{v}"),
+		}
+	}
+}
+
+impl PrimitiveType {
+	/// SBE's default null value: the most negative signed integer, the largest unsigned
+	/// integer, NaN for floating point and 0 for `char`.
+	pub fn null_value(&self) -> Value {
+		match self {
+			PrimitiveType::Char => Value::Char(0),
+			PrimitiveType::Int8 => Value::Int8(i8::MIN),
+			PrimitiveType::Int16 => Value::Int16(i16::MIN),
+			PrimitiveType::Int32 => Value::Int32(i32::MIN),
+			PrimitiveType::Int64 => Value::Int64(i64::MIN),
+			PrimitiveType::Uint8 => Value::Uint8(u8::MAX),
+			PrimitiveType::Uint16 => Value::Uint16(u16::MAX),
+			PrimitiveType::Uint32 => Value::Uint32(u32::MAX),
+			PrimitiveType::Uint64 => Value::Uint64(u64::MAX),
+			PrimitiveType::Float => Value::Float(f32::NAN),
+			PrimitiveType::Double => Value::Double(f64::NAN),
+		}
+	}
+
+	/// SBE's default minimum value, signed integers leave the most negative value for null.
+	pub fn min_value(&self) -> Value {
+		match self {
+			PrimitiveType::Char => Value::Char(0x20),
+			PrimitiveType::Int8 => Value::Int8(i8::MIN + 1),
+			PrimitiveType::Int16 => Value::Int16(i16::MIN + 1),
+			PrimitiveType::Int32 => Value::Int32(i32::MIN + 1),
+			PrimitiveType::Int64 => Value::Int64(i64::MIN + 1),
+			PrimitiveType::Uint8 => Value::Uint8(0),
+			PrimitiveType::Uint16 => Value::Uint16(0),
+			PrimitiveType::Uint32 => Value::Uint32(0),
+			PrimitiveType::Uint64 => Value::Uint64(0),
+			PrimitiveType::Float => Value::Float(f32::MIN),
+			PrimitiveType::Double => Value::Double(f64::MIN),
+		}
+	}
+
+	/// SBE's default maximum value, unsigned integers leave the largest value for null.
+	pub fn max_value(&self) -> Value {
+		match self {
+			PrimitiveType::Char => Value::Char(0x7e),
+			PrimitiveType::Int8 => Value::Int8(i8::MAX),
+			PrimitiveType::Int16 => Value::Int16(i16::MAX),
+			PrimitiveType::Int32 => Value::Int32(i32::MAX),
+			PrimitiveType::Int64 => Value::Int64(i64::MAX),
+			PrimitiveType::Uint8 => Value::Uint8(u8::MAX - 1),
+			PrimitiveType::Uint16 => Value::Uint16(u16::MAX - 1),
+			PrimitiveType::Uint32 => Value::Uint32(u32::MAX - 1),
+			PrimitiveType::Uint64 => Value::Uint64(u64::MAX - 1),
+			PrimitiveType::Float => Value::Float(f32::MAX),
+			PrimitiveType::Double => Value::Double(f64::MAX),
+		}
+	}
+}
+
+impl Type {
+	fn primitive(&self) -> Result<PrimitiveType, ValueError> {
+		self.primitive_type
+			.ok_or_else(|| ValueError::Untyped { name: self.name.clone() })
+	}
+
+	/// The `minValue`, or the default minimum of the primitive type.
+	pub fn min(&self) -> Result<Value, ValueError> {
+		let primitive = self.primitive()?;
+		self.min_value
+			.as_deref()
+			.map_or(Ok(primitive.min_value()), |v| Value::parse(primitive, v))
+	}
+
+	/// The `maxValue`, or the default maximum of the primitive type.
+	pub fn max(&self) -> Result<Value, ValueError> {
+		let primitive = self.primitive()?;
+		self.max_value
+			.as_deref()
+			.map_or(Ok(primitive.max_value()), |v| Value::parse(primitive, v))
+	}
+
+	/// The `nullValue`, or the default null of the primitive type.
+	pub fn null(&self) -> Result<Value, ValueError> {
+		let primitive = self.primitive()?;
+		self.null_value
+			.as_deref()
+			.map_or(Ok(primitive.null_value()), |v| Value::parse(primitive, v))
+	}
+
+	/// The value of a constant type, `None` if the type has no value. A `char` constant
+	/// longer than one character is an array of characters that must fit the length.
+	pub fn constant(&self) -> Result<Option<Value>, ValueError> {
+		let primitive = self.primitive()?;
+		let Some(text) = self.value.as_deref().map(str::trim).filter(|v| !v.is_empty()) else {
+			return Ok(None);
+		};
+		if primitive != PrimitiveType::Char || (text.chars().count() == 1 && self.length <= Some(1))
+		{
+			return Value::parse(primitive, text).map(Some);
+		}
+		let out_of_range = || ValueError::OutOfRange { value: text.to_string(), primitive };
+		if !text.is_ascii() || self.length.is_some_and(|length| text.len() > length as usize) {
+			return Err(out_of_range());
+		}
+		Ok(Some(Value::Chars(text.to_string())))
+	}
+}
+
+impl EncodingType {
+	/// The primitive type of the encoding, looking up a standalone `<type>` in the schema.
+	pub fn primitive_type(&self, schema: &Schema) -> Option<PrimitiveType> {
+		match self {
+			EncodingType::Primitive(primitive) => Some(*primitive),
+			EncodingType::Type(name) =>
+				schema.types().find(|t| &t.name == name).and_then(|t| t.primitive_type),
+		}
+	}
+}
+
+impl EnumType {
+	/// The `nullValue` of the enum parsed as its encoding primitive, `None` if not set.
+	pub fn null(&self, primitive: PrimitiveType) -> Result<Option<Value>, ValueError> {
+		self.null_value.as_deref().map(|v| Value::parse(primitive, v)).transpose()
+	}
+}
+
+impl ValidValue {
+	/// The value parsed as the encoding primitive of the enum.
+	pub fn parse(&self, primitive: PrimitiveType) -> Result<Value, ValueError> {
+		Value::parse(primitive, &self.value)
+	}
+}
+
+impl Choice {
+	/// The bit position of the choice, it must be within the bits of the encoding primitive.
+	pub fn bit(&self, primitive: PrimitiveType) -> Result<u32, ValueError> {
+		let text = self.value.trim();
+		let bit = text
+			.parse::<u32>()
+			.map_err(|_| ValueError::Invalid { value: text.to_string(), primitive })?;
+		if bit >= primitive.size() * 8 {
+			return Err(ValueError::OutOfRange { value: text.to_string(), primitive });
+		}
+		Ok(bit)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_parse_values() {
+		assert_eq!(Value::parse(PrimitiveType::Int8, " -9 "), Ok(Value::Int8(-9)));
+		assert_eq!(Value::parse(PrimitiveType::Char, "B"), Ok(Value::Char(b'B')));
+		assert_eq!(
+			Value::parse(PrimitiveType::Uint64, "18446744073709551615"),
+			Ok(Value::Uint64(u64::MAX))
+		);
+		assert_eq!(Value::parse(PrimitiveType::Double, "1.5"), Ok(Value::Double(1.5)));
+		assert_eq!(
+			Value::parse(PrimitiveType::Uint8, "256"),
+			Err(ValueError::OutOfRange { value: "256".into(), primitive: PrimitiveType::Uint8 })
+		);
+		assert_eq!(
+			Value::parse(PrimitiveType::Char, "AB"),
+			Err(ValueError::Invalid { value: "AB".into(), primitive: PrimitiveType::Char })
+		);
+		assert!(matches!(
+			Value::parse(PrimitiveType::Float, "1e39"),
+			Err(ValueError::OutOfRange { .. })
+		));
+	}
+
+	#[test]
+	fn test_defaults() {
+		assert_eq!(PrimitiveType::Int32.null_value(), Value::Int32(i32::MIN));
+		assert_eq!(PrimitiveType::Uint16.max_value(), Value::Uint16(65534));
+		assert_eq!(PrimitiveType::Char.min_value().as_i128(), Some(0x20));
+
+		let t = Type {
+			name: "Percent".into(),
+			primitive_type: Some(PrimitiveType::Uint8),
+			max_value: Some("100".into()),
+			..Default::default()
+		};
+		assert_eq!(t.min(), Ok(Value::Uint8(0)));
+		assert_eq!(t.max(), Ok(Value::Uint8(100)));
+		assert_eq!(t.null(), Ok(Value::Uint8(255)));
+	}
+
+	#[test]
+	fn test_constants() {
+		let constant = |primitive, length, value: &str| Type {
+			name: "c".into(),
+			primitive_type: Some(primitive),
+			length,
+			value: Some(value.into()),
+			..Default::default()
+		};
+		assert_eq!(
+			constant(PrimitiveType::Char, None, "Petrol").constant(),
+			Ok(Some(Value::Chars("Petrol".into())))
+		);
+		assert_eq!(
+			constant(PrimitiveType::Char, Some(1), "8").constant(),
+			Ok(Some(Value::Char(b'8')))
+		);
+		assert!(constant(PrimitiveType::Char, Some(2), "abc").constant().is_err());
+		assert_eq!(constant(PrimitiveType::Int8, None, "-9").constant(), Ok(Some(Value::Int8(-9))));
+		assert_eq!(constant(PrimitiveType::Int8, None, "").constant(), Ok(None));
+	}
+
+	#[test]
+	fn test_bytes() {
+		let value = Value::Uint16(0x0102);
+		assert_eq!(value.to_bytes(ByteOrder::LittleEndian), [2, 1]);
+		assert_eq!(value.to_bytes(ByteOrder::BigEndian), [1, 2]);
+		assert_eq!(Value::read(PrimitiveType::Uint16, &[1, 2], ByteOrder::BigEndian), Some(value));
+		let bytes = Value::Double(-1.5).to_bytes(ByteOrder::BigEndian);
+		assert_eq!(
+			Value::read(PrimitiveType::Double, &bytes, ByteOrder::BigEndian),
+			Some(Value::Double(-1.5))
+		);
+		assert_eq!(Value::read(PrimitiveType::Int32, &[0; 3], ByteOrder::LittleEndian), None);
+	}
+}
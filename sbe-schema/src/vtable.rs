@@ -0,0 +1,310 @@
+//! Lookup table of all named entities of a schema.
+
+use std::collections::{hash_map::Entry, HashMap};
+
+use thiserror::Error;
+
+use crate::{
+	evolution::{join, Element},
+	types::{
+		Composite, CompositeMember, Data, EncodingType, EnumType, Field, Group, Message,
+		PrimitiveType, Ref, SetType, Type,
+	},
+	walk::{members, named_types, Member, Named},
+	Schema,
+};
+
+/// Problems found while building the vtable.
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum VTableError {
+	/// The name is defined more than once, the first definition is kept.
+	#[error("{path}: duplicate definition of `{name}`")]
+	Duplicate {
+		/// The duplicated name.
+		name: String,
+		/// Path of the duplicate definition.
+		path: String,
+	},
+	/// The referenced name is not defined.
+	#[error("{path}: unresolved type `{name}`")]
+	Unresolved {
+		/// The referenced name.
+		name: String,
+		/// Path of the element with the reference.
+		path: String,
+	},
+}
+
+/// Named entity of the schema.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VTableObject<'a> {
+	/// Built-in primitive type.
+	Primitive(PrimitiveType),
+	/// `<composite>` type.
+	Composite(&'a Composite),
+	/// `<enum>` type.
+	Enum(&'a EnumType),
+	/// `<set>` type.
+	Set(&'a SetType),
+	/// Standalone `<type>`.
+	Type(&'a Type),
+}
+
+/// Element referencing a type by name.
+pub trait TypeReference {
+	/// Name of the referenced type.
+	fn type_name(&self) -> &str;
+}
+
+impl TypeReference for Field {
+	fn type_name(&self) -> &str {
+		&self.r#type
+	}
+}
+
+impl TypeReference for Ref {
+	fn type_name(&self) -> &str {
+		&self.ref_type
+	}
+}
+
+impl TypeReference for Data {
+	fn type_name(&self) -> &str {
+		&self.r#type
+	}
+}
+
+impl TypeReference for Group {
+	fn type_name(&self) -> &str {
+		&self.dimension_type
+	}
+}
+
+/// VTable for lookups of types, primitive types and messages by name. Messages have their own
+/// names, a message may have the name of a type.
+#[derive(Debug, PartialEq)]
+pub struct VTable<'a> {
+	objects: HashMap<String, VTableObject<'a>>,
+	messages: HashMap<String, &'a Message>,
+	errors: Vec<VTableError>,
+}
+
+impl<'a> VTable<'a> {
+	/// Create a new VTable containing the primitive types.
+	pub fn new() -> Self {
+		let objects = PrimitiveType::ALL
+			.into_iter()
+			.map(|p| (p.name().to_string(), VTableObject::Primitive(p)))
+			.collect();
+		Self { objects, messages: HashMap::new(), errors: Vec::new() }
+	}
+
+	/// Add object to the vtable, a duplicate name is recorded as an error and the first
+	/// definition is kept.
+	pub fn add(&mut self, name: String, path: String, obj: VTableObject<'a>) {
+		match self.objects.entry(name) {
+			Entry::Occupied(entry) =>
+				self.errors.push(VTableError::Duplicate { name: entry.key().clone(), path }),
+			Entry::Vacant(entry) => {
+				entry.insert(obj);
+			},
+		}
+	}
+
+	/// Add message to the vtable, a duplicate name is recorded as an error and the first
+	/// definition is kept.
+	pub fn add_message(&mut self, path: String, message: &'a Message) {
+		match self.messages.entry(message.name.clone()) {
+			Entry::Occupied(entry) =>
+				self.errors.push(VTableError::Duplicate { name: entry.key().clone(), path }),
+			Entry::Vacant(entry) => {
+				entry.insert(message);
+			},
+		}
+	}
+
+	/// Find the type with the given name.
+	pub fn resolve(&self, name: &str) -> Option<&VTableObject<'a>> {
+		self.objects.get(name)
+	}
+
+	/// Find the message with the given name.
+	pub fn message(&self, name: &str) -> Option<&'a Message> {
+		self.messages.get(name).copied()
+	}
+
+	/// Follow the reference to the definition of the referenced type.
+	pub fn definition(&self, reference: &impl TypeReference) -> Option<&VTableObject<'a>> {
+		self.resolve(reference.type_name())
+	}
+
+	/// Duplicate definitions and unresolved references found in the schema.
+	pub fn errors(&self) -> &[VTableError] {
+		&self.errors
+	}
+
+	fn check_reference(&mut self, name: &str, path: String) {
+		if !self.objects.contains_key(name) {
+			self.errors.push(VTableError::Unresolved { name: name.to_string(), path });
+		}
+	}
+}
+
+impl<'a> Default for VTable<'a> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Build vtable for lookups of all types and messages of the schema.
+pub fn build_vtable(schema: &Schema) -> VTable {
+	let mut vtable = VTable::new();
+	for (path, named) in named_types(schema) {
+		let object = match named {
+			Named::Type(t) => VTableObject::Type(t),
+			Named::Composite(c) => VTableObject::Composite(c),
+			Named::Enum(e) => VTableObject::Enum(e),
+			Named::Set(s) => VTableObject::Set(s),
+		};
+		vtable.add(named.name().to_string(), path, object);
+	}
+	for message in schema.messages() {
+		vtable.add_message(message.segment(), message);
+	}
+
+	for (path, named) in named_types(schema) {
+		let encoding_type = match named {
+			Named::Composite(composite) => {
+				check_composite(&mut vtable, &path, composite);
+				None
+			},
+			Named::Enum(e) => e.encoding_type.as_ref(),
+			Named::Set(s) => s.encoding_type.as_ref(),
+			Named::Type(_) => None,
+		};
+		if let Some(EncodingType::Type(name)) = encoding_type {
+			vtable.check_reference(name, path);
+		}
+	}
+	for message in schema.messages() {
+		for (path, member) in members(message) {
+			let name = match member {
+				Member::Field(f) => f.type_name(),
+				Member::Group(g) => g.type_name(),
+				Member::Data(d) => d.type_name(),
+			};
+			vtable.check_reference(name, path);
+		}
+	}
+
+	vtable
+}
+
+/// Check the refs and the encoding types of the members of the composite, also of nested
+/// composites.
+fn check_composite(vtable: &mut VTable, path: &str, composite: &Composite) {
+	for member in composite.members() {
+		let (encoding_type, path) = match member {
+			CompositeMember::Type(_) => continue,
+			CompositeMember::Ref(r) => {
+				vtable.check_reference(r.type_name(), join(path, &r.key()));
+				continue;
+			},
+			CompositeMember::Enum(e) => (e.encoding_type.as_ref(), join(path, &e.key())),
+			CompositeMember::Set(s) => (s.encoding_type.as_ref(), join(path, &s.key())),
+			CompositeMember::Composite(c) => {
+				check_composite(vtable, &join(path, &c.key()), c);
+				continue;
+			},
+		};
+		if let Some(EncodingType::Type(name)) = encoding_type {
+			vtable.check_reference(name, path);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_build_vtable() {
+		let schema: Schema = r#"<messageSchema version="0">
+			<types>
+				<type name="Symbol" primitiveType="char" length="8"/>
+				<composite name="messageHeader"><type name="version" primitiveType="uint16"/></composite>
+				<composite name="Price"><ref name="sym" type="Symbol"/><ref name="qty" type="Qty"/></composite>
+				<enum name="Side" encodingType="char"><validValue name="Buy">1</validValue></enum>
+				<set name="Flags" encodingType="uint8"><choice name="A">0</choice></set>
+				<type name="Symbol" primitiveType="char" length="4"/>
+			</types>
+			<message name="Order" id="1">
+				<field name="side" id="1" type="Side"/>
+				<field name="px" id="2" type="Price"/>
+				<field name="qty" id="3" type="uint32"/>
+			</message>
+		</messageSchema>"#
+			.parse()
+			.unwrap();
+		let vtable = build_vtable(&schema);
+
+		assert!(
+			matches!(vtable.resolve("Symbol"), Some(VTableObject::Type(t)) if t.length == Some(8))
+		);
+		assert!(matches!(vtable.resolve("Flags"), Some(VTableObject::Set(_))));
+		assert!(vtable.resolve("Order").is_none());
+		assert!(vtable.message("Order").is_some());
+		assert_eq!(vtable.resolve("uint8"), Some(&VTableObject::Primitive(PrimitiveType::Uint8)));
+
+		let fields = schema.messages.as_ref().unwrap()[0].fields.as_ref().unwrap();
+		assert!(matches!(vtable.definition(&fields[0]), Some(VTableObject::Enum(_))));
+		assert!(matches!(vtable.definition(&fields[1]), Some(VTableObject::Composite(_))));
+		assert!(matches!(vtable.definition(&fields[2]), Some(VTableObject::Primitive(_))));
+
+		assert_eq!(
+			vtable.errors(),
+			&[
+				VTableError::Duplicate {
+					name: "Symbol".to_string(),
+					path: "types/type[name=Symbol]".to_string()
+				},
+				VTableError::Unresolved {
+					name: "Qty".to_string(),
+					path: "types/composite[name=Price]/ref[name=qty]".to_string()
+				},
+			]
+		);
+	}
+
+	#[test]
+	fn test_nested_references() {
+		let schema: Schema = r#"<messageSchema version="0">
+			<types>
+				<type name="Qty" primitiveType="uint32"/>
+				<composite name="Order">
+					<composite name="leg">
+						<ref name="qty" type="Qty"/>
+						<ref name="px" type="Price"/>
+						<composite name="venue"><ref name="mic" type="Mic"/></composite>
+					</composite>
+					<enum name="side" encodingType="SideCode"><validValue name="Buy">1</validValue></enum>
+					<set name="flags" encodingType="Qty"><choice name="A">0</choice></set>
+					<set name="more" encodingType="Bits"><choice name="B">0</choice></set>
+				</composite>
+			</types>
+		</messageSchema>"#
+			.parse()
+			.unwrap();
+		let unresolved =
+			build_vtable(&schema).errors().iter().map(|e| e.to_string()).collect::<Vec<_>>();
+		assert_eq!(
+			unresolved,
+			[
+				"types/composite[name=Order]/composite[name=leg]/ref[name=px]: unresolved type `Price`",
+				"types/composite[name=Order]/composite[name=leg]/composite[name=venue]/ref[name=mic]: unresolved type `Mic`",
+				"types/composite[name=Order]/enum[name=side]: unresolved type `SideCode`",
+				"types/composite[name=Order]/set[name=more]: unresolved type `Bits`",
+			]
+		);
+	}
+}
@@ -0,0 +1,99 @@
+//! Walking the elements of a schema together with their paths.
+
+use crate::{
+	evolution::{join, Element},
+	types::{Composite, Data, EnumType, Field, Group, Message, SetType, Type},
+	Schema,
+};
+
+/// Type defined directly in the `<types>` section.
+#[derive(Clone, Copy)]
+pub(crate) enum Named<'a> {
+	Type(&'a Type),
+	Composite(&'a Composite),
+	Enum(&'a EnumType),
+	Set(&'a SetType),
+}
+
+impl<'a> Named<'a> {
+	pub(crate) fn name(&self) -> &'a str {
+		match self {
+			Named::Type(t) => &t.name,
+			Named::Composite(c) => &c.name,
+			Named::Enum(e) => &e.name,
+			Named::Set(s) => &s.name,
+		}
+	}
+}
+
+/// All types of all `<types>` sections with their paths.
+pub(crate) fn named_types(schema: &Schema) -> Vec<(String, Named<'_>)> {
+	let mut named = Vec::new();
+	let path = |element: &dyn Element| join("types", &element.key());
+	named.extend(schema.types().map(|t| (path(t), Named::Type(t))));
+	named.extend(schema.composites().map(|c| (path(c), Named::Composite(c))));
+	named.extend(schema.enums().map(|e| (path(e), Named::Enum(e))));
+	named.extend(schema.sets().map(|s| (path(s), Named::Set(s))));
+	named
+}
+
+/// Field, group or var data of a message.
+pub(crate) enum Member<'a> {
+	Field(&'a Field),
+	Group(&'a Group),
+	Data(&'a Data),
+}
+
+impl<'a> Member<'a> {
+	pub(crate) fn name(&self) -> &'a str {
+		match self {
+			Member::Field(f) => &f.name,
+			Member::Group(g) => &g.name,
+			Member::Data(d) => &d.name,
+		}
+	}
+
+	pub(crate) fn id(&self) -> u32 {
+		match self {
+			Member::Field(f) => f.id,
+			Member::Group(g) => g.id,
+			Member::Data(d) => d.id,
+		}
+	}
+
+	pub(crate) fn since_version(&self) -> Option<u32> {
+		match self {
+			Member::Field(f) => f.since_version,
+			Member::Group(g) => g.since_version,
+			Member::Data(d) => d.since_version,
+		}
+	}
+}
+
+/// All members of the message, including members of its groups at any depth, with their paths.
+pub(crate) fn members(message: &Message) -> Vec<(String, Member<'_>)> {
+	let mut members = Vec::new();
+	let path = message.segment();
+	members.extend(
+		message
+			.fields
+			.iter()
+			.flatten()
+			.map(|f| (join(&path, &f.key()), Member::Field(f))),
+	);
+	for group in message.groups() {
+		group_members(&path, group, &mut members);
+	}
+	members.extend(message.data().map(|d| (join(&path, &d.key()), Member::Data(d))));
+	members
+}
+
+fn group_members<'a>(parent: &str, group: &'a Group, members: &mut Vec<(String, Member<'a>)>) {
+	let path = join(parent, &group.key());
+	members.push((path.clone(), Member::Group(group)));
+	members.extend(group.fields().map(|f| (join(&path, &f.key()), Member::Field(f))));
+	for nested in group.groups() {
+		group_members(&path, nested, members);
+	}
+	members.extend(group.data().map(|d| (join(&path, &d.key()), Member::Data(d))));
+}
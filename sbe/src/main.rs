@@ -12,7 +12,9 @@
 //!
 //! ```shell
 //! sbe schema generate -l rust -f schema.xml
+//! sbe decode --schema schema.xml message.hex
 //! ```
+mod message;
 mod schema;
 mod term;
 mod tool;
@@ -36,6 +38,9 @@ enum Commands {
 	/// specify the path to the java executable.
 	#[command(subcommand)]
 	Tool(tool::Commands),
+	/// Decode and encode SBE messages with a schema, without generated code
+	#[command(flatten)]
+	Message(message::Commands),
 }
 
 fn main() {
@@ -44,6 +49,7 @@ fn main() {
 	let result = match cli.command {
 		Commands::Schema(args) => schema::handle(args),
 		Commands::Tool(args) => tool::handle(args),
+		Commands::Message(args) => message::handle(args),
 	};
 
 	if let Err(e) = &result {
@@ -0,0 +1,74 @@
+use std::path::PathBuf;
+
+use anyhow::{bail, Context, Result};
+use clap::{command, Parser};
+use sbe_schema::{ByteOrder, Decoder};
+use serde_json::json;
+
+use crate::{
+	message::{parse_bytes, read_input, BinaryFormat, SchemaArgs},
+	schema::load,
+};
+
+/// Decode SBE messages to JSON.
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+pub struct DecodeArgs {
+	#[command(flatten)]
+	pub schema: SchemaArgs,
+	/// file with the encoded message, default stdin
+	pub input: Option<PathBuf>,
+	/// format of the input, detected from the input by default
+	#[arg(long, value_enum)]
+	pub format: Option<BinaryFormat>,
+	/// the input is a stream of messages, each preceded by its length as uint32 in the byte
+	/// order of the schema; prints one JSON object with the message name and value per line
+	#[arg(long)]
+	pub stream: bool,
+	/// print a hex dump with the element of the schema every byte belongs to instead of JSON
+	#[arg(long, conflicts_with = "stream")]
+	pub annotate: bool,
+}
+
+pub fn run(args: DecodeArgs) -> Result<()> {
+	let schema = load(&args.schema.schema, &args.schema.include_paths)?;
+	let buf = parse_bytes(read_input(args.input.as_deref())?, args.format)?;
+	let decoder = Decoder::new(&schema)?;
+
+	if args.annotate {
+		print!("{}", decoder.annotate(&buf)?);
+		return Ok(());
+	}
+	if !args.stream {
+		let decoded = decoder.decode(&buf)?;
+		println!("{}", serde_json::to_string_pretty(&decoded.value)?);
+		return Ok(());
+	}
+
+	let mut offset = 0;
+	while offset < buf.len() {
+		let Some(prefix) = buf.get(offset..offset + 4) else {
+			bail!("truncated length prefix at offset {offset}");
+		};
+		let prefix = prefix.try_into()?;
+		let length = match schema.byte_order() {
+			ByteOrder::LittleEndian => u32::from_le_bytes(prefix),
+			ByteOrder::BigEndian => u32::from_be_bytes(prefix),
+		} as usize;
+		let start = offset + 4;
+		let Some(message) = buf.get(start..start + length) else {
+			bail!("truncated message of {length} bytes at offset {start}");
+		};
+		let decoded =
+			decoder.decode(message).with_context(|| format!("message at offset {start}"))?;
+		if decoded.length != length {
+			bail!(
+				"message at offset {start} is {} bytes long but its length prefix is {length}",
+				decoded.length
+			);
+		}
+		println!("{}", json!({ "message": decoded.name, "value": decoded.value }));
+		offset = start + length;
+	}
+	Ok(())
+}
@@ -0,0 +1,52 @@
+use std::{
+	fs,
+	io::{self, Write},
+	path::PathBuf,
+};
+
+use anyhow::{Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use clap::{command, Parser};
+use serde_json::Value;
+
+use crate::{
+	message::{read_input, BinaryFormat, SchemaArgs},
+	schema::load,
+};
+
+/// Encode an SBE message from JSON.
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+pub struct EncodeArgs {
+	#[command(flatten)]
+	pub schema: SchemaArgs,
+	/// name or template id of the message
+	#[arg(long, short)]
+	pub message: String,
+	/// JSON file with the value of the message, default stdin
+	pub input: Option<PathBuf>,
+	/// format of the encoded message
+	#[arg(long, value_enum, default_value_t = BinaryFormat::Raw)]
+	pub format: BinaryFormat,
+	/// file to write the encoded message to, default stdout
+	#[arg(long, short)]
+	pub output: Option<PathBuf>,
+}
+
+pub fn run(args: EncodeArgs) -> Result<()> {
+	let schema = load(&args.schema.schema, &args.schema.include_paths)?;
+	let value: Value =
+		serde_json::from_slice(&read_input(args.input.as_deref())?).context("input is not JSON")?;
+	let buf = sbe_schema::encode(&schema, &args.message, &value)?;
+
+	let output = match args.format {
+		BinaryFormat::Raw => buf,
+		BinaryFormat::Hex => format!("{}\n", hex::encode(buf)).into_bytes(),
+		BinaryFormat::Base64 => format!("{}\n", STANDARD.encode(buf)).into_bytes(),
+	};
+	match &args.output {
+		Some(file) =>
+			fs::write(file, output).with_context(|| format!("failed to write {}", file.display())),
+		None => Ok(io::stdout().write_all(&output)?),
+	}
+}
@@ -0,0 +1,140 @@
+//!
+//! Decoding and encoding of messages driven by the schema, without generated code
+mod decode;
+mod encode;
+
+use std::{
+	fs,
+	io::{self, Read},
+	path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use clap::{Subcommand, ValueEnum};
+
+#[derive(Subcommand)]
+pub enum Commands {
+	/// Decode SBE messages to JSON
+	Decode(decode::DecodeArgs),
+	/// Encode an SBE message from JSON
+	Encode(encode::EncodeArgs),
+}
+
+/// Encoding of the bytes of messages
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum BinaryFormat {
+	/// the bytes as they are
+	Raw,
+	/// hex digits, whitespace is ignored
+	Hex,
+	/// standard base64, whitespace is ignored
+	Base64,
+}
+
+pub fn handle(command: Commands) -> Result<()> {
+	match command {
+		Commands::Decode(args) => decode::run(args),
+		Commands::Encode(args) => encode::run(args),
+	}
+}
+
+/// Read the file, or stdin without a file or for `-`.
+fn read_input(file: Option<&Path>) -> Result<Vec<u8>> {
+	match file {
+		Some(file) if file != Path::new("-") =>
+			fs::read(file).with_context(|| format!("failed to read {}", file.display())),
+		_ => {
+			let mut input = Vec::new();
+			io::stdin().read_to_end(&mut input).context("failed to read stdin")?;
+			Ok(input)
+		},
+	}
+}
+
+/// The bytes of the input in the format, detected from the input if not given. Text of hex
+/// digits only is hex, text of base64 characters with at least one that is not a hex digit is
+/// base64 and anything else is raw bytes. Base64 of hex digits only needs the format.
+fn parse_bytes(input: Vec<u8>, format: Option<BinaryFormat>) -> Result<Vec<u8>> {
+	let text = || -> Result<String> {
+		let text = std::str::from_utf8(&input).context("input is not text")?;
+		Ok(text.split_whitespace().collect())
+	};
+	let format = match format {
+		Some(format) => format,
+		None => match text() {
+			Ok(text) => detect(&text),
+			Err(_) => BinaryFormat::Raw,
+		},
+	};
+	match format {
+		BinaryFormat::Raw => Ok(input),
+		BinaryFormat::Hex =>
+			hex::decode(text()?).context("input is not hex, use --format for other formats"),
+		BinaryFormat::Base64 => STANDARD
+			.decode(text()?)
+			.context("input is not base64, use --format for other formats"),
+	}
+}
+
+/// Format of the text by the characters it consists of.
+fn detect(text: &str) -> BinaryFormat {
+	let base64 = |b: u8| b.is_ascii_alphanumeric() || b"+/=".contains(&b);
+	if text.is_empty() {
+		BinaryFormat::Raw
+	} else if text.bytes().all(|b| b.is_ascii_hexdigit()) {
+		BinaryFormat::Hex
+	} else if text.bytes().all(base64) {
+		BinaryFormat::Base64
+	} else {
+		BinaryFormat::Raw
+	}
+}
+
+/// Arguments selecting the schema of the messages.
+#[derive(clap::Args, Debug)]
+pub struct SchemaArgs {
+	/// schema file of the messages
+	#[arg(long, short)]
+	pub schema: PathBuf,
+	/// additional directories searched for included files
+	#[arg(long = "include-path", short = 'I')]
+	pub include_paths: Vec<PathBuf>,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn parse(input: &[u8], format: Option<BinaryFormat>) -> Result<Vec<u8>> {
+		parse_bytes(input.to_vec(), format)
+	}
+
+	#[test]
+	fn test_parse_bytes_detects_format() {
+		assert_eq!(parse(b"0a 0B\n", None).unwrap(), [0x0a, 0x0b]);
+		// hex digits only are hex even if the text is valid base64 too
+		assert_eq!(parse(b"AAAA", None).unwrap(), [0xaa, 0xaa]);
+		assert_eq!(parse(b"AQID", None).unwrap(), [1, 2, 3]);
+		assert_eq!(parse(b"AQ==\n", None).unwrap(), [1]);
+		assert_eq!(parse(b"order, qty", None).unwrap(), b"order, qty");
+		assert_eq!(parse(&[0xff, 0x00, 0x41], None).unwrap(), [0xff, 0x00, 0x41]);
+		assert_eq!(parse(b"", None).unwrap(), b"");
+	}
+
+	#[test]
+	fn test_parse_bytes_reports_invalid_detected_input() {
+		let error = parse(b"abc", None).unwrap_err();
+		assert!(error.to_string().contains("--format"), "{error}");
+		assert!(parse(b"AQI", None).is_err());
+		assert_eq!(parse(b"abc", Some(BinaryFormat::Raw)).unwrap(), b"abc");
+	}
+
+	#[test]
+	fn test_parse_bytes_with_format() {
+		assert_eq!(parse(b"AAAA", Some(BinaryFormat::Base64)).unwrap(), [0, 0, 0]);
+		assert_eq!(parse(b"0a0b", Some(BinaryFormat::Raw)).unwrap(), b"0a0b");
+		assert!(parse(b"AQID", Some(BinaryFormat::Hex)).is_err());
+		assert!(parse(&[0xff], Some(BinaryFormat::Hex)).is_err());
+	}
+}
@@ -0,0 +1,66 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::{command, Parser};
+use sbe_schema::ChangeKind;
+
+use crate::{
+	schema::{load, OutputFormat},
+	term::{info, write, Color},
+};
+
+/// Show semantic differences between two SBE schemas.
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+pub struct DiffArgs {
+	#[arg(long)]
+	pub latest: PathBuf,
+	#[arg(long)]
+	pub current: PathBuf,
+	/// additional directories searched for included files
+	#[arg(long = "include-path", short = 'I')]
+	pub include_paths: Vec<PathBuf>,
+	/// output format of the differences
+	#[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+	pub format: OutputFormat,
+}
+
+pub fn run(args: DiffArgs) -> Result<()> {
+	let latest_schema = load(&args.latest, &args.include_paths)?;
+	let current_schema = load(&args.current, &args.include_paths)?;
+	let diff = sbe_schema::diff(&latest_schema, &current_schema)?;
+
+	if args.format == OutputFormat::Json {
+		println!("{}", serde_json::to_string_pretty(&diff)?);
+		return Ok(());
+	}
+
+	if diff.is_empty() {
+		info("No differences found")?;
+		return Ok(());
+	}
+
+	let width = diff.differences().iter().map(|d| d.path.len()).max().unwrap_or_default();
+	let (mut added, mut removed, mut modified) = (0, 0, 0);
+	for d in diff.differences() {
+		let (marker, color) = match d.kind {
+			ChangeKind::Added => {
+				added += 1;
+				('+', Color::Green)
+			},
+			ChangeKind::Removed => {
+				removed += 1;
+				('-', Color::Red)
+			},
+			_ => {
+				modified += 1;
+				('~', Color::Yellow)
+			},
+		};
+		write(&format!("{marker} {:width$}  {}", d.path, d.description), color)?;
+	}
+
+	info(&format!("{added} added, {removed} removed, {modified} modified"))?;
+
+	Ok(())
+}
@@ -1,21 +1,43 @@
 use std::path::PathBuf;
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use clap::{command, Parser, ValueEnum};
-use sbe_schema::{FullCompatibility, NoneCompatibility, SbeSchemaValidator, Validator};
+use sbe_schema::{
+	BackwardCompatibility, CompatibilityLevel as Level, CompatibilityReport, EvolutionStrategy,
+	ForwardCompatibility, FullCompatibility, NoneCompatibility, SbeSchemaValidator,
+	TransitiveCompatibility, Validator,
+};
 
-use crate::term::info;
+use crate::{
+	schema::{load, load_history, load_revision, OutputFormat},
+	term::info,
+};
 
-/// Check compatibility between two SBE schemas.
+/// Check compatibility between two SBE schemas, or between a schema and its whole history.
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 pub struct CompatibilityArgs {
 	#[arg(long, short)]
 	pub level: CompatibilityLevel,
+	/// latest schema file, transitive levels take every historical file from the oldest
 	#[arg(long)]
-	pub latest: PathBuf,
+	pub latest: Vec<PathBuf>,
 	#[arg(long)]
 	pub current: PathBuf,
+	/// git revision with the latest version of the current schema file, e.g. `origin/main`,
+	/// transitive levels take several revisions from the oldest
+	#[arg(long, value_name = "GIT_REF")]
+	pub against: Vec<String>,
+	/// check against every version of the current schema file committed on the current branch,
+	/// for transitive levels
+	#[arg(long)]
+	pub git_history: bool,
+	/// additional directories searched for included files
+	#[arg(long = "include-path", short = 'I')]
+	pub include_paths: Vec<PathBuf>,
+	/// output format of the compatibility report
+	#[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+	pub format: OutputFormat,
 }
 
 #[derive(Debug, Clone, ValueEnum)]
@@ -24,35 +46,176 @@ pub enum CompatibilityLevel {
 	Forward,
 	Full,
 	None,
+	/// backward compatible with every historical schema
+	BackwardTransitive,
+	/// forward compatible with every historical schema
+	ForwardTransitive,
+	/// fully compatible with every historical schema
+	FullTransitive,
 }
 
 pub fn check(args: CompatibilityArgs) -> Result<()> {
-	match args {
-		CompatibilityArgs { level: CompatibilityLevel::Backward, latest: _, current: _ } => {
-			info("Checking backward compatibility")?;
-		},
-		CompatibilityArgs { level: CompatibilityLevel::Forward, latest: _, current: _ } => {
-			info("Checking forward compatibility")?;
-		},
-		CompatibilityArgs { level: CompatibilityLevel::Full, latest, current } => {
-			info("Checking full compatibility")?;
-			let latest_schema = latest.try_into()?;
-			let current_schema = current.try_into()?;
-			let v = Validator::new(FullCompatibility::<SbeSchemaValidator>::new(
-				SbeSchemaValidator::new(&latest_schema, &current_schema),
-			));
-			v.check()?;
-		},
-		CompatibilityArgs { level: CompatibilityLevel::None, latest, current } => {
-			info("Checking no compatibility")?;
-			let latest_schema = latest.try_into()?;
-			let current_schema = current.try_into()?;
-			let v = Validator::new(NoneCompatibility::<SbeSchemaValidator>::new(
-				SbeSchemaValidator::new(&latest_schema, &current_schema),
-			));
-			v.check()?;
+	let current_schema = load(&args.current, &args.include_paths)?;
+	let mut history = Vec::new();
+	for latest in &args.latest {
+		history.push((latest.display().to_string(), load(latest, &args.include_paths)?));
+	}
+	for revision in &args.against {
+		let schema = load_revision(&args.current, revision, &args.include_paths)?;
+		history.push((revision.clone(), schema));
+	}
+	if args.git_history {
+		history.extend(load_history(&args.current, &args.include_paths)?);
+	}
+
+	let transitive = match args.level {
+		CompatibilityLevel::BackwardTransitive => Some(Level::Backward),
+		CompatibilityLevel::ForwardTransitive => Some(Level::Forward),
+		CompatibilityLevel::FullTransitive => Some(Level::Full),
+		_ => None,
+	};
+	if let Some(level) = transitive {
+		if history.is_empty() {
+			bail!("no historical schema given, use --latest, --against or --git-history");
+		}
+		let strategy = history
+			.iter()
+			.fold(TransitiveCompatibility::new(level, &current_schema), |s, (label, schema)| {
+				s.version(label, schema)
+			});
+		return run_transitive(&strategy, args.format);
+	}
+
+	let [(_, latest_schema)] = history.as_slice() else {
+		bail!("exactly one --latest or --against is required, use a transitive level for more");
+	};
+	let validator = SbeSchemaValidator::new(latest_schema, &current_schema);
+
+	match args.level {
+		CompatibilityLevel::Backward =>
+			run("backward", Validator::new(BackwardCompatibility::new(validator)), args.format),
+		CompatibilityLevel::Forward =>
+			run("forward", Validator::new(ForwardCompatibility::new(validator)), args.format),
+		CompatibilityLevel::Full =>
+			run("full", Validator::new(FullCompatibility::new(validator)), args.format),
+		_ => run("no", Validator::new(NoneCompatibility::new(validator)), args.format),
+	}
+}
+
+fn run_transitive(strategy: &TransitiveCompatibility, format: OutputFormat) -> Result<()> {
+	let report = strategy.versions()?;
+	match format {
+		OutputFormat::Table => {
+			info(&format!(
+				"Checking {:?} compatibility with {} historical schemas",
+				report.required(),
+				report.versions().len()
+			))?;
+			for version in report.versions() {
+				let verdict = if version.compatible { "compatible" } else { "NOT compatible" };
+				info(&format!("\n{} (version {}): {verdict}", version.label, version.version))?;
+				print_table(&version.report)?;
+			}
 		},
+		OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+	}
+
+	strategy.verify(&report.combined())?;
+
+	Ok(())
+}
+
+fn run<E: EvolutionStrategy>(name: &str, v: Validator<E>, format: OutputFormat) -> Result<()> {
+	if format == OutputFormat::Table {
+		info(&format!("Checking {name} compatibility"))?;
+	}
+
+	let report = v.report()?;
+	match format {
+		OutputFormat::Table => print_table(&report)?,
+		OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
 	}
 
+	v.verify(&report)?;
+
 	Ok(())
 }
+
+fn print_table(report: &CompatibilityReport) -> Result<()> {
+	if report.is_empty() {
+		info("No changes found")?;
+	} else {
+		let rows = report
+			.findings()
+			.iter()
+			.map(|f| {
+				[format!("{:?}", f.level), f.kind.to_string(), f.path.clone(), f.reason.clone()]
+			})
+			.collect::<Vec<_>>();
+		let header = ["LEVEL", "CHANGE", "PATH", "REASON"].map(String::from);
+		let mut widths = header.each_ref().map(|h| h.len());
+		for row in &rows {
+			for (width, cell) in widths.iter_mut().zip(row) {
+				*width = (*width).max(cell.len());
+			}
+		}
+
+		for row in std::iter::once(&header).chain(&rows) {
+			let line = row
+				.iter()
+				.zip(widths)
+				.map(|(cell, width)| format!("{cell:width$}"))
+				.collect::<Vec<_>>()
+				.join("  ");
+			info(line.trim_end())?;
+		}
+	}
+
+	info(&format!("Compatibility level: {:?}", report.level()))?;
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::schema::git::tests::{schema, TestRepo, HEADER, SHARED};
+
+	fn args(repo: &TestRepo, level: CompatibilityLevel, against: &[&str]) -> CompatibilityArgs {
+		CompatibilityArgs {
+			level,
+			latest: Vec::new(),
+			current: repo.path("schemas/order.xml"),
+			against: against.iter().map(|r| r.to_string()).collect(),
+			git_history: false,
+			include_paths: vec![repo.path("lib")],
+			format: OutputFormat::Json,
+		}
+	}
+
+	#[test]
+	fn test_check_against_revision() {
+		let repo = TestRepo::new("against");
+		let qty = r#"<field name="qty" id="1" type="Quantity"/>"#;
+		repo.write("schemas/order.xml", &schema(1, qty));
+		repo.write("schemas/common/header.xml", HEADER);
+		repo.write("lib/shared.xml", SHARED);
+		let first = repo.commit("order schema").to_string();
+
+		let price =
+			r#"<field name="price" id="2" type="int64" presence="optional" sinceVersion="2"/>"#;
+		repo.write("schemas/order.xml", &schema(2, &format!("{qty}{price}")));
+		repo.commit("add price");
+		assert!(check(args(&repo, CompatibilityLevel::Backward, &["HEAD"])).is_ok());
+		assert!(check(args(&repo, CompatibilityLevel::Backward, &[&first])).is_ok());
+
+		// removing a field of the committed schema breaks forward compatibility
+		repo.write("schemas/order.xml", &schema(3, price));
+		assert!(check(args(&repo, CompatibilityLevel::Forward, &["HEAD"])).is_err());
+		assert!(
+			check(args(&repo, CompatibilityLevel::ForwardTransitive, &[&first, "HEAD"])).is_err()
+		);
+		// only transitive levels take several revisions
+		assert!(check(args(&repo, CompatibilityLevel::Full, &[&first, "HEAD"])).is_err());
+	}
+}
@@ -0,0 +1,57 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::{bail, Context, Result};
+use clap::{command, Parser};
+use sbe_schema::Schema;
+
+use crate::term::{error, info, write, Color};
+
+/// Rewrite SBE schema files in the canonical style.
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+pub struct FmtArgs {
+	/// schema files to format
+	#[arg(required = true)]
+	pub files: Vec<PathBuf>,
+	/// only check the formatting, fail if a file would be rewritten
+	#[arg(long)]
+	pub check: bool,
+}
+
+pub fn run(args: FmtArgs) -> Result<()> {
+	let mut unformatted = 0;
+	let mut failed = 0;
+	for file in &args.files {
+		let original = fs::read_to_string(file)
+			.with_context(|| format!("failed to read {}", file.display()))?;
+		// files that cannot be formatted without losing content are reported and left untouched
+		let formatted = match Schema::format_xml(&original, Some(file)) {
+			Ok(formatted) => formatted,
+			Err(e) => {
+				failed += 1;
+				error(&e.to_string())?;
+				continue;
+			},
+		};
+		if formatted == original {
+			continue;
+		}
+
+		unformatted += 1;
+		if args.check {
+			write(&format!("{}: not formatted", file.display()), Color::Yellow)?;
+		} else {
+			fs::write(file, formatted)
+				.with_context(|| format!("failed to write {}", file.display()))?;
+			info(&format!("{}: formatted", file.display()))?;
+		}
+	}
+
+	if failed > 0 {
+		bail!("{failed} of {} files cannot be formatted", args.files.len());
+	}
+	if args.check && unformatted > 0 {
+		bail!("{unformatted} of {} files not formatted", args.files.len());
+	}
+	Ok(())
+}
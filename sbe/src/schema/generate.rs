@@ -32,6 +32,20 @@ pub struct GenerateArgs {
 	/// path to the Java executable, default "java" and uses PATH
 	#[arg(long)]
 	pub java: Option<PathBuf>,
+	/// code generator to use
+	#[arg(long, value_enum, default_value_t = Engine::Java)]
+	pub engine: Engine,
+	/// additional directories searched for included files, native engine only
+	#[arg(long = "include-path", short = 'I')]
+	pub include_paths: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Engine {
+	/// the SBE jar, requires java
+	Java,
+	/// the generator built into sbe, generates Rust only
+	Native,
 }
 
 #[derive(Debug, Clone, ValueEnum)]
@@ -47,6 +61,13 @@ const DEFAULT_SBE_OUTPUT_DIR: &str = "generated";
 const DEFAULT_SBE_XINCLUDE_FLAG: bool = true;
 
 pub fn run(args: GenerateArgs) -> Result<()> {
+	if args.engine == Engine::Native {
+		return generate_native(args);
+	}
+	if !args.include_paths.is_empty() {
+		bail!("--include-path is supported by the native engine only");
+	}
+
 	dbg!(&args);
 
 	let mut cmd = Command::new(args.java.unwrap_or("java".into()));
@@ -98,3 +119,19 @@ pub fn run(args: GenerateArgs) -> Result<()> {
 	}
 	Ok(())
 }
+
+/// Generate the Rust module of the schema without java, into `<output_dir>/<package>.rs`.
+fn generate_native(args: GenerateArgs) -> Result<()> {
+	if !matches!(args.language, Language::Rust) {
+		bail!("the native engine generates only Rust code");
+	}
+	let mut schema = super::load(&args.file, &args.include_paths)?;
+	if let Some(package) = args.package {
+		schema.package = Some(package);
+	}
+	let code = sbe_codegen::generate(&schema)?;
+	let output_dir = args.output_dir.unwrap_or(DEFAULT_SBE_OUTPUT_DIR.into());
+	std::fs::create_dir_all(&output_dir)?;
+	std::fs::write(output_dir.join(format!("{}.rs", sbe_codegen::module_name(&schema))), code)?;
+	Ok(())
+}
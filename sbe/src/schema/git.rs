@@ -0,0 +1,329 @@
+//! Reading schemas from a revision of the local git repository.
+
+use std::{
+	collections::BTreeMap,
+	fmt,
+	io::{self, ErrorKind},
+	path::{Path, PathBuf},
+	sync::{Arc, Mutex, PoisonError},
+};
+
+use anyhow::{Context, Result};
+use git2::{ObjectType, Oid, Repository, Sort, Tree};
+use sbe_schema::{normalize_path, FileSource, Schema};
+
+use super::loader;
+
+/// Files looked up in a revision, keyed by their path in the repository, with the id of their
+/// blob or none if the revision has no such file.
+type Lookups = BTreeMap<PathBuf, Option<Oid>>;
+
+/// Reads files from the tree of a revision instead of the working tree, straight from the
+/// object database.
+pub struct GitSource {
+	repo: Mutex<Repository>,
+	tree: Oid,
+	workdir: PathBuf,
+	revision: String,
+	lookups: Arc<Mutex<Lookups>>,
+}
+
+impl GitSource {
+	/// Open the repository containing the file at the given revision, e.g. `origin/main`.
+	pub fn open(path: &Path, revision: &str) -> Result<Self> {
+		let path = resolve(path)?;
+		let dir = path.parent().unwrap_or(&path);
+		let repo = Repository::discover(dir)
+			.with_context(|| format!("{} is not in a git repository", path.display()))?;
+		let workdir =
+			repo.workdir().context("bare repository has no working tree")?.canonicalize()?;
+		let tree = repo
+			.revparse_single(revision)
+			.and_then(|object| object.peel_to_tree())
+			.with_context(|| format!("unknown revision {revision}"))?
+			.id();
+		Ok(Self {
+			repo: Mutex::new(repo),
+			tree,
+			workdir,
+			revision: revision.to_string(),
+			lookups: Arc::default(),
+		})
+	}
+
+	/// Files looked up so far, shared with the source so that they can be inspected after a
+	/// loader took it.
+	fn lookups(&self) -> Arc<Mutex<Lookups>> {
+		Arc::clone(&self.lookups)
+	}
+
+	/// Path of the file relative to the root of the repository.
+	fn entry_path(&self, path: &Path) -> io::Result<PathBuf> {
+		let path = resolve(path)?;
+		path.strip_prefix(&self.workdir).map(Path::to_path_buf).map_err(|_| {
+			io::Error::new(ErrorKind::NotFound, "file is outside of the git repository")
+		})
+	}
+
+	/// Id of the blob of the file in the revision, the lookup is recorded.
+	fn lookup(&self, path: &Path) -> io::Result<Option<Oid>> {
+		let entry_path = self.entry_path(path)?;
+		let repo = self.repo.lock().map_err(|_| io::Error::other("git repository poisoned"))?;
+		let tree = repo.find_tree(self.tree).map_err(io::Error::other)?;
+		let blob = blob_id(&tree, &entry_path);
+		self.lookups
+			.lock()
+			.unwrap_or_else(PoisonError::into_inner)
+			.insert(entry_path, blob);
+		Ok(blob)
+	}
+
+	fn blob(&self, path: &Path) -> io::Result<Vec<u8>> {
+		let not_found = || {
+			io::Error::new(ErrorKind::NotFound, format!("file does not exist in {}", self.revision))
+		};
+		let id = self.lookup(path)?.ok_or_else(not_found)?;
+		let repo = self.repo.lock().map_err(|_| io::Error::other("git repository poisoned"))?;
+		let blob = repo.find_blob(id).map_err(io::Error::other)?;
+		Ok(blob.content().to_vec())
+	}
+}
+
+impl FileSource for GitSource {
+	fn read(&self, path: &Path) -> io::Result<String> {
+		String::from_utf8(self.blob(path)?).map_err(|e| io::Error::new(ErrorKind::InvalidData, e))
+	}
+
+	fn is_file(&self, path: &Path) -> bool {
+		self.lookup(path).is_ok_and(|blob| blob.is_some())
+	}
+
+	fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+		resolve(path)
+	}
+}
+
+impl fmt::Debug for GitSource {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("GitSource")
+			.field("workdir", &self.workdir)
+			.field("revision", &self.revision)
+			.finish()
+	}
+}
+
+/// Schemas committed on the current branch with the commits that changed them, from the
+/// oldest. A commit changes the schema if it changes the file or any file resolved while loading
+/// it, e.g. an included file.
+pub fn schema_history(path: &Path, include_paths: &[PathBuf]) -> Result<Vec<(Oid, Schema)>> {
+	let path = resolve(path)?;
+	let repo = Repository::discover(path.parent().unwrap_or(&path))
+		.with_context(|| format!("{} is not in a git repository", path.display()))?;
+	let workdir = repo.workdir().context("bare repository has no working tree")?.canonicalize()?;
+	let entry_path = path.strip_prefix(&workdir)?;
+
+	let mut walk = repo.revwalk()?;
+	walk.push_head()?;
+	walk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)?;
+	let mut history = Vec::new();
+	let mut previous: Option<Lookups> = None;
+	for oid in walk {
+		let commit = repo.find_commit(oid?)?;
+		let tree = commit.tree()?;
+		if blob_id(&tree, entry_path).is_none() {
+			previous = None;
+			continue;
+		}
+		// the schema is the same if every file looked up while loading it is the same
+		if previous
+			.as_ref()
+			.is_some_and(|lookups| lookups.iter().all(|(file, blob)| blob_id(&tree, file) == *blob))
+		{
+			continue;
+		}
+		let source = GitSource::open(&path, &commit.id().to_string())?;
+		let lookups = source.lookups();
+		let schema = loader(include_paths).source(source).load(&path)?;
+		previous = Some(lookups.lock().unwrap_or_else(PoisonError::into_inner).clone());
+		history.push((commit.id(), schema));
+	}
+	Ok(history)
+}
+
+/// Id of the blob at the path of the tree, none if there is no such file.
+fn blob_id(tree: &Tree, path: &Path) -> Option<Oid> {
+	tree.get_path(path)
+		.ok()
+		.filter(|entry| entry.kind() == Some(ObjectType::Blob))
+		.map(|e| e.id())
+}
+
+/// Absolute path with symlinks of the directories that still exist resolved, so that it can be
+/// compared with the working tree of the repository. The file itself may exist only in the
+/// revision.
+fn resolve(path: &Path) -> io::Result<PathBuf> {
+	let path = normalize_path(path)?;
+	for ancestor in path.ancestors() {
+		if let Ok(canonical) = ancestor.canonicalize() {
+			return Ok(canonical.join(path.strip_prefix(ancestor).unwrap_or(&path)));
+		}
+	}
+	Ok(path)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+	use std::fs;
+
+	use git2::{IndexAddOption, Signature};
+
+	use sbe_schema::PrimitiveType;
+
+	use super::*;
+	use crate::schema::{load_history, load_revision};
+
+	/// Git repository in a temporary directory, removed on drop.
+	pub(crate) struct TestRepo {
+		repo: Repository,
+		dir: PathBuf,
+	}
+
+	impl TestRepo {
+		pub(crate) fn new(name: &str) -> Self {
+			let dir = std::env::temp_dir().join(format!("sbe-git-{name}-{}", std::process::id()));
+			let _ = fs::remove_dir_all(&dir);
+			let repo = Repository::init(&dir).unwrap();
+			Self { repo, dir }
+		}
+
+		/// Absolute path of a file of the working tree.
+		pub(crate) fn path(&self, file: &str) -> PathBuf {
+			self.dir.join(file)
+		}
+
+		pub(crate) fn write(&self, file: &str, content: &str) {
+			let path = self.path(file);
+			fs::create_dir_all(path.parent().unwrap()).unwrap();
+			fs::write(path, content).unwrap();
+		}
+
+		/// Commit every file of the working tree.
+		pub(crate) fn commit(&self, message: &str) -> Oid {
+			let mut index = self.repo.index().unwrap();
+			index.add_all(["*"], IndexAddOption::DEFAULT, None).unwrap();
+			index.update_all(["*"], None).unwrap();
+			index.write().unwrap();
+			let tree = self.repo.find_tree(index.write_tree().unwrap()).unwrap();
+			let signature = Signature::now("sbe", "sbe@example.com").unwrap();
+			let parent = self.repo.head().and_then(|head| head.peel_to_commit()).ok();
+			let parents = parent.iter().collect::<Vec<_>>();
+			self.repo
+				.commit(Some("HEAD"), &signature, &signature, message, &tree, &parents)
+				.unwrap()
+		}
+	}
+
+	impl Drop for TestRepo {
+		fn drop(&mut self) {
+			let _ = fs::remove_dir_all(&self.dir);
+		}
+	}
+
+	/// Schema with the message header in `common/header.xml` and a type found on the include
+	/// path.
+	pub(crate) fn schema(version: u32, fields: &str) -> String {
+		format!(
+			r#"<sbe:messageSchema xmlns:sbe="http://fixprotocol.io/2016/sbe" xmlns:xi="http://www.w3.org/2001/XInclude" id="1" version="{version}">
+				<xi:include href="common/header.xml"/>
+				<xi:include href="shared.xml"/>
+				<sbe:message name="Order" id="1">{fields}</sbe:message>
+			</sbe:messageSchema>"#
+		)
+	}
+
+	pub(crate) const HEADER: &str = r#"<types>
+		<composite name="messageHeader">
+			<type name="blockLength" primitiveType="uint16"/>
+			<type name="templateId" primitiveType="uint16"/>
+			<type name="schemaId" primitiveType="uint16"/>
+			<type name="version" primitiveType="uint16"/>
+		</composite>
+	</types>"#;
+
+	pub(crate) const SHARED: &str =
+		r#"<types><type name="Quantity" primitiveType="int32"/></types>"#;
+
+	#[test]
+	fn test_load_revision_with_includes() {
+		let repo = TestRepo::new("load-revision");
+		repo.write(
+			"schemas/order.xml",
+			&schema(1, r#"<field name="qty" id="1" type="Quantity"/>"#),
+		);
+		repo.write("schemas/common/header.xml", HEADER);
+		repo.write("lib/shared.xml", SHARED);
+		repo.commit("order schema");
+
+		// the working tree differs from the revision, includes are read from the revision too
+		repo.write("schemas/order.xml", &schema(2, ""));
+		fs::remove_file(repo.path("lib/shared.xml")).unwrap();
+
+		// include path relative to the working directory of the process
+		let cwd = std::env::current_dir().unwrap();
+		let up = cwd.components().skip(1).map(|_| "..").collect::<PathBuf>();
+		let lib = up.join(repo.path("lib").strip_prefix("/").unwrap());
+		assert!(lib.is_relative());
+		let schema = load_revision(&repo.path("schemas/order.xml"), "HEAD", &[lib]).unwrap();
+		assert_eq!(schema.version, 1);
+		assert!(schema.message_header().is_some());
+		assert_eq!(schema.types().map(|t| t.name.as_str()).collect::<Vec<_>>(), ["Quantity"]);
+		assert_eq!(schema.messages().next().unwrap().fields().count(), 1);
+
+		let missing = load_revision(&repo.path("schemas/order.xml"), "HEAD", &[]).unwrap_err();
+		assert!(missing.to_string().contains("shared.xml"), "{missing}");
+		assert!(load_revision(&repo.path("schemas/order.xml"), "unknown", &[]).is_err());
+	}
+
+	#[test]
+	fn test_history_follows_included_files() {
+		let repo = TestRepo::new("history");
+		let qty = r#"<field name="qty" id="1" type="Quantity"/>"#;
+		repo.write("schemas/order.xml", &schema(1, qty));
+		repo.write("schemas/common/header.xml", HEADER);
+		repo.write("lib/shared.xml", SHARED);
+		repo.commit("order schema");
+		repo.write("lib/shared.xml", &SHARED.replace("int32", "int64"));
+		let widened = repo.commit("widen the quantity");
+		repo.write("README.md", "orders");
+		repo.commit("unrelated");
+		repo.write("schemas/order.xml", &schema(2, qty));
+		let bumped = repo.commit("bump the version");
+
+		let order = repo.path("schemas/order.xml");
+		let history = schema_history(&order, &[repo.path("lib")]).unwrap();
+		let commits = history.iter().map(|(commit, _)| *commit).collect::<Vec<_>>();
+		assert_eq!(commits.len(), 3);
+		assert_eq!(commits[1..], [widened, bumped]);
+
+		let history = load_history(&order, &[repo.path("lib")]).unwrap();
+		let versions =
+			history.iter().map(|(label, s)| (label.as_str(), s.version)).collect::<Vec<_>>();
+		let label = |commit: Oid| commit.to_string()[..7].to_string();
+		assert_eq!(versions, [(label(widened).as_str(), 1), (label(bumped).as_str(), 2)]);
+		let quantity = history[0].1.types().next().unwrap();
+		assert_eq!(quantity.primitive_type, Some(PrimitiveType::Int64));
+	}
+
+	#[test]
+	fn test_git_source_outside_of_repository() {
+		let repo = TestRepo::new("outside");
+		repo.write("order.xml", HEADER);
+		repo.commit("types");
+
+		let source = GitSource::open(&repo.path("order.xml"), "HEAD").unwrap();
+		assert!(source.is_file(&repo.path("order.xml")));
+		assert!(!source.is_file(&repo.path("missing.xml")));
+		let outside = std::env::temp_dir().join("sbe-git-outside.xml");
+		assert_eq!(source.read(&outside).unwrap_err().kind(), ErrorKind::NotFound);
+	}
+}
@@ -1,12 +1,18 @@
 //!
 //! Schema evolution handling commands
+mod diff;
 mod evolution;
+mod fmt;
 mod generate;
+mod git;
 mod validate;
 
+use std::path::{Path, PathBuf};
+
 use anyhow::Result;
 
-use clap::Subcommand;
+use clap::{Subcommand, ValueEnum};
+use sbe_schema::{Schema, SchemaLoader};
 
 #[derive(Subcommand)]
 pub enum Commands {
@@ -17,6 +23,19 @@ pub enum Commands {
 	Validate(validate::ValidateArgs),
 	/// Generate code from an SBE schema
 	Generate(generate::GenerateArgs),
+	/// Show semantic differences between two SBE schemas
+	Diff(diff::DiffArgs),
+	/// Rewrite SBE schemas in the canonical style
+	Fmt(fmt::FmtArgs),
+}
+
+/// Output format of the reports
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum OutputFormat {
+	/// human readable output
+	Table,
+	/// JSON document, e.g. for CI
+	Json,
 }
 
 pub fn handle(command: Commands) -> Result<()> {
@@ -24,5 +43,38 @@ pub fn handle(command: Commands) -> Result<()> {
 		Commands::Evolution(command) => evolution::handle(command),
 		Commands::Validate(args) => validate::run(args),
 		Commands::Generate(args) => generate::run(args),
+		Commands::Diff(args) => diff::run(args),
+		Commands::Fmt(args) => fmt::run(args),
+	}
+}
+
+/// Loader searching included files also in the given directories.
+fn loader(include_paths: &[PathBuf]) -> SchemaLoader {
+	include_paths.iter().fold(SchemaLoader::new(), |l, p| l.search_path(p))
+}
+
+/// Load the schema file resolving includes, also in the given directories.
+pub fn load(path: &Path, include_paths: &[PathBuf]) -> Result<Schema> {
+	Ok(loader(include_paths).load(path)?)
+}
+
+/// Load the schema file as of the git revision, its includes are read from the same revision.
+pub fn load_revision(path: &Path, revision: &str, include_paths: &[PathBuf]) -> Result<Schema> {
+	let source = git::GitSource::open(path, revision)?;
+	Ok(loader(include_paths).source(source).load(path)?)
+}
+
+/// Load every version of the schema file committed on the current branch, from the oldest. Each
+/// schema version is represented by the last commit that changed the schema with that version,
+/// changes of included files count too.
+pub fn load_history(path: &Path, include_paths: &[PathBuf]) -> Result<Vec<(String, Schema)>> {
+	let mut history: Vec<(String, Schema)> = Vec::new();
+	for (commit, schema) in git::schema_history(path, include_paths)? {
+		let label = commit.to_string()[..7].to_string();
+		match history.last_mut() {
+			Some(last) if last.1.version == schema.version => *last = (label, schema),
+			_ => history.push((label, schema)),
+		}
 	}
+	Ok(history)
 }
@@ -1,7 +1,13 @@
 use std::path::PathBuf;
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use clap::{command, Parser};
+use sbe_schema::Severity;
+
+use crate::{
+	schema::{load, OutputFormat},
+	term::{info, write, Color},
+};
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -9,8 +15,42 @@ pub struct ValidateArgs {
 	/// schema file to validate
 	#[arg(long, short)]
 	pub file: PathBuf,
+	/// additional directories searched for included files
+	#[arg(long = "include-path", short = 'I')]
+	pub include_paths: Vec<PathBuf>,
+	/// output format of the diagnostics
+	#[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+	pub format: OutputFormat,
 }
 
-pub fn run(_args: ValidateArgs) -> Result<()> {
+pub fn run(args: ValidateArgs) -> Result<()> {
+	let schema = load(&args.file, &args.include_paths)?;
+	let diagnostics = sbe_schema::validate(&schema);
+
+	match args.format {
+		OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&diagnostics)?),
+		OutputFormat::Table =>
+			for d in diagnostics.diagnostics() {
+				let color = match d.severity {
+					Severity::Error => Color::Red,
+					Severity::Warning => Color::Yellow,
+				};
+				write(&format!("{}[{}] {}: {}", d.severity, d.rule, d.path, d.message), color)?;
+			},
+	}
+
+	let errors = diagnostics
+		.diagnostics()
+		.iter()
+		.filter(|d| d.severity == Severity::Error)
+		.count();
+	let warnings = diagnostics.diagnostics().len() - errors;
+	if errors > 0 {
+		bail!("{}: {errors} errors, {warnings} warnings", args.file.display());
+	}
+	if args.format == OutputFormat::Table {
+		info(&format!("{}: valid, {warnings} warnings", args.file.display()))?;
+	}
+
 	Ok(())
 }
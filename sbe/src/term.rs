@@ -1,25 +1,34 @@
 //! Functions relating to printing different levels of info to the terminal.
 
-use std::io::{self, Write};
+use std::io::{self, IsTerminal, Write};
 
 use termcolor::{ColorChoice, ColorSpec, StandardStream, WriteColor};
 
 pub use termcolor::Color;
 
+/// Use colors only when writing to a terminal, so that piped output, e.g. JSON, stays clean
+fn color_choice() -> ColorChoice {
+	if io::stdout().is_terminal() {
+		ColorChoice::Always
+	} else {
+		ColorChoice::Never
+	}
+}
+
 /// Reset stdout
 pub fn reset() -> io::Result<()> {
-	let mut stdout = StandardStream::stdout(ColorChoice::Always);
+	let mut stdout = StandardStream::stdout(color_choice());
 	stdout.reset()?;
 	Ok(())
 }
 
 /// Write a message
-// pub fn write(text: &str, color: Color) -> io::Result<()> {
-//     let mut stdout = StandardStream::stdout(ColorChoice::Always);
-//     stdout.set_color(ColorSpec::default().set_fg(Some(color)))?;
-//     writeln!(&mut stdout, "{text}")?;
-//     Ok(())
-// }
+pub fn write(text: &str, color: Color) -> io::Result<()> {
+	let mut stdout = StandardStream::stdout(color_choice());
+	stdout.set_color(ColorSpec::default().set_fg(Some(color)))?;
+	writeln!(&mut stdout, "{text}")?;
+	Ok(())
+}
 
 /// Write a warning message
 // pub fn warn(text: &str) -> io::Result<()> {
@@ -39,7 +48,7 @@ pub fn reset() -> io::Result<()> {
 
 /// Write a info message
 pub fn info(text: &str) -> io::Result<()> {
-	let mut stdout = StandardStream::stdout(ColorChoice::Always);
+	let mut stdout = StandardStream::stdout(color_choice());
 	stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
 	writeln!(&mut stdout, "{text}")?;
 	Ok(())
@@ -55,7 +64,7 @@ pub fn info(text: &str) -> io::Result<()> {
 
 /// Write an error message
 pub fn error(text: &str) -> io::Result<()> {
-	let mut stdout = StandardStream::stdout(ColorChoice::Always);
+	let mut stdout = StandardStream::stdout(color_choice());
 	stdout.set_color(ColorSpec::new().set_fg(Some(Color::Red)))?;
 	writeln!(&mut stdout, "{text}")?;
 	Ok(())